@@ -0,0 +1,164 @@
+//! Typed errors shared by the server functions and their callers.
+//!
+//! Server functions still return `ServerFnError` on the wire; an `ApiError`
+//! is carried as its HTTP status code and message, and recovered on the
+//! client with `ApiError::from(&ServerFnError)`.
+
+use dioxus::prelude::ServerFnError;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ApiError {
+    NotFound(String),
+    PermissionDenied(String),
+    /// The path resolves outside of the sandbox root.
+    OutsideSandbox(String),
+    InvalidInput(String),
+    /// The target already exists or was changed concurrently.
+    Conflict(String),
+    /// The operation isn't available on this host or build.
+    Unsupported(String),
+    Internal(String),
+}
+
+impl ApiError {
+    pub fn message(&self) -> &str {
+        match self {
+            ApiError::NotFound(message)
+            | ApiError::PermissionDenied(message)
+            | ApiError::OutsideSandbox(message)
+            | ApiError::InvalidInput(message)
+            | ApiError::Conflict(message)
+            | ApiError::Unsupported(message)
+            | ApiError::Internal(message) => message,
+        }
+    }
+
+    /// HTTP status code the error travels as.
+    pub fn status(&self) -> u16 {
+        match self {
+            ApiError::NotFound(_) => 404,
+            ApiError::PermissionDenied(_) => 403,
+            ApiError::OutsideSandbox(_) => 451,
+            ApiError::InvalidInput(_) => 400,
+            ApiError::Conflict(_) => 409,
+            ApiError::Unsupported(_) => 501,
+            ApiError::Internal(_) => 500,
+        }
+    }
+
+    /// Maps an I/O error on `path` onto the matching variant.
+    pub fn io(path: &str, err: std::io::Error) -> Self {
+        use std::io::ErrorKind;
+
+        let message = format!("{path}: {err}");
+        match err.kind() {
+            ErrorKind::NotFound => ApiError::NotFound(message),
+            ErrorKind::PermissionDenied => ApiError::PermissionDenied(message),
+            ErrorKind::AlreadyExists => ApiError::Conflict(message),
+            ErrorKind::InvalidInput | ErrorKind::InvalidData => ApiError::InvalidInput(message),
+            ErrorKind::Unsupported => ApiError::Unsupported(message),
+            _ => ApiError::Internal(message),
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<ApiError> for ServerFnError {
+    fn from(err: ApiError) -> Self {
+        ServerFnError::ServerError {
+            code: err.status(),
+            message: err.message().to_string(),
+            details: None,
+        }
+    }
+}
+
+impl From<&ServerFnError> for ApiError {
+    fn from(err: &ServerFnError) -> Self {
+        match err {
+            ServerFnError::ServerError { code, message, .. } => {
+                let message = message.clone();
+                match code {
+                    404 => ApiError::NotFound(message),
+                    403 => ApiError::PermissionDenied(message),
+                    451 => ApiError::OutsideSandbox(message),
+                    400 => ApiError::InvalidInput(message),
+                    409 => ApiError::Conflict(message),
+                    501 => ApiError::Unsupported(message),
+                    _ => ApiError::Internal(message),
+                }
+            }
+            other => ApiError::Internal(other.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Error, ErrorKind};
+
+    fn every_variant() -> Vec<ApiError> {
+        vec![
+            ApiError::NotFound("a".to_string()),
+            ApiError::PermissionDenied("b".to_string()),
+            ApiError::OutsideSandbox("c".to_string()),
+            ApiError::InvalidInput("d".to_string()),
+            ApiError::Conflict("e".to_string()),
+            ApiError::Unsupported("f".to_string()),
+            ApiError::Internal("g".to_string()),
+        ]
+    }
+
+    #[test]
+    fn every_variant_survives_the_trip_as_a_status_code() {
+        let statuses: Vec<u16> = every_variant().iter().map(ApiError::status).collect();
+        assert_eq!(statuses, [404, 403, 451, 400, 409, 501, 500]);
+        for err in every_variant() {
+            let sent = ServerFnError::from(err.clone());
+            assert_eq!(ApiError::from(&sent), err);
+        }
+    }
+
+    #[test]
+    fn unknown_errors_come_back_as_internal() {
+        let teapot = ServerFnError::ServerError {
+            code: 418,
+            message: "short and stout".to_string(),
+            details: None,
+        };
+        assert_eq!(
+            ApiError::from(&teapot),
+            ApiError::Internal("short and stout".to_string())
+        );
+        let garbled = ServerFnError::Deserialization("expected a string".to_string());
+        assert!(matches!(ApiError::from(&garbled), ApiError::Internal(_)));
+    }
+
+    #[test]
+    fn io_errors_map_by_kind() {
+        let io = |kind| ApiError::io("/a", Error::new(kind, "oops"));
+        assert_eq!(
+            io(ErrorKind::NotFound),
+            ApiError::NotFound("/a: oops".to_string())
+        );
+        for (kind, status) in [
+            (ErrorKind::PermissionDenied, 403),
+            (ErrorKind::AlreadyExists, 409),
+            (ErrorKind::InvalidInput, 400),
+            (ErrorKind::InvalidData, 400),
+            (ErrorKind::Unsupported, 501),
+            (ErrorKind::Interrupted, 500),
+        ] {
+            assert_eq!(io(kind).status(), status, "{kind:?}");
+        }
+    }
+}
@@ -0,0 +1,2975 @@
+//! File-system server functions. All paths are resolved through the sandbox.
+
+use dioxus::fullstack::{ByteStream, JsonStream};
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "server")]
+use crate::{sandbox, ApiError};
+#[cfg(feature = "server")]
+use dioxus::fullstack::body::Bytes;
+#[cfg(feature = "server")]
+use futures::SinkExt;
+
+/// Items a streaming server function queues before it waits for the client
+/// to catch up.
+#[cfg(feature = "server")]
+const STREAM_BUFFER: usize = 8;
+
+/// Runs `work`, which blocks on the file system or a child process, off the
+/// async workers.
+#[cfg(feature = "server")]
+async fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T, ApiError> + Send + 'static,
+) -> Result<T, ApiError> {
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|err| ApiError::Internal(err.to_string()))?
+}
+
+/// Largest source image, per axis, `thumbnail` agrees to decode. Guards
+/// against decompression bombs that are tiny on disk but huge in memory.
+#[cfg(feature = "server")]
+const MAX_SOURCE_DIM: u32 = 16_384;
+
+/// Number of thumbnails kept in memory. Past it, the least recently used
+/// one is dropped.
+#[cfg(feature = "server")]
+const THUMBNAIL_CACHE_CAPACITY: usize = 512;
+
+#[cfg(feature = "server")]
+type ThumbnailKey = (std::path::PathBuf, std::time::SystemTime, u64, u32);
+
+/// A cached thumbnail and when it was last used.
+#[cfg(feature = "server")]
+type ThumbnailEntry = (Vec<u8>, std::time::Instant);
+
+#[cfg(feature = "server")]
+static THUMBNAILS: std::sync::LazyLock<
+    std::sync::Mutex<std::collections::HashMap<ThumbnailKey, ThumbnailEntry>>,
+> = std::sync::LazyLock::new(Default::default);
+
+/// Scales `(width, height)` down to fit in a `max_dim` square, preserving the
+/// aspect ratio. Images that already fit are left as is.
+pub fn fit_within(width: u32, height: u32, max_dim: u32) -> (u32, u32) {
+    if width <= max_dim && height <= max_dim {
+        return (width, height);
+    }
+    let scale = max_dim as f64 / width.max(height) as f64;
+    (
+        ((width as f64 * scale).round() as u32).max(1),
+        ((height as f64 * scale).round() as u32).max(1),
+    )
+}
+
+/// Returns a PNG thumbnail of the image at `path`, no larger than `max_dim`
+/// on either side.
+///
+/// Thumbnails are cached by path, modification time and size, so an edited
+/// image gets a fresh thumbnail.
+#[post("/api/thumbnail")]
+pub async fn thumbnail(path: String, max_dim: u32) -> Result<Vec<u8>, ServerFnError> {
+    let resolved = sandbox::resolve(&path)?;
+    Ok(blocking(move || cached_thumbnail(&resolved, &path, max_dim, render_thumbnail)).await?)
+}
+
+/// The thumbnail of the image at `resolved` from the cache, or from `render`
+/// (given the same arguments) on a miss.
+#[cfg(feature = "server")]
+fn cached_thumbnail(
+    resolved: &std::path::Path,
+    path: &str,
+    max_dim: u32,
+    render: impl FnOnce(&std::path::Path, &str, u32) -> Result<Vec<u8>, ApiError>,
+) -> Result<Vec<u8>, ApiError> {
+    use std::time::Instant;
+
+    let metadata = std::fs::metadata(resolved).map_err(|err| ApiError::io(path, err))?;
+    if !metadata.is_file() {
+        return Err(ApiError::InvalidInput(format!("{path}: not a file")));
+    }
+    let modified = metadata.modified().map_err(|err| ApiError::io(path, err))?;
+
+    let key = (resolved.to_path_buf(), modified, metadata.len(), max_dim);
+    if let Some((bytes, used)) = THUMBNAILS.lock().unwrap().get_mut(&key) {
+        *used = Instant::now();
+        return Ok(bytes.clone());
+    }
+
+    let bytes = render(resolved, path, max_dim)?;
+
+    let mut cache = THUMBNAILS.lock().unwrap();
+    if cache.len() >= THUMBNAIL_CACHE_CAPACITY {
+        let oldest = cache
+            .iter()
+            .min_by_key(|(_, (_, used))| *used)
+            .map(|(key, _)| key.clone());
+        if let Some(oldest) = oldest {
+            cache.remove(&oldest);
+        }
+    }
+    cache.insert(key, (bytes.clone(), Instant::now()));
+
+    Ok(bytes)
+}
+
+/// Decodes the image at `resolved` and encodes it, scaled to fit within
+/// `max_dim`, as a PNG.
+#[cfg(feature = "server")]
+fn render_thumbnail(
+    resolved: &std::path::Path,
+    path: &str,
+    max_dim: u32,
+) -> Result<Vec<u8>, ApiError> {
+    use image::{ImageFormat, ImageReader, Limits};
+    use std::io::Cursor;
+
+    let mut reader = ImageReader::open(resolved)
+        .and_then(|reader| reader.with_guessed_format())
+        .map_err(|err| ApiError::io(path, err))?;
+    if reader.format().is_none() {
+        return Err(ApiError::InvalidInput(format!("{path}: not an image")));
+    }
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_SOURCE_DIM);
+    limits.max_image_height = Some(MAX_SOURCE_DIM);
+    reader.limits(limits);
+
+    let image = reader
+        .decode()
+        .map_err(|err| ApiError::InvalidInput(format!("{path}: {err}")))?;
+    let (width, height) = fit_within(image.width(), image.height(), max_dim.max(1));
+    let thumbnail = image.thumbnail_exact(width, height);
+
+    let mut bytes = Vec::new();
+    thumbnail
+        .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+        .map_err(|err| ApiError::Internal(format!("{path}: {err}")))?;
+    Ok(bytes)
+}
+
+/// Most frames `video_thumbnails` extracts from one video.
+pub const MAX_VIDEO_FRAMES: usize = 32;
+
+/// Width, in pixels, of the frames `video_thumbnails` returns.
+#[cfg(all(feature = "server", feature = "video"))]
+const VIDEO_FRAME_WIDTH: u32 = 320;
+
+/// Number of frame strips kept in memory before the cache is flushed.
+#[cfg(feature = "server")]
+const VIDEO_CACHE_CAPACITY: usize = 64;
+
+#[cfg(feature = "server")]
+type VideoKey = (std::path::PathBuf, std::time::SystemTime, u64, usize);
+
+#[cfg(feature = "server")]
+static VIDEO_THUMBNAILS: std::sync::LazyLock<
+    std::sync::Mutex<std::collections::HashMap<VideoKey, Vec<Vec<u8>>>>,
+> = std::sync::LazyLock::new(Default::default);
+
+/// Timestamps, in seconds, of `count` frames spread evenly over a video
+/// lasting `duration` seconds.
+///
+/// Each is the middle of one of `count` equal parts of the video, so the
+/// often black first and last frames are never picked.
+pub fn frame_times(duration: f64, count: usize) -> Vec<f64> {
+    if count == 0 || !duration.is_finite() || duration <= 0.0 {
+        return Vec::new();
+    }
+    let step = duration / count as f64;
+    (0..count).map(|i| (i as f64 + 0.5) * step).collect()
+}
+
+/// Runs `program` with `args`, returning its standard output. A missing
+/// `program` is reported as `Unsupported`, a failing one as invalid input
+/// on `path`.
+#[cfg(all(feature = "server", feature = "video"))]
+fn run_ffmpeg_tool(
+    program: &str,
+    args: &[&std::ffi::OsStr],
+    path: &str,
+) -> Result<Vec<u8>, ApiError> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|err| match err.kind() {
+            std::io::ErrorKind::NotFound => {
+                ApiError::Unsupported(format!("{program} isn't installed on this server"))
+            }
+            _ => ApiError::Internal(format!("{program}: {err}")),
+        })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(ApiError::InvalidInput(format!("{path}: {}", stderr.trim())));
+    }
+    Ok(output.stdout)
+}
+
+/// Length of the video at `resolved`, in seconds, as reported by `ffprobe`.
+#[cfg(all(feature = "server", feature = "video"))]
+fn video_duration(resolved: &std::path::Path, path: &str) -> Result<f64, ApiError> {
+    let stdout = run_ffmpeg_tool(
+        "ffprobe",
+        &[
+            "-v".as_ref(),
+            "error".as_ref(),
+            "-show_entries".as_ref(),
+            "format=duration".as_ref(),
+            "-of".as_ref(),
+            "default=noprint_wrappers=1:nokey=1".as_ref(),
+            resolved.as_os_str(),
+        ],
+        path,
+    )?;
+    String::from_utf8_lossy(&stdout)
+        .trim()
+        .parse()
+        .map_err(|_| ApiError::InvalidInput(format!("{path}: not a video")))
+}
+
+/// The frame shown `at` seconds into the video at `resolved`, as a JPEG
+/// scaled to [`VIDEO_FRAME_WIDTH`].
+#[cfg(all(feature = "server", feature = "video"))]
+fn video_frame(resolved: &std::path::Path, path: &str, at: f64) -> Result<Vec<u8>, ApiError> {
+    let at = format!("{at:.3}");
+    let scale = format!("scale={VIDEO_FRAME_WIDTH}:-2");
+    run_ffmpeg_tool(
+        "ffmpeg",
+        &[
+            "-v".as_ref(),
+            "error".as_ref(),
+            "-ss".as_ref(),
+            at.as_ref(),
+            "-i".as_ref(),
+            resolved.as_os_str(),
+            "-frames:v".as_ref(),
+            "1".as_ref(),
+            "-vf".as_ref(),
+            scale.as_ref(),
+            "-f".as_ref(),
+            "image2pipe".as_ref(),
+            "-vcodec".as_ref(),
+            "mjpeg".as_ref(),
+            "-".as_ref(),
+        ],
+        path,
+    )
+}
+
+/// `count` frames spread evenly over the video at `resolved`.
+#[cfg(all(feature = "server", feature = "video"))]
+fn video_frames(
+    resolved: &std::path::Path,
+    path: &str,
+    count: usize,
+) -> Result<Vec<Vec<u8>>, ApiError> {
+    let duration = video_duration(resolved, path)?;
+    frame_times(duration, count)
+        .into_iter()
+        .map(|at| video_frame(resolved, path, at))
+        .collect()
+}
+
+#[cfg(all(feature = "server", not(feature = "video")))]
+fn video_frames(
+    _resolved: &std::path::Path,
+    _path: &str,
+    _count: usize,
+) -> Result<Vec<Vec<u8>>, ApiError> {
+    Err(ApiError::Unsupported(
+        "video thumbnails aren't enabled on this server".into(),
+    ))
+}
+
+/// Returns up to `count` (at most [`MAX_VIDEO_FRAMES`]) JPEG frames taken at
+/// evenly spaced points of the video at `path` (see [`frame_times`]), for
+/// scrubbing previews in a media browser.
+///
+/// Frames are extracted with `ffmpeg`, which needs the `video` feature and
+/// the `ffmpeg` and `ffprobe` binaries on the server; without them this
+/// returns `Unsupported`. Strips are cached like [`thumbnail`]s.
+#[post("/api/video_thumbnails")]
+pub async fn video_thumbnails(path: String, count: usize) -> Result<Vec<Vec<u8>>, ServerFnError> {
+    let resolved = sandbox::resolve(&path)?;
+    let count = count.min(MAX_VIDEO_FRAMES);
+    Ok(blocking(move || cached_video_frames(&resolved, &path, count, video_frames)).await?)
+}
+
+/// `count` frames of the video at `resolved` from the cache, or from
+/// `extract` (given the same arguments) on a miss.
+#[cfg(feature = "server")]
+fn cached_video_frames(
+    resolved: &std::path::Path,
+    path: &str,
+    count: usize,
+    extract: impl FnOnce(&std::path::Path, &str, usize) -> Result<Vec<Vec<u8>>, ApiError>,
+) -> Result<Vec<Vec<u8>>, ApiError> {
+    let metadata = std::fs::metadata(resolved).map_err(|err| ApiError::io(path, err))?;
+    if !metadata.is_file() {
+        return Err(ApiError::InvalidInput(format!("{path}: not a file")));
+    }
+    let modified = metadata.modified().map_err(|err| ApiError::io(path, err))?;
+
+    let key = (resolved.to_path_buf(), modified, metadata.len(), count);
+    if let Some(frames) = VIDEO_THUMBNAILS.lock().unwrap().get(&key) {
+        return Ok(frames.clone());
+    }
+
+    let frames = extract(resolved, path, count)?;
+
+    let mut cache = VIDEO_THUMBNAILS.lock().unwrap();
+    if cache.len() >= VIDEO_CACHE_CAPACITY {
+        cache.clear();
+    }
+    cache.insert(key, frames.clone());
+
+    Ok(frames)
+}
+
+/// Dimensions, format and EXIF details of an image. EXIF fields are `None`
+/// when the image doesn't carry them.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageInfo {
+    pub width: u32,
+    pub height: u32,
+    /// MIME type, e.g. `image/jpeg`.
+    pub format: String,
+    /// EXIF orientation, from 1 (upright) to 8.
+    pub orientation: Option<u16>,
+    /// When the photo was taken, as `YYYY:MM:DD HH:MM:SS` in the camera's
+    /// local time.
+    pub taken_at: Option<String>,
+    /// Camera make and model.
+    pub camera: Option<String>,
+}
+
+/// First string of an ASCII EXIF `tag` in the primary image, trimmed.
+#[cfg(feature = "server")]
+fn exif_text(exif: &exif::Exif, tag: exif::Tag) -> Option<String> {
+    match &exif.get_field(tag, exif::In::PRIMARY)?.value {
+        exif::Value::Ascii(values) => {
+            let text = String::from_utf8_lossy(values.first()?);
+            let text = text.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+            (!text.is_empty()).then(|| text.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Returns the dimensions, format and EXIF details of the image at `path`.
+/// Only the image's headers are read; its pixels are never decoded.
+#[post("/api/image_info")]
+pub async fn image_info(path: String) -> Result<ImageInfo, ServerFnError> {
+    let resolved = sandbox::resolve(&path)?;
+    Ok(blocking(move || read_image_info(&resolved, &path)).await?)
+}
+
+/// [`image_info`] of the image at `resolved`, which the client calls `path`.
+#[cfg(feature = "server")]
+fn read_image_info(resolved: &std::path::Path, path: &str) -> Result<ImageInfo, ApiError> {
+    use exif::Tag;
+    use image::{ImageDecoder, ImageReader};
+
+    let reader = ImageReader::open(resolved)
+        .and_then(|reader| reader.with_guessed_format())
+        .map_err(|err| ApiError::io(path, err))?;
+    let Some(format) = reader.format() else {
+        return Err(ApiError::InvalidInput(format!("{path}: not an image")));
+    };
+    let mut decoder = reader
+        .into_decoder()
+        .map_err(|err| ApiError::InvalidInput(format!("{path}: {err}")))?;
+    let (width, height) = decoder.dimensions();
+
+    // Unreadable EXIF is treated like missing EXIF: the image itself is fine.
+    let exif = decoder
+        .exif_metadata()
+        .ok()
+        .flatten()
+        .and_then(|raw| exif::Reader::new().read_raw(raw).ok());
+    let orientation = exif.as_ref().and_then(|exif| {
+        let field = exif.get_field(Tag::Orientation, exif::In::PRIMARY)?;
+        u16::try_from(field.value.get_uint(0)?).ok()
+    });
+    let taken_at = exif.as_ref().and_then(|exif| {
+        exif_text(exif, Tag::DateTimeOriginal).or_else(|| exif_text(exif, Tag::DateTime))
+    });
+    let camera = exif.as_ref().and_then(|exif| {
+        let make = exif_text(exif, Tag::Make);
+        let model = exif_text(exif, Tag::Model);
+        match (make, model) {
+            // Models usually repeat the make, e.g. "Canon" / "Canon EOS R6".
+            (Some(make), Some(model)) if model.starts_with(&make) => Some(model),
+            (Some(make), Some(model)) => Some(format!("{make} {model}")),
+            (make, model) => make.or(model),
+        }
+    });
+
+    Ok(ImageInfo {
+        width,
+        height,
+        format: format.to_mime_type().to_string(),
+        orientation,
+        taken_at,
+        camera,
+    })
+}
+
+/// Largest slice `read_range` returns in one call; longer requests are
+/// truncated to it.
+pub const MAX_READ_RANGE: u64 = 8 * 1024 * 1024;
+
+/// Reads up to `length` bytes of the file at `path`, starting at `offset`.
+///
+/// Fewer bytes are returned when the range runs past the end of the file, and
+/// `length` is capped at [`MAX_READ_RANGE`]. An `offset` past the end of the
+/// file is rejected.
+#[post("/api/read_range")]
+pub async fn read_range(path: String, offset: u64, length: u64) -> Result<Vec<u8>, ServerFnError> {
+    let resolved = sandbox::resolve(&path)?;
+    Ok(read_file_range(&resolved, &path, offset, length)?)
+}
+
+/// Bytes of the file `resolved`, which the client calls `path`, as read by
+/// [`read_range`].
+#[cfg(feature = "server")]
+fn read_file_range(
+    resolved: &std::path::Path,
+    path: &str,
+    offset: u64,
+    length: u64,
+) -> Result<Vec<u8>, ApiError> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = std::fs::File::open(resolved).map_err(|err| ApiError::io(path, err))?;
+    let metadata = file.metadata().map_err(|err| ApiError::io(path, err))?;
+    if !metadata.is_file() {
+        return Err(ApiError::InvalidInput(format!("{path}: not a file")));
+    }
+    if offset > metadata.len() {
+        return Err(ApiError::InvalidInput(format!(
+            "{path}: offset {offset} is past the end of the file ({} bytes)",
+            metadata.len()
+        )));
+    }
+
+    let length = length.min(MAX_READ_RANGE).min(metadata.len() - offset);
+    let mut bytes = Vec::with_capacity(length as usize);
+    file.seek(SeekFrom::Start(offset))
+        .and_then(|_| file.take(length).read_to_end(&mut bytes))
+        .map_err(|err| ApiError::io(path, err))?;
+
+    Ok(bytes)
+}
+
+/// Set to `0`/`false` to make `append_file` fail on missing files instead of
+/// creating them.
+#[cfg(feature = "server")]
+const APPEND_CREATE_ENV: &str = "JADEOS_APPEND_CREATE";
+
+/// Serializes appends so concurrent calls can't interleave within one call's
+/// data, even when the OS splits a write.
+#[cfg(feature = "server")]
+static APPEND_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// Appends `data` to the file at `path`, creating it unless disabled through
+/// `JADEOS_APPEND_CREATE`.
+///
+/// Unlike a read-modify-write through `read_range`, this never loses data
+/// appended concurrently by another caller.
+#[post("/api/append_file")]
+pub async fn append_file(path: String, data: String) -> Result<(), ServerFnError> {
+    let create = std::env::var(APPEND_CREATE_ENV)
+        .map(|value| !matches!(value.as_str(), "0" | "false"))
+        .unwrap_or(true);
+
+    let resolved = sandbox::resolve(&path)?;
+    Ok(append_to(&resolved, &path, data.as_bytes(), create)?)
+}
+
+/// Appends `data` to the file `resolved`, which the client calls `path`,
+/// creating it if `create` is set.
+#[cfg(feature = "server")]
+fn append_to(
+    resolved: &std::path::Path,
+    path: &str,
+    data: &[u8],
+    create: bool,
+) -> Result<(), ApiError> {
+    use std::io::Write;
+
+    let _guard = APPEND_LOCK.lock().unwrap();
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .create(create)
+        .open(resolved)
+        .map_err(|err| ApiError::io(path, err))?;
+    file.write_all(data).map_err(|err| ApiError::io(path, err))
+}
+
+/// Confines the calling client's session to the directory `path`: from
+/// then on, its paths are resolved against it instead, and nothing above it
+/// can be reached. `path` is itself resolved against the session's current
+/// root, so a session can only narrow its root.
+#[post("/api/set_root")]
+pub async fn set_root(path: String) -> Result<(), ServerFnError> {
+    sandbox::set_session_root(&path)?;
+    Ok(())
+}
+
+/// What `move_path`/`copy_path` do when the destination already exists.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConflictPolicy {
+    /// Fail with `ApiError::Conflict`, so the caller can ask the user.
+    #[default]
+    Fail,
+    /// Delete the existing entry first.
+    Replace,
+    /// Pick a free name like `report (2).txt`.
+    KeepBoth,
+    /// Leave the existing entry and don't transfer this one.
+    Skip,
+}
+
+/// Returns `name` with ` (n)` inserted before its extension, e.g.
+/// `report (2).txt`.
+pub fn numbered_name(name: &str, n: usize) -> String {
+    match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => format!("{stem} ({n}).{ext}"),
+        _ => format!("{name} ({n})"),
+    }
+}
+
+/// Name of the `n`th duplicate of `name`: `report copy.txt` for the first,
+/// then `report copy 2.txt` and so on. Directory names have no extension.
+pub fn copy_name(name: &str, n: usize, is_dir: bool) -> String {
+    let suffix = match n {
+        0 | 1 => " copy".to_string(),
+        n => format!(" copy {n}"),
+    };
+    match name.rsplit_once('.') {
+        Some((stem, ext)) if !is_dir && !stem.is_empty() => format!("{stem}{suffix}.{ext}"),
+        _ => format!("{name}{suffix}"),
+    }
+}
+
+/// Copies a file, or a directory and everything below it, to `to`.
+/// Symlinks are not followed but copied as links to the same target; other
+/// special files are skipped.
+#[cfg(feature = "server")]
+pub(crate) fn copy_recursive(from: &std::path::Path, to: &std::path::Path) -> std::io::Result<()> {
+    let metadata = std::fs::symlink_metadata(from)?;
+    if metadata.is_dir() {
+        std::fs::create_dir(to)?;
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else if metadata.is_file() {
+        std::fs::copy(from, to).map(|_| ())
+    } else if metadata.is_symlink() {
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(std::fs::read_link(from)?, to)
+        }
+        #[cfg(not(unix))]
+        {
+            Err(std::io::ErrorKind::Unsupported.into())
+        }
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "server")]
+fn remove_entry(path: &std::path::Path) -> std::io::Result<()> {
+    if std::fs::symlink_metadata(path)?.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+}
+
+/// Where `source` lands inside the directory `dir` under `policy`, or `None`
+/// when it should be skipped. `from` and `to_dir` are their client forms for
+/// error messages.
+#[cfg(feature = "server")]
+fn transfer_target_in(
+    source: std::path::PathBuf,
+    dir: std::path::PathBuf,
+    from: &str,
+    to_dir: &str,
+    policy: ConflictPolicy,
+) -> Result<Option<(std::path::PathBuf, std::path::PathBuf)>, ApiError> {
+    if !dir.is_dir() {
+        return Err(ApiError::InvalidInput(format!("{to_dir}: not a directory")));
+    }
+    let name = source
+        .file_name()
+        .ok_or_else(|| ApiError::InvalidInput(format!("{from}: can't transfer the root")))?
+        .to_string_lossy()
+        .into_owned();
+    if dir.starts_with(&source) {
+        return Err(ApiError::InvalidInput(format!(
+            "{from}: can't transfer into itself"
+        )));
+    }
+
+    let mut target = dir.join(&name);
+    // Transferring an entry onto itself: only keeping both makes sense, and
+    // replacing would delete it.
+    if target == source && policy != ConflictPolicy::KeepBoth {
+        return Ok(None);
+    }
+    if std::fs::symlink_metadata(&target).is_ok() {
+        match policy {
+            ConflictPolicy::Fail => {
+                return Err(ApiError::Conflict(format!(
+                    "{to_dir}/{name} already exists"
+                )))
+            }
+            ConflictPolicy::Skip => return Ok(None),
+            // Moving `/a/x/x` into `/a`: the entry to replace holds the
+            // source.
+            ConflictPolicy::Replace if source.starts_with(&target) => {
+                return Err(ApiError::InvalidInput(format!(
+                    "{from}: can't replace {to_dir}/{name}, which contains it"
+                )))
+            }
+            ConflictPolicy::Replace => {
+                remove_entry(&target).map_err(|err| ApiError::io(&name, err))?
+            }
+            ConflictPolicy::KeepBoth => {
+                target = (2..)
+                    .map(|n| dir.join(numbered_name(&name, n)))
+                    .find(|candidate| std::fs::symlink_metadata(candidate).is_err())
+                    .expect("unbounded search always finds a free name");
+            }
+        }
+    }
+
+    Ok(Some((source, target)))
+}
+
+#[cfg(feature = "server")]
+fn client_path(path: &std::path::Path) -> Result<String, ApiError> {
+    let root = sandbox::root()?;
+    let relative = path.strip_prefix(&root).unwrap_or(path);
+    Ok(format!("/{}", relative.to_string_lossy()))
+}
+
+/// Moves `from` into the directory `to_dir`, returning its new path, or
+/// `None` when skipped by `policy`.
+#[post("/api/move_path")]
+pub async fn move_path(
+    from: String,
+    to_dir: String,
+    policy: ConflictPolicy,
+) -> Result<Option<String>, ServerFnError> {
+    let (source, dir) = (sandbox::resolve(&from)?, sandbox::resolve(&to_dir)?);
+    let target = blocking(move || {
+        let Some((source, target)) = transfer_target_in(source, dir, &from, &to_dir, policy)?
+        else {
+            return Ok(None);
+        };
+        move_entry(&source, &target).map_err(|err| ApiError::io(&from, err))?;
+        Ok(Some(target))
+    })
+    .await?;
+    Ok(target.map(|target| client_path(&target)).transpose()?)
+}
+
+/// Renames `from` to `to`, or copies it and removes the original when they
+/// are on different file systems.
+#[cfg(feature = "server")]
+fn move_entry(from: &std::path::Path, to: &std::path::Path) -> std::io::Result<()> {
+    match std::fs::rename(from, to) {
+        Err(err) if err.kind() == std::io::ErrorKind::CrossesDevices => {
+            copy_recursive(from, to)?;
+            remove_entry(from)
+        }
+        result => result,
+    }
+}
+
+/// Copies `from` into the directory `to_dir`, returning the copy's path, or
+/// `None` when skipped by `policy`.
+#[post("/api/copy_path")]
+pub async fn copy_path(
+    from: String,
+    to_dir: String,
+    policy: ConflictPolicy,
+) -> Result<Option<String>, ServerFnError> {
+    let (source, dir) = (sandbox::resolve(&from)?, sandbox::resolve(&to_dir)?);
+    let target = blocking(move || {
+        let Some((source, target)) = transfer_target_in(source, dir, &from, &to_dir, policy)?
+        else {
+            return Ok(None);
+        };
+        copy_recursive(&source, &target).map_err(|err| ApiError::io(&from, err))?;
+        Ok(Some(target))
+    })
+    .await?;
+    Ok(target.map(|target| client_path(&target)).transpose()?)
+}
+
+/// Copies `path` next to itself under the first free [`copy_name`],
+/// returning the copy's path.
+#[post("/api/duplicate_path")]
+pub async fn duplicate_path(path: String) -> Result<String, ServerFnError> {
+    let source = sandbox::resolve(&path)?;
+    if source == sandbox::root()? {
+        return Err(ApiError::InvalidInput(format!("{path}: can't duplicate the root")).into());
+    }
+    let target = blocking(move || duplicate(&source, &path)).await?;
+    Ok(client_path(&target)?)
+}
+
+/// Copies `source`, which the client calls `path`, next to itself under the
+/// first free [`copy_name`], returning the copy's path.
+///
+/// Existing entries are never overwritten, even ones created while looking
+/// for a free name.
+#[cfg(feature = "server")]
+fn duplicate(source: &std::path::Path, path: &str) -> Result<std::path::PathBuf, ApiError> {
+    use std::io::ErrorKind;
+
+    let metadata = std::fs::symlink_metadata(source).map_err(|err| ApiError::io(path, err))?;
+    let (Some(dir), Some(name)) = (source.parent(), source.file_name()) else {
+        return Err(ApiError::InvalidInput(format!(
+            "{path}: can't duplicate the root"
+        )));
+    };
+    let name = name.to_string_lossy();
+
+    let mut n = 1;
+    loop {
+        let target = dir.join(copy_name(&name, n, metadata.is_dir()));
+        // Copying a file replaces whatever is there, so the name is claimed
+        // first. Directories and links fail to be created over anything.
+        let claimed = match metadata.is_file() {
+            true => std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&target)
+                .map(drop),
+            false => Ok(()),
+        };
+        match claimed.and_then(|()| copy_recursive(source, &target)) {
+            Ok(()) => return Ok(target),
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => n += 1,
+            Err(err) => {
+                if metadata.is_file() {
+                    let _ = std::fs::remove_file(&target);
+                }
+                return Err(ApiError::io(path, err));
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HashAlgo {
+    Sha1,
+    #[default]
+    Sha256,
+    Sha512,
+}
+
+/// Feeds `reader` through `D` in fixed-size chunks, returning the lowercase
+/// hex digest. Memory use doesn't depend on the input's size.
+#[cfg(feature = "server")]
+fn hex_digest<D: sha2::Digest>(mut reader: impl std::io::Read) -> std::io::Result<String> {
+    use std::fmt::Write;
+
+    let mut hasher = D::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    let mut hex = String::new();
+    for byte in hasher.finalize() {
+        let _ = write!(hex, "{byte:02x}");
+    }
+    Ok(hex)
+}
+
+/// Hashes the file at `path` with `algo`, returning the hex digest.
+#[post("/api/hash_file")]
+pub async fn hash_file(path: String, algo: HashAlgo) -> Result<String, ServerFnError> {
+    let resolved = sandbox::resolve(&path)?;
+    Ok(blocking(move || hash_path(&resolved, &path, algo)).await?)
+}
+
+/// Hex digest of the file at `resolved`, which the client calls `path`.
+#[cfg(feature = "server")]
+fn hash_path(resolved: &std::path::Path, path: &str, algo: HashAlgo) -> Result<String, ApiError> {
+    let file = std::fs::File::open(resolved).map_err(|err| ApiError::io(path, err))?;
+    if !file
+        .metadata()
+        .map_err(|err| ApiError::io(path, err))?
+        .is_file()
+    {
+        return Err(ApiError::InvalidInput(format!("{path}: not a file")));
+    }
+
+    let reader = std::io::BufReader::new(file);
+    let digest = match algo {
+        HashAlgo::Sha1 => hex_digest::<sha1::Sha1>(reader),
+        HashAlgo::Sha256 => hex_digest::<sha2::Sha256>(reader),
+        HashAlgo::Sha512 => hex_digest::<sha2::Sha512>(reader),
+    };
+    digest.map_err(|err| ApiError::io(path, err))
+}
+
+/// Metadata of a file or directory, as shown by a properties panel.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PathStat {
+    pub path: String,
+    pub is_dir: bool,
+    pub is_symlink: bool,
+    /// Size in bytes; for directories, the size of the entry itself rather
+    /// than of its contents (see [`dir_size`]).
+    pub len: u64,
+    /// Last modification, in milliseconds since the Unix epoch.
+    pub modified_ms: Option<u64>,
+    pub readonly: bool,
+}
+
+/// Returns the metadata of `path`, without following a final symlink.
+#[post("/api/stat_path")]
+pub async fn stat_path(path: String) -> Result<PathStat, ServerFnError> {
+    let resolved = sandbox::resolve(&path)?;
+    let metadata = std::fs::symlink_metadata(&resolved).map_err(|err| ApiError::io(&path, err))?;
+    let modified_ms = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|elapsed| elapsed.as_millis() as u64);
+
+    Ok(PathStat {
+        is_dir: metadata.is_dir(),
+        is_symlink: metadata.file_type().is_symlink(),
+        len: metadata.len(),
+        modified_ms,
+        readonly: metadata.permissions().readonly(),
+        path,
+    })
+}
+
+#[cfg(feature = "server")]
+fn tree_size(path: &std::path::Path) -> std::io::Result<u64> {
+    let metadata = std::fs::symlink_metadata(path)?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+    let mut total = 0;
+    for entry in std::fs::read_dir(path)? {
+        total += tree_size(&entry?.path())?;
+    }
+    Ok(total)
+}
+
+/// Returns the total size in bytes of the files under `path`. Symlinks are
+/// counted as themselves, not followed.
+#[post("/api/dir_size")]
+pub async fn dir_size(path: String) -> Result<u64, ServerFnError> {
+    let resolved = sandbox::resolve(&path)?;
+    Ok(blocking(move || tree_size(&resolved).map_err(|err| ApiError::io(&path, err))).await?)
+}
+
+/// A file or directory as listed to the client.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FileEntry {
+    pub name: String,
+    /// Path relative to the sandbox root, starting with `/`.
+    pub path: String,
+    pub is_dir: bool,
+    pub len: u64,
+    /// Last modification, in milliseconds since the Unix epoch.
+    pub modified_ms: Option<u64>,
+}
+
+#[cfg(feature = "server")]
+fn file_entry(path: &std::path::Path) -> Result<FileEntry, ApiError> {
+    file_entry_at(path, client_path(path)?)
+}
+
+/// Like `file_entry`, for a `path` the client calls `client`.
+#[cfg(feature = "server")]
+fn file_entry_at(path: &std::path::Path, client: String) -> Result<FileEntry, ApiError> {
+    let metadata = std::fs::symlink_metadata(path).map_err(|err| ApiError::io(&client, err))?;
+    Ok(FileEntry {
+        name: path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        is_dir: metadata.is_dir(),
+        len: metadata.len(),
+        modified_ms: metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|elapsed| elapsed.as_millis() as u64),
+        path: client,
+    })
+}
+
+/// Number of paths remembered for `recent_files`.
+#[cfg(feature = "server")]
+const RECENT_CAPACITY: usize = 100;
+
+/// Canonical paths touched by `read_file`/`write_file`, most recent first.
+#[cfg(feature = "server")]
+static RECENT: std::sync::Mutex<std::collections::VecDeque<std::path::PathBuf>> =
+    std::sync::Mutex::new(std::collections::VecDeque::new());
+
+#[cfg(feature = "server")]
+fn touch_recent(path: &std::path::Path) {
+    push_recent(&mut RECENT.lock().unwrap(), path);
+}
+
+/// Moves `path` to the front of `recent`, dropping the oldest entries past
+/// [`RECENT_CAPACITY`].
+#[cfg(feature = "server")]
+fn push_recent(
+    recent: &mut std::collections::VecDeque<std::path::PathBuf>,
+    path: &std::path::Path,
+) {
+    recent.retain(|other| other != path);
+    recent.push_front(path.to_path_buf());
+    recent.truncate(RECENT_CAPACITY);
+}
+
+/// Reads the whole UTF-8 text file at `path`. Files larger than
+/// [`MAX_READ_RANGE`] have to be read in parts with `read_range`.
+#[post("/api/read_file")]
+pub async fn read_file(path: String) -> Result<String, ServerFnError> {
+    let resolved = sandbox::resolve(&path)?;
+    Ok(blocking(move || {
+        let text = read_text(&resolved, &path)?;
+        touch_recent(&resolved);
+        Ok(text)
+    })
+    .await?)
+}
+
+/// The UTF-8 text file at `resolved`, which the client calls `path`, if it's
+/// at most [`MAX_READ_RANGE`] bytes long.
+#[cfg(feature = "server")]
+fn read_text(resolved: &std::path::Path, path: &str) -> Result<String, ApiError> {
+    let metadata = std::fs::metadata(resolved).map_err(|err| ApiError::io(path, err))?;
+    if !metadata.is_file() {
+        return Err(ApiError::InvalidInput(format!("{path}: not a file")));
+    }
+    if metadata.len() > MAX_READ_RANGE {
+        return Err(ApiError::InvalidInput(format!(
+            "{path}: too large to read at once ({} bytes)",
+            metadata.len()
+        )));
+    }
+
+    let bytes = std::fs::read(resolved).map_err(|err| ApiError::io(path, err))?;
+    String::from_utf8(bytes)
+        .map_err(|_| ApiError::InvalidInput(format!("{path}: not a UTF-8 text file")))
+}
+
+/// Replaces the contents of the file at `path` with `contents`, creating it
+/// if needed.
+#[post("/api/write_file")]
+pub async fn write_file(path: String, contents: String) -> Result<(), ServerFnError> {
+    let resolved = sandbox::resolve(&path)?;
+    Ok(blocking(move || {
+        std::fs::write(&resolved, contents).map_err(|err| ApiError::io(&path, err))?;
+        touch_recent(&resolved);
+        Ok(())
+    })
+    .await?)
+}
+
+/// Largest file `upload_file` accepts.
+pub const MAX_UPLOAD: usize = 32 * 1024 * 1024;
+
+/// Saves `bytes` as a new file called `name` in `dir`, returning its path.
+///
+/// An existing file is never overwritten: the upload gets a free name like
+/// `image (2).png` instead.
+#[post("/api/upload_file")]
+pub async fn upload_file(
+    dir: String,
+    name: String,
+    bytes: Vec<u8>,
+) -> Result<String, ServerFnError> {
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+        return Err(ApiError::InvalidInput(format!("{name}: not a valid file name")).into());
+    }
+    if bytes.len() > MAX_UPLOAD {
+        return Err(ApiError::InvalidInput(format!(
+            "{name}: too large to upload ({} bytes)",
+            bytes.len()
+        ))
+        .into());
+    }
+    let resolved = sandbox::resolve(&dir)?;
+    let target = blocking(move || save_upload(&resolved, &dir, &name, &bytes)).await?;
+    Ok(client_path(&target)?)
+}
+
+/// Writes `bytes` to a new file in the directory `resolved`, which the client
+/// calls `dir`, under the first free name starting with `name`.
+#[cfg(feature = "server")]
+fn save_upload(
+    resolved: &std::path::Path,
+    dir: &str,
+    name: &str,
+    bytes: &[u8],
+) -> Result<std::path::PathBuf, ApiError> {
+    use std::io::{ErrorKind, Write};
+
+    if !std::fs::metadata(resolved)
+        .map_err(|err| ApiError::io(dir, err))?
+        .is_dir()
+    {
+        return Err(ApiError::InvalidInput(format!("{dir}: not a directory")));
+    }
+
+    for candidate in std::iter::once(name.to_string()).chain((2..).map(|n| numbered_name(name, n)))
+    {
+        let target = resolved.join(candidate);
+        // Created exclusively, so a file appearing meanwhile isn't replaced.
+        let mut file = match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&target)
+        {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(ApiError::io(name, err)),
+        };
+        if let Err(err) = file.write_all(bytes) {
+            let _ = std::fs::remove_file(&target);
+            return Err(ApiError::io(name, err));
+        }
+        return Ok(target);
+    }
+    unreachable!("unbounded search always finds a free name")
+}
+
+/// Returns up to `limit` of the files most recently read or written, most
+/// recent first.
+///
+/// The log is shared by every session, so only files below the caller's
+/// root are returned. Entries are checked against the sandbox again, so
+/// files that were deleted or moved away since are left out.
+#[post("/api/recent_files")]
+pub async fn recent_files(limit: usize) -> Result<Vec<FileEntry>, ServerFnError> {
+    let root = sandbox::root()?;
+    let recent = RECENT.lock().unwrap().clone();
+    Ok(recent
+        .iter()
+        .filter(|path| path.starts_with(&root))
+        .filter_map(|path| {
+            let resolved = sandbox::resolve(&client_path(path).ok()?).ok()?;
+            (resolved == *path && resolved.is_file()).then(|| file_entry(&resolved).ok())?
+        })
+        .take(limit)
+        .collect())
+}
+
+/// How `list_dir` orders entries. Directories always come first.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SortKey {
+    #[default]
+    Name,
+    /// Largest first.
+    Size,
+    /// Most recently modified first.
+    Modified,
+    /// The order saved with `set_manual_order`, followed by entries missing
+    /// from it by name.
+    Manual,
+}
+
+/// File in each directory holding its manual order, one name per line.
+/// Hidden from listings.
+#[cfg(feature = "server")]
+const ORDER_FILE: &str = ".jadeos-order";
+
+/// Lists the entries of the directory at `path`, sorted by `sort`.
+#[post("/api/list_dir")]
+pub async fn list_dir(path: String, sort: SortKey) -> Result<Vec<FileEntry>, ServerFnError> {
+    let resolved = sandbox::resolve(&path)?;
+    // The listing runs off the request, where the session's root can't be
+    // looked up anymore.
+    let dir = client_path(&resolved)?;
+    Ok(blocking(move || list_entries(&resolved, &dir, sort)).await?)
+}
+
+/// Entries of the directory `resolved`, which the client calls `path`.
+#[cfg(feature = "server")]
+fn list_entries(
+    resolved: &std::path::Path,
+    path: &str,
+    sort: SortKey,
+) -> Result<Vec<FileEntry>, ApiError> {
+    let mut entries = std::fs::read_dir(resolved)
+        .map_err(|err| ApiError::io(path, err))?
+        .filter_map(|entry| match entry {
+            Ok(entry) if entry.file_name() == ORDER_FILE => None,
+            Ok(entry) => {
+                let name = entry.file_name();
+                let client = format!("{}/{}", path.trim_end_matches('/'), name.to_string_lossy());
+                Some(file_entry_at(&entry.path(), client))
+            }
+            Err(err) => Some(Err(ApiError::io(path, err))),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    entries.sort_by(|a, b| {
+        b.is_dir
+            .cmp(&a.is_dir)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    match sort {
+        SortKey::Name => {}
+        SortKey::Size => entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then(b.len.cmp(&a.len))),
+        SortKey::Modified => entries.sort_by(|a, b| {
+            b.is_dir
+                .cmp(&a.is_dir)
+                .then(b.modified_ms.cmp(&a.modified_ms))
+        }),
+        SortKey::Manual => {
+            let order = std::fs::read_to_string(resolved.join(ORDER_FILE)).unwrap_or_default();
+            let rank: std::collections::HashMap<&str, usize> = order
+                .lines()
+                .enumerate()
+                .map(|(i, name)| (name, i))
+                .collect();
+            // Stable, so unranked entries keep their name order at the end
+            // of their group.
+            entries.sort_by_key(|entry| {
+                let rank = rank.get(entry.name.as_str()).copied();
+                (!entry.is_dir, rank.unwrap_or(usize::MAX))
+            });
+        }
+    }
+    Ok(entries)
+}
+
+/// Entries per batch sent by `stream_dir`.
+pub const STREAM_DIR_BATCH: usize = 256;
+
+/// Streams the entries of the directory at `path` in batches of
+/// [`STREAM_DIR_BATCH`] as the directory is read, for directories too large
+/// to list in one `list_dir` call.
+///
+/// Entries arrive in the order the file system returns them, unsorted.
+/// Entries removed while being read are skipped. Reading waits while the
+/// client falls behind and stops as soon as it goes away.
+#[post("/api/stream_dir")]
+pub async fn stream_dir(path: String) -> Result<JsonStream<Vec<FileEntry>>, ServerFnError> {
+    let resolved = sandbox::resolve(&path)?;
+    let entries = std::fs::read_dir(&resolved).map_err(|err| ApiError::io(&path, err))?;
+    // The read runs off the request, where the session's root can't be
+    // looked up anymore.
+    let dir = client_path(&resolved)?;
+
+    let (mut tx, rx) = futures::channel::mpsc::channel(STREAM_BUFFER);
+    // Directory reads block, so they're kept off the async workers.
+    tokio::task::spawn_blocking(move || {
+        let mut send = |batch| futures::executor::block_on(tx.send(batch)).is_ok();
+        stream_entries(entries, &dir, &mut send);
+    });
+    Ok(JsonStream::new(rx))
+}
+
+/// Reads `entries` of the directory the client calls `dir`, passing them to
+/// `send` in batches of [`STREAM_DIR_BATCH`] until it returns `false`.
+#[cfg(feature = "server")]
+fn stream_entries(
+    entries: std::fs::ReadDir,
+    dir: &str,
+    send: &mut impl FnMut(Vec<FileEntry>) -> bool,
+) {
+    let mut batch = Vec::with_capacity(STREAM_DIR_BATCH);
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let name = entry.file_name();
+        if name == ORDER_FILE {
+            continue;
+        }
+        let client = format!("{}/{}", dir.trim_end_matches('/'), name.to_string_lossy());
+        let Ok(entry) = file_entry_at(&entry.path(), client) else {
+            continue;
+        };
+        batch.push(entry);
+        if batch.len() == STREAM_DIR_BATCH {
+            let full = std::mem::replace(&mut batch, Vec::with_capacity(STREAM_DIR_BATCH));
+            if !send(full) {
+                return;
+            }
+        }
+    }
+    if !batch.is_empty() {
+        send(batch);
+    }
+}
+
+/// Most entries `home_listing` returns in one page.
+pub const MAX_DIR_PAGE: usize = 1000;
+
+/// A page of a directory's entries.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DirPage {
+    pub path: String,
+    pub entries: Vec<FileEntry>,
+    /// Entries in the directory across all pages.
+    pub total: usize,
+}
+
+/// Up to `limit` (at most [`MAX_DIR_PAGE`]) entries of the directory
+/// `resolved`, which the client calls `path`, by name, starting at `offset`.
+#[cfg(feature = "server")]
+fn dir_page(
+    resolved: &std::path::Path,
+    path: String,
+    offset: usize,
+    limit: usize,
+) -> Result<DirPage, ApiError> {
+    let entries = list_entries(resolved, &path, SortKey::Name)?;
+    Ok(DirPage {
+        total: entries.len(),
+        entries: entries
+            .into_iter()
+            .skip(offset)
+            .take(limit.min(MAX_DIR_PAGE))
+            .collect(),
+        path,
+    })
+}
+
+/// Lists up to `limit` entries of the home directory by name, starting at
+/// `offset`, so the file manager can open on it with a single call.
+///
+/// Home is the caller's sandbox root: the one its session set, or the
+/// global root.
+#[post("/api/home_listing")]
+pub async fn home_listing(offset: usize, limit: usize) -> Result<DirPage, ServerFnError> {
+    let resolved = sandbox::resolve("/")?;
+    let path = client_path(&resolved)?;
+    Ok(blocking(move || dir_page(&resolved, path, offset, limit)).await?)
+}
+
+/// Saves `order`, a list of entry names, as the manual order of the
+/// directory `dir` for `list_dir` with [`SortKey::Manual`].
+#[post("/api/set_manual_order")]
+pub async fn set_manual_order(dir: String, order: Vec<String>) -> Result<(), ServerFnError> {
+    let resolved = sandbox::resolve(&dir)?;
+    Ok(save_manual_order(&resolved, &dir, &order)?)
+}
+
+/// Saves `order` for the directory `resolved`, which the client calls `dir`.
+#[cfg(feature = "server")]
+fn save_manual_order(
+    resolved: &std::path::Path,
+    dir: &str,
+    order: &[String],
+) -> Result<(), ApiError> {
+    if !resolved.is_dir() {
+        return Err(ApiError::InvalidInput(format!("{dir}: not a directory")));
+    }
+    if let Some(name) = order
+        .iter()
+        .find(|name| name.is_empty() || name.contains(['/', '\\', '\n']))
+    {
+        return Err(ApiError::InvalidInput(format!(
+            "{name:?} is not an entry name"
+        )));
+    }
+
+    std::fs::write(resolved.join(ORDER_FILE), order.join("\n"))
+        .map_err(|err| ApiError::io(dir, err))
+}
+
+/// Returns a number that changes whenever entries are added to, removed from
+/// or renamed in the directory at `path`, so clients can poll it and only
+/// call `list_dir` again when it differs.
+///
+/// It's derived from the directory's modification time, which edits to the
+/// contents of existing files don't touch.
+#[post("/api/dir_version")]
+pub async fn dir_version(path: String) -> Result<u64, ServerFnError> {
+    let resolved = sandbox::resolve(&path)?;
+    Ok(dir_version_of(&resolved, &path)?)
+}
+
+/// [`dir_version`] of the directory `resolved`, which the client calls
+/// `path`.
+#[cfg(feature = "server")]
+fn dir_version_of(resolved: &std::path::Path, path: &str) -> Result<u64, ApiError> {
+    let metadata = std::fs::metadata(resolved).map_err(|err| ApiError::io(path, err))?;
+    if !metadata.is_dir() {
+        return Err(ApiError::InvalidInput(format!("{path}: not a directory")));
+    }
+    let modified = metadata.modified().map_err(|err| ApiError::io(path, err))?;
+    let elapsed = modified
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    Ok(elapsed.as_nanos() as u64)
+}
+
+/// What a symlink at `link_path` pointing to the client's `target` stores,
+/// once the target is known to lie inside `root`.
+///
+/// Relative targets may not contain `..`: the host applies it after
+/// following any symlink before it, which the text of the path can't tell,
+/// so normalizing it lexically would let a link step out of the root.
+#[cfg(feature = "server")]
+fn symlink_target(
+    root: &std::path::Path,
+    link_path: &std::path::Path,
+    target: &str,
+) -> Result<std::path::PathBuf, ApiError> {
+    use std::path::{Component, Path, PathBuf};
+
+    if target.starts_with('/') {
+        return sandbox::resolve_in(root, target);
+    }
+    let outside = || ApiError::OutsideSandbox(format!("{target}: outside of the sandbox"));
+    let mut joined = link_path.parent().ok_or_else(outside)?.to_path_buf();
+    for component in Path::new(target).components() {
+        match component {
+            Component::Normal(name) => joined.push(name),
+            Component::CurDir => {}
+            _ => return Err(outside()),
+        }
+    }
+    // Resolving follows the symlinks along the way.
+    let relative = joined.strip_prefix(root).map_err(|_| outside())?;
+    sandbox::resolve_in(root, &relative.to_string_lossy())?;
+    Ok(PathBuf::from(target))
+}
+
+/// Creates a symbolic link at `link` pointing to `target`.
+///
+/// A `target` starting with `/` is a sandbox path like any other; anything
+/// else is relative to the link's directory and is stored as is, so the link
+/// keeps working if the tree around it moves. Relative targets can't go up
+/// with `..`. Either way the target has to lie inside the sandbox, so
+/// following the link later can't escape it.
+#[post("/api/create_symlink")]
+pub async fn create_symlink(target: String, link: String) -> Result<(), ServerFnError> {
+    let link_path = sandbox::resolve(&link)?;
+    if std::fs::symlink_metadata(&link_path).is_ok() {
+        return Err(ApiError::Conflict(format!("{link} already exists")).into());
+    }
+    let stored = symlink_target(&sandbox::root()?, &link_path, &target)?;
+
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(&stored, &link_path).map_err(|err| ApiError::io(&link, err))?;
+        Ok(())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = stored;
+        Err(ApiError::Unsupported("symbolic links aren't supported on this server".into()).into())
+    }
+}
+
+/// Adds `path`, and everything below it if it's a directory, to `zip` under
+/// its path relative to `base`. Symlinks and `skip` are left out.
+#[cfg(feature = "server")]
+fn zip_entry(
+    zip: &mut zip::ZipWriter<std::fs::File>,
+    path: &std::path::Path,
+    base: &std::path::Path,
+    skip: &std::path::Path,
+) -> zip::result::ZipResult<()> {
+    if path == skip {
+        return Ok(());
+    }
+    let metadata = std::fs::symlink_metadata(path)?;
+    let name = path
+        .strip_prefix(base)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/");
+    let options = zip::write::SimpleFileOptions::default();
+
+    if metadata.is_dir() {
+        zip.add_directory(format!("{name}/"), options)?;
+        for entry in std::fs::read_dir(path)? {
+            zip_entry(zip, &entry?.path(), base, skip)?;
+        }
+    } else if metadata.is_file() {
+        zip.start_file(name, options)?;
+        std::io::copy(&mut std::fs::File::open(path)?, zip)?;
+    }
+    Ok(())
+}
+
+/// `paths` sorted, without duplicates and paths lying below another one.
+#[cfg(feature = "server")]
+fn outermost_paths(mut paths: Vec<std::path::PathBuf>) -> Vec<std::path::PathBuf> {
+    paths.sort();
+    let mut outermost: Vec<std::path::PathBuf> = Vec::with_capacity(paths.len());
+    for path in paths {
+        // Sorted, a path's descendants directly follow it.
+        if !outermost.last().is_some_and(|last| path.starts_with(last)) {
+            outermost.push(path);
+        }
+    }
+    outermost
+}
+
+/// Zips the files and directories at `paths` into an archive named
+/// `archive_name` in the deepest directory containing all of them, keeping
+/// their structure relative to it. Returns the archive's path.
+///
+/// `.zip` is appended to the name if missing, and a name that's taken gets
+/// a number like `archive (2).zip`. Paths inside other selected directories
+/// are zipped once, as part of those.
+#[post("/api/zip_selection")]
+pub async fn zip_selection(
+    paths: Vec<String>,
+    archive_name: String,
+) -> Result<String, ServerFnError> {
+    if paths.is_empty() {
+        return Err(ApiError::InvalidInput("nothing to zip".into()).into());
+    }
+    if archive_name.is_empty() || archive_name.contains(['/', '\\']) {
+        return Err(ApiError::InvalidInput(format!("{archive_name:?} is not a file name")).into());
+    }
+    let archive_name = if archive_name.to_lowercase().ends_with(".zip") {
+        archive_name
+    } else {
+        format!("{archive_name}.zip")
+    };
+
+    let sources = outermost_paths(
+        paths
+            .iter()
+            .map(|path| sandbox::resolve(path))
+            .collect::<Result<Vec<_>, _>>()?,
+    );
+    let root = sandbox::root()?;
+    if sources.contains(&root) {
+        return Err(ApiError::InvalidInput("can't zip the whole sandbox".into()).into());
+    }
+
+    let mut base = sources[0].parent().unwrap_or(&root).to_path_buf();
+    for source in &sources[1..] {
+        while !source.starts_with(&base) {
+            base.pop();
+        }
+    }
+
+    let archive = std::iter::once(base.join(&archive_name))
+        .chain((2..).map(|n| base.join(numbered_name(&archive_name, n))))
+        .find(|candidate| std::fs::symlink_metadata(candidate).is_err())
+        .expect("unbounded search always finds a free name");
+
+    let client = client_path(&archive)?;
+    blocking(move || write_zip(&sources, &base, &archive, &archive_name)).await?;
+
+    Ok(client)
+}
+
+/// Zips `sources` into a new `archive`, named `archive_name` for errors,
+/// with entry names relative to `base`. A partly written archive is removed.
+#[cfg(feature = "server")]
+fn write_zip(
+    sources: &[std::path::PathBuf],
+    base: &std::path::Path,
+    archive: &std::path::Path,
+    archive_name: &str,
+) -> Result<(), ApiError> {
+    let file = std::fs::File::create(archive).map_err(|err| ApiError::io(archive_name, err))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let written = sources
+        .iter()
+        .try_for_each(|source| zip_entry(&mut zip, source, base, archive))
+        .and_then(|()| zip.finish().map(|_| ()));
+    if let Err(err) = written {
+        let _ = std::fs::remove_file(archive);
+        return Err(ApiError::Internal(format!("{archive_name}: {err}")));
+    }
+    Ok(())
+}
+
+/// Size of the chunks `tar_stream` sends its archive in.
+pub const TAR_CHUNK_LEN: usize = 64 * 1024;
+
+/// Hands what's written to it to `send` in chunks of [`TAR_CHUNK_LEN`]
+/// bytes. `send` returns whether the client still listens; once it doesn't,
+/// writes fail with `BrokenPipe`.
+#[cfg(feature = "server")]
+struct ChunkWriter<F: FnMut(Vec<u8>) -> bool> {
+    send: F,
+    buffer: Vec<u8>,
+}
+
+#[cfg(feature = "server")]
+impl<F: FnMut(Vec<u8>) -> bool> ChunkWriter<F> {
+    fn new(send: F) -> Self {
+        Self {
+            send,
+            buffer: Vec::with_capacity(TAR_CHUNK_LEN),
+        }
+    }
+
+    fn send_buffer(&mut self) -> std::io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::replace(&mut self.buffer, Vec::with_capacity(TAR_CHUNK_LEN));
+        match (self.send)(chunk) {
+            true => Ok(()),
+            false => Err(std::io::ErrorKind::BrokenPipe.into()),
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+impl<F: FnMut(Vec<u8>) -> bool> std::io::Write for ChunkWriter<F> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let len = buf.len().min(TAR_CHUNK_LEN - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..len]);
+        if self.buffer.len() == TAR_CHUNK_LEN {
+            self.send_buffer()?;
+        }
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.send_buffer()
+    }
+}
+
+/// Adds `path`, and everything below it if it's a directory, to `tar` under
+/// its path relative to `base`, in name order. Symlinks are left out.
+///
+/// Entry names are built from what `read_dir` returns below `path`, so they
+/// never contain `..` or absolute components.
+#[cfg(feature = "server")]
+fn tar_entry(
+    tar: &mut tar::Builder<impl std::io::Write>,
+    path: &std::path::Path,
+    base: &std::path::Path,
+) -> std::io::Result<()> {
+    let metadata = std::fs::symlink_metadata(path)?;
+    let name = path.strip_prefix(base).unwrap_or(path);
+
+    if metadata.is_dir() {
+        tar.append_dir(name, path)?;
+        let mut entries = std::fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        entries.sort();
+        for entry in entries {
+            tar_entry(tar, &entry, base)?;
+        }
+    } else if metadata.is_file() {
+        tar.append_file(name, &mut std::fs::File::open(path)?)?;
+    }
+    Ok(())
+}
+
+/// Streams a tar archive of the file or directory at `path`, for
+/// downloading trees too large for `zip_selection`.
+///
+/// Entries are written as the tree is walked and sent in chunks of
+/// [`TAR_CHUNK_LEN`] bytes, so the server never holds the archive, or the
+/// listing of the whole tree, in memory; the walk waits while the client
+/// falls behind. Symlinks are skipped. If a file can't be read the stream
+/// ends with an error rather than with a truncated archive.
+#[post("/api/tar_stream")]
+pub async fn tar_stream(path: String) -> Result<ByteStream, ServerFnError> {
+    let resolved = sandbox::resolve(&path)?;
+    std::fs::symlink_metadata(&resolved).map_err(|err| ApiError::io(&path, err))?;
+    // Entries are named from the archived entry itself down.
+    let base = resolved.parent().unwrap_or(&resolved).to_path_buf();
+
+    let (mut tx, rx) = futures::channel::mpsc::channel::<std::io::Result<Bytes>>(STREAM_BUFFER);
+    // File reads block, so they're kept off the async workers.
+    tokio::task::spawn_blocking(move || {
+        let written = {
+            let writer = ChunkWriter::new(|chunk| {
+                futures::executor::block_on(tx.send(Ok(chunk.into()))).is_ok()
+            });
+            let mut tar = tar::Builder::new(writer);
+            tar.follow_symlinks(false);
+            tar_entry(&mut tar, &resolved, &base)
+                .and_then(|()| tar.into_inner())
+                .and_then(|mut writer| std::io::Write::flush(&mut writer))
+        };
+        match written {
+            Err(err) if err.kind() != std::io::ErrorKind::BrokenPipe => {
+                let _ = futures::executor::block_on(tx.send(Err(err)));
+            }
+            _ => {}
+        }
+    });
+    Ok(ByteStream::from(rx))
+}
+
+/// Most nodes `dir_tree` returns in one response.
+pub const DIR_TREE_BUDGET: usize = 10_000;
+
+/// A directory tree as returned by `dir_tree`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TreeNode {
+    pub name: String,
+    pub path: String,
+    pub is_dir: bool,
+    /// Empty for files, and for directories below the depth limit.
+    pub children: Vec<TreeNode>,
+    /// Whether some entries below this node were left out because the depth
+    /// limit or the node budget was reached.
+    pub truncated: bool,
+}
+
+/// The tree below `path`, which the client calls `client`.
+#[cfg(feature = "server")]
+fn tree_node(
+    path: &std::path::Path,
+    client: String,
+    depth: usize,
+    budget: &mut usize,
+) -> Result<TreeNode, ApiError> {
+    let is_dir = std::fs::symlink_metadata(path)
+        .map_err(|err| ApiError::io(&client, err))?
+        .is_dir();
+    let mut node = TreeNode {
+        name: path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        path: client,
+        is_dir,
+        children: Vec::new(),
+        truncated: false,
+    };
+    if !is_dir {
+        return Ok(node);
+    }
+
+    let mut entries = std::fs::read_dir(path)
+        .map_err(|err| ApiError::io(&node.path, err))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|kind| !kind.is_symlink()))
+        .map(|entry| entry.path())
+        .collect::<Vec<_>>();
+    if entries.is_empty() {
+        return Ok(node);
+    }
+    if depth == 0 {
+        node.truncated = true;
+        return Ok(node);
+    }
+
+    entries.sort();
+    for entry in entries {
+        if *budget == 0 {
+            node.truncated = true;
+            break;
+        }
+        *budget -= 1;
+        let name = entry.file_name().unwrap_or_default().to_string_lossy();
+        let client = format!("{}/{name}", node.path.trim_end_matches('/'));
+        let child = tree_node(&entry, client, depth - 1, budget)?;
+        node.truncated |= child.truncated;
+        node.children.push(child);
+    }
+    Ok(node)
+}
+
+/// Returns the tree below `path`, `max_depth` levels deep, with at most
+/// [`DIR_TREE_BUDGET`] nodes. Symlinks are skipped.
+#[post("/api/dir_tree")]
+pub async fn dir_tree(path: String, max_depth: usize) -> Result<TreeNode, ServerFnError> {
+    let resolved = sandbox::resolve(&path)?;
+    // The walk runs off the request, where the session's root can't be
+    // looked up anymore.
+    let client = client_path(&resolved)?;
+    Ok(blocking(move || {
+        let mut budget = DIR_TREE_BUDGET;
+        tree_node(&resolved, client, max_depth, &mut budget)
+    })
+    .await?)
+}
+
+/// A change to a file watched with `watch_file`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FileEvent {
+    /// The contents changed, or the file was replaced (e.g. by an editor
+    /// saving through a rename) or recreated.
+    Modified,
+    Removed,
+}
+
+/// How often `watch_file` checks the file.
+#[cfg(feature = "server")]
+const WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// What `watch_file` compares between checks. The inode catches files
+/// replaced with one of the same size and modification time.
+#[cfg(feature = "server")]
+fn file_identity(path: &std::path::Path) -> Option<(std::time::SystemTime, u64, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    #[cfg(unix)]
+    let inode = std::os::unix::fs::MetadataExt::ino(&metadata);
+    #[cfg(not(unix))]
+    let inode = 0;
+    Some((metadata.modified().ok()?, metadata.len(), inode))
+}
+
+/// Streams changes to the file at `path` until the client disconnects.
+///
+/// The path is watched rather than the file, so after the file is replaced
+/// the new one is followed.
+#[post("/api/watch_file")]
+pub async fn watch_file(path: String) -> Result<JsonStream<FileEvent>, ServerFnError> {
+    let resolved = sandbox::resolve(&path)?;
+    if !resolved.is_file() {
+        return Err(ApiError::InvalidInput(format!("{path}: not a file")).into());
+    }
+
+    Ok(JsonStream::spawn(move |tx| {
+        watch_path(resolved, WATCH_INTERVAL, tx)
+    }))
+}
+
+/// Checks the file at `resolved` every `interval`, sending its changes to
+/// `tx` until the receiver is dropped.
+#[cfg(feature = "server")]
+async fn watch_path(
+    resolved: std::path::PathBuf,
+    interval: std::time::Duration,
+    tx: futures::channel::mpsc::UnboundedSender<FileEvent>,
+) {
+    let mut last = file_identity(&resolved);
+    loop {
+        tokio::time::sleep(interval).await;
+        // Checked every tick, as a file that never changes would otherwise
+        // be watched forever.
+        if tx.is_closed() {
+            break;
+        }
+        let current = file_identity(&resolved);
+        if current == last {
+            continue;
+        }
+        let event = match current {
+            Some(_) => FileEvent::Modified,
+            None => FileEvent::Removed,
+        };
+        last = current;
+        if tx.unbounded_send(event).is_err() {
+            break;
+        }
+    }
+}
+
+/// How `grep` interprets its pattern.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GrepOptions {
+    pub case_insensitive: bool,
+    /// Only match the pattern between word boundaries.
+    pub whole_word: bool,
+    /// Treat the pattern as a regular expression rather than literal text.
+    pub regex: bool,
+}
+
+/// A line matched by `grep`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GrepMatch {
+    pub path: String,
+    /// 1-based.
+    pub line_no: usize,
+    /// The matching line, without its line break, cut to
+    /// [`GREP_MAX_LINE_LEN`] characters.
+    pub line: String,
+}
+
+/// Most matches `grep` streams before it stops.
+pub const GREP_MAX_MATCHES: usize = 1_000;
+/// How long `grep` searches before it stops.
+pub const GREP_TIME_BUDGET: std::time::Duration = std::time::Duration::from_secs(10);
+/// Longest line `grep` returns; longer lines are cut.
+pub const GREP_MAX_LINE_LEN: usize = 500;
+/// Longest pattern `grep` accepts.
+pub const GREP_MAX_PATTERN_LEN: usize = 1_000;
+
+/// Upper bound on the compiled size of `grep` regexes, so a pathological
+/// pattern can't exhaust the server's memory.
+#[cfg(feature = "server")]
+const GREP_REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+/// How much of a file `grep` inspects to tell whether it's binary.
+#[cfg(feature = "server")]
+const BINARY_SNIFF_LEN: usize = 8 * 1024;
+
+#[cfg(feature = "server")]
+fn grep_regex(pattern: &str, options: GrepOptions) -> Result<regex::Regex, ApiError> {
+    if pattern.is_empty() || pattern.len() > GREP_MAX_PATTERN_LEN {
+        return Err(ApiError::InvalidInput(format!(
+            "patterns must be 1 to {GREP_MAX_PATTERN_LEN} bytes long"
+        )));
+    }
+    let mut source = match options.regex {
+        true => pattern.to_string(),
+        false => regex::escape(pattern),
+    };
+    if options.whole_word {
+        source = format!(r"\b(?:{source})\b");
+    }
+    regex::RegexBuilder::new(&source)
+        .case_insensitive(options.case_insensitive)
+        .size_limit(GREP_REGEX_SIZE_LIMIT)
+        .build()
+        .map_err(|err| ApiError::InvalidInput(format!("invalid pattern: {err}")))
+}
+
+/// Searches the file at `path`, handing its matches to `send`, which returns
+/// whether the client still listens. Returns `false` once the search should
+/// stop: the client is gone, `remaining` matches were sent or `deadline`
+/// passed, which is checked every line so a huge file can't overrun it.
+#[cfg(feature = "server")]
+fn grep_file(
+    path: &std::path::Path,
+    client: &str,
+    regex: &regex::Regex,
+    send: &mut impl FnMut(GrepMatch) -> bool,
+    remaining: &mut usize,
+    deadline: std::time::Instant,
+) -> bool {
+    use std::io::{BufRead, BufReader};
+
+    let Ok(file) = std::fs::File::open(path) else {
+        return true;
+    };
+    let mut reader = BufReader::with_capacity(BINARY_SNIFF_LEN, file);
+    match reader.fill_buf() {
+        Ok(head) if !head.contains(&0) => {}
+        // Unreadable or binary.
+        _ => return true,
+    }
+
+    let mut line = Vec::new();
+    let mut line_no = 0;
+    loop {
+        if std::time::Instant::now() >= deadline {
+            return false;
+        }
+        line.clear();
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) | Err(_) => return true,
+            Ok(_) => line_no += 1,
+        }
+        let text = String::from_utf8_lossy(&line);
+        let text = text.trim_end_matches(['\n', '\r']);
+        if !regex.is_match(text) {
+            continue;
+        }
+        let found = GrepMatch {
+            path: client.to_string(),
+            line_no,
+            line: text.chars().take(GREP_MAX_LINE_LEN).collect(),
+        };
+        *remaining -= 1;
+        if !send(found) || *remaining == 0 {
+            return false;
+        }
+    }
+}
+
+/// Searches the files below `resolved` in name order, handing matches to
+/// `send` like [`grep_file`] until [`GREP_MAX_MATCHES`] were sent or
+/// `deadline` passed. Matches are named by their path below `sandbox_root`.
+#[cfg(feature = "server")]
+fn grep_tree(
+    resolved: std::path::PathBuf,
+    sandbox_root: &std::path::Path,
+    regex: &regex::Regex,
+    send: &mut impl FnMut(GrepMatch) -> bool,
+    deadline: std::time::Instant,
+) {
+    let mut remaining = GREP_MAX_MATCHES;
+    let mut pending = vec![resolved];
+    while let Some(path) = pending.pop() {
+        if std::time::Instant::now() >= deadline {
+            return;
+        }
+        let Ok(metadata) = std::fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            let Ok(entries) = std::fs::read_dir(&path) else {
+                continue;
+            };
+            let mut entries: Vec<_> = entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .collect();
+            // Reversed, so popping visits them in name order.
+            entries.sort_by(|a, b| b.cmp(a));
+            pending.extend(entries);
+        } else if metadata.is_file() {
+            let relative = path.strip_prefix(sandbox_root).unwrap_or(&path);
+            let client = format!("/{}", relative.to_string_lossy());
+            if !grep_file(&path, &client, regex, send, &mut remaining, deadline) {
+                return;
+            }
+        }
+    }
+}
+
+/// Streams the lines of the files below `root` matching `pattern`, as they
+/// are found.
+///
+/// Files are visited in name order; binary files and symlinks are skipped.
+/// The search stops after [`GREP_MAX_MATCHES`] matches or
+/// [`GREP_TIME_BUDGET`], whichever comes first, or when the client goes away.
+#[post("/api/grep")]
+pub async fn grep(
+    root: String,
+    pattern: String,
+    options: GrepOptions,
+) -> Result<JsonStream<GrepMatch>, ServerFnError> {
+    let regex = grep_regex(&pattern, options)?;
+    let resolved = sandbox::resolve(&root)?;
+    if !resolved.is_dir() {
+        return Err(ApiError::InvalidInput(format!("{root}: not a directory")).into());
+    }
+    // The walk runs off the request, where the session's root can't be
+    // looked up anymore.
+    let sandbox_root = sandbox::root()?;
+
+    let (mut tx, rx) = futures::channel::mpsc::channel(STREAM_BUFFER);
+    // File reads block, so they're kept off the async workers.
+    tokio::task::spawn_blocking(move || {
+        let deadline = std::time::Instant::now() + GREP_TIME_BUDGET;
+        let mut send = |found| futures::executor::block_on(tx.send(found)).is_ok();
+        grep_tree(resolved, &sandbox_root, &regex, &mut send, deadline);
+    });
+    Ok(JsonStream::new(rx))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A temporary sandbox root, canonical like the real one.
+    #[cfg(feature = "server")]
+    fn sandbox_root() -> (tempfile::TempDir, std::path::PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("root");
+        std::fs::create_dir(&root).unwrap();
+        let root = root.canonicalize().unwrap();
+        (dir, root)
+    }
+
+    #[cfg(all(feature = "server", unix))]
+    #[test]
+    fn symlink_targets_stay_inside_the_sandbox() {
+        let (_dir, root) = sandbox_root();
+        std::fs::create_dir_all(root.join("d/e")).unwrap();
+        let link = root.join("d/e/link");
+
+        assert_eq!(symlink_target(&root, &link, "/d").unwrap(), root.join("d"));
+        assert_eq!(
+            symlink_target(&root, &link, "./sibling").unwrap(),
+            std::path::PathBuf::from("./sibling")
+        );
+        assert!(symlink_target(&root, &link, "../e").is_err());
+        assert!(symlink_target(&root, &link, "/../../etc").is_err());
+    }
+
+    #[cfg(all(feature = "server", unix))]
+    #[test]
+    fn symlink_targets_cant_climb_through_other_links() {
+        let (_dir, root) = sandbox_root();
+        std::fs::create_dir_all(root.join("d/e")).unwrap();
+        // A link to the root itself, as `create_symlink` stores `/`.
+        std::os::unix::fs::symlink(&root, root.join("d/e/a")).unwrap();
+        let link = root.join("d/e/link");
+
+        // Lexically `d/p`, but `<root>/../../p` on the host.
+        assert!(symlink_target(&root, &link, "a/../../p").is_err());
+        // Going down through the link stays inside.
+        assert!(symlink_target(&root, &link, "a/d").is_ok());
+    }
+
+    /// `transfer_target_in` for `from` and `to_dir` under `root`.
+    #[cfg(feature = "server")]
+    fn target(
+        root: &std::path::Path,
+        from: &str,
+        to_dir: &str,
+        policy: ConflictPolicy,
+    ) -> Result<Option<std::path::PathBuf>, ApiError> {
+        let planned = transfer_target_in(root.join(from), root.join(to_dir), from, to_dir, policy)?;
+        Ok(planned.map(|(_, target)| target.strip_prefix(root).unwrap().to_path_buf()))
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn transfer_targets_follow_the_conflict_policy() {
+        let (_dir, root) = sandbox_root();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("dst")).unwrap();
+        std::fs::write(root.join("src/a.txt"), "new").unwrap();
+        std::fs::write(root.join("src/b.txt"), "new").unwrap();
+        std::fs::write(root.join("dst/a.txt"), "old").unwrap();
+
+        let free = target(&root, "src/b.txt", "dst", ConflictPolicy::Fail).unwrap();
+        assert_eq!(free, Some("dst/b.txt".into()));
+        assert!(matches!(
+            target(&root, "src/a.txt", "dst", ConflictPolicy::Fail),
+            Err(ApiError::Conflict(_))
+        ));
+        assert_eq!(
+            target(&root, "src/a.txt", "dst", ConflictPolicy::Skip).unwrap(),
+            None
+        );
+        assert_eq!(
+            target(&root, "src/a.txt", "dst", ConflictPolicy::KeepBoth).unwrap(),
+            Some("dst/a (2).txt".into())
+        );
+        assert_eq!(
+            target(&root, "src/a.txt", "dst", ConflictPolicy::Replace).unwrap(),
+            Some("dst/a.txt".into())
+        );
+        assert!(!root.join("dst/a.txt").exists());
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn transfer_targets_never_replace_the_source_or_its_ancestors() {
+        let (_dir, root) = sandbox_root();
+        std::fs::create_dir_all(root.join("a/x/x")).unwrap();
+
+        // Onto itself.
+        assert_eq!(
+            target(&root, "a/x", "a", ConflictPolicy::Replace).unwrap(),
+            None
+        );
+        // Into itself.
+        assert!(target(&root, "a/x", "a/x/x", ConflictPolicy::Replace).is_err());
+        // Onto the folder holding it.
+        assert!(matches!(
+            target(&root, "a/x/x", "a", ConflictPolicy::Replace),
+            Err(ApiError::InvalidInput(_))
+        ));
+        assert!(root.join("a/x/x").is_dir());
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn moves_rename_entries() {
+        let (_dir, root) = sandbox_root();
+        std::fs::create_dir_all(root.join("src/inner")).unwrap();
+        std::fs::write(root.join("src/inner/a.txt"), "a").unwrap();
+
+        move_entry(&root.join("src"), &root.join("moved")).unwrap();
+        assert!(!root.join("src").exists());
+        assert_eq!(
+            std::fs::read_to_string(root.join("moved/inner/a.txt")).unwrap(),
+            "a"
+        );
+    }
+
+    #[cfg(all(feature = "server", unix))]
+    #[test]
+    fn failed_moves_leave_no_copy_behind() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (_dir, root) = sandbox_root();
+        std::fs::create_dir(root.join("locked")).unwrap();
+        std::fs::write(root.join("locked/a.txt"), "a").unwrap();
+        // The file can be read, so copied, but not taken out of `locked`.
+        std::fs::set_permissions(root.join("locked"), std::fs::Permissions::from_mode(0o555))
+            .unwrap();
+
+        let moved = move_entry(&root.join("locked/a.txt"), &root.join("a.txt"));
+        std::fs::set_permissions(root.join("locked"), std::fs::Permissions::from_mode(0o755))
+            .unwrap();
+        // Root moves anything.
+        if moved.is_ok() {
+            return;
+        }
+        assert_eq!(
+            moved.unwrap_err().kind(),
+            std::io::ErrorKind::PermissionDenied
+        );
+        assert!(!root.join("a.txt").exists());
+        assert!(root.join("locked/a.txt").is_file());
+    }
+
+    /// Archives `path` in `root` the way `tar_stream` does, collecting the
+    /// chunks.
+    #[cfg(feature = "server")]
+    fn tar_chunks(root: &std::path::Path, path: &str) -> Vec<Vec<u8>> {
+        let mut chunks = Vec::new();
+        let writer = ChunkWriter::new(|chunk| {
+            chunks.push(chunk);
+            true
+        });
+        let mut tar = tar::Builder::new(writer);
+        tar.follow_symlinks(false);
+        tar_entry(&mut tar, &root.join(path), root).unwrap();
+        std::io::Write::flush(&mut tar.into_inner().unwrap()).unwrap();
+        chunks
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn tar_archives_read_back() {
+        let (_dir, root) = sandbox_root();
+        std::fs::create_dir_all(root.join("d/e")).unwrap();
+        std::fs::write(root.join("d/b.txt"), "bee").unwrap();
+        // Spans several chunks.
+        std::fs::write(root.join("d/e/a.bin"), vec![7u8; TAR_CHUNK_LEN * 2 + 1]).unwrap();
+
+        let chunks = tar_chunks(&root, "d");
+        assert!(chunks.len() > 2);
+        assert!(chunks[..chunks.len() - 1]
+            .iter()
+            .all(|chunk| chunk.len() == TAR_CHUNK_LEN));
+
+        let mut archive = tar::Archive::new(std::io::Cursor::new(chunks.concat()));
+        let entries: Vec<(String, Vec<u8>)> = archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let name = entry.path().unwrap().to_string_lossy().into_owned();
+                let mut contents = Vec::new();
+                std::io::Read::read_to_end(&mut entry, &mut contents).unwrap();
+                (name, contents)
+            })
+            .collect();
+        let names: Vec<&str> = entries.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["d", "d/b.txt", "d/e", "d/e/a.bin"]);
+        assert_eq!(entries[1].1, b"bee");
+        assert_eq!(entries[3].1.len(), TAR_CHUNK_LEN * 2 + 1);
+    }
+
+    #[cfg(all(feature = "server", unix))]
+    #[test]
+    fn tar_archives_stop_at_unreadable_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (_dir, root) = sandbox_root();
+        std::fs::create_dir_all(root.join("d")).unwrap();
+        std::fs::write(root.join("d/secret"), "x").unwrap();
+        std::fs::set_permissions(root.join("d/secret"), std::fs::Permissions::from_mode(0o0))
+            .unwrap();
+        // Root reads anything.
+        if std::fs::read(root.join("d/secret")).is_ok() {
+            return;
+        }
+
+        let mut tar = tar::Builder::new(ChunkWriter::new(|_| true));
+        assert!(tar_entry(&mut tar, &root.join("d"), &root).is_err());
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn dir_pages_count_every_entry() {
+        let (_dir, root) = sandbox_root();
+        for name in ["c", "a", "b"] {
+            std::fs::write(root.join(name), "").unwrap();
+        }
+        std::fs::create_dir(root.join("z")).unwrap();
+
+        let page = dir_page(&root, "/".to_string(), 1, 2).unwrap();
+        let names: Vec<&str> = page
+            .entries
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect();
+        // Of `z`, `a`, `b`, `c`: directories come first.
+        assert_eq!(names, ["a", "b"]);
+        assert_eq!(page.total, 4);
+        assert!(dir_page(&root, "/".to_string(), 4, 2)
+            .unwrap()
+            .entries
+            .is_empty());
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn directories_stream_in_batches() {
+        let (_dir, root) = sandbox_root();
+        for i in 0..STREAM_DIR_BATCH * 2 + 1 {
+            std::fs::write(root.join(format!("{i}.txt")), "").unwrap();
+        }
+        std::fs::write(root.join(ORDER_FILE), "").unwrap();
+
+        let mut batches = Vec::new();
+        let entries = std::fs::read_dir(&root).unwrap();
+        stream_entries(entries, "/", &mut |batch| {
+            batches.push(batch);
+            true
+        });
+        let sizes: Vec<usize> = batches.iter().map(Vec::len).collect();
+        assert_eq!(sizes, [STREAM_DIR_BATCH, STREAM_DIR_BATCH, 1]);
+
+        let mut names: Vec<String> = batches.concat().into_iter().map(|e| e.path).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), STREAM_DIR_BATCH * 2 + 1);
+        assert!(names
+            .iter()
+            .all(|name| name.starts_with('/') && name.ends_with(".txt")));
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn directory_streams_stop_when_dropped() {
+        let (_dir, root) = sandbox_root();
+        for i in 0..STREAM_DIR_BATCH * 3 {
+            std::fs::write(root.join(format!("{i}.txt")), "").unwrap();
+        }
+
+        // The client went away as soon as the first batch was sent.
+        let (mut tx, rx) = futures::channel::mpsc::channel(STREAM_BUFFER);
+        drop(rx);
+        let mut sent = 0;
+        let entries = std::fs::read_dir(&root).unwrap();
+        stream_entries(entries, "/", &mut |batch| {
+            sent += 1;
+            futures::executor::block_on(tx.send(batch)).is_ok()
+        });
+        assert_eq!(sent, 1);
+    }
+
+    #[cfg(all(feature = "server", unix))]
+    #[test]
+    fn dir_versions_change_with_the_entries() {
+        let (_dir, root) = sandbox_root();
+        std::fs::write(root.join("a.txt"), "a").unwrap();
+        // File times are coarse; start each step from a known old one so a
+        // change can't land on the same tick.
+        let reset = || {
+            let old = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1);
+            std::fs::File::open(&root)
+                .unwrap()
+                .set_modified(old)
+                .unwrap();
+            dir_version_of(&root, "/").unwrap()
+        };
+
+        let version = reset();
+        assert_eq!(dir_version_of(&root, "/").unwrap(), version);
+        std::fs::write(root.join("a.txt"), "edited").unwrap();
+        assert_eq!(dir_version_of(&root, "/").unwrap(), version);
+
+        let changes: [&dyn Fn(); 3] = [
+            &|| std::fs::write(root.join("b.txt"), "b").unwrap(),
+            &|| std::fs::rename(root.join("b.txt"), root.join("c.txt")).unwrap(),
+            &|| std::fs::remove_file(root.join("c.txt")).unwrap(),
+        ];
+        for change in changes {
+            let version = reset();
+            change();
+            assert_ne!(dir_version_of(&root, "/").unwrap(), version);
+        }
+
+        assert!(matches!(
+            dir_version_of(&root.join("a.txt"), "/a.txt"),
+            Err(ApiError::InvalidInput(_))
+        ));
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn manual_orders_round_trip_through_listings() {
+        let (_dir, root) = sandbox_root();
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            std::fs::write(root.join(name), name).unwrap();
+        }
+        std::fs::create_dir(root.join("d")).unwrap();
+
+        let order = ["c.txt", "d", "a.txt", "gone.txt"].map(String::from);
+        save_manual_order(&root, "/", &order).unwrap();
+        std::fs::write(root.join("0-new.txt"), "").unwrap();
+
+        let names: Vec<String> = list_entries(&root, "/", SortKey::Manual)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect();
+        // Directories first; entries missing from the order follow by name.
+        assert_eq!(names, ["d", "c.txt", "a.txt", "0-new.txt", "b.txt"]);
+        let nested = list_entries(&root.join("d"), "/d", SortKey::Name).unwrap();
+        assert!(nested.is_empty());
+        std::fs::write(root.join("d/e.txt"), "").unwrap();
+        let nested = list_entries(&root.join("d"), "/d", SortKey::Name).unwrap();
+        assert_eq!(nested[0].path, "/d/e.txt");
+
+        assert!(save_manual_order(&root, "/", &["../x".to_string()]).is_err());
+        assert!(save_manual_order(&root.join("a.txt"), "/a.txt", &[]).is_err());
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn zips_hold_files_and_nested_directories() {
+        let (_dir, root) = sandbox_root();
+        std::fs::create_dir_all(root.join("d/e/empty")).unwrap();
+        std::fs::write(root.join("a.txt"), "a").unwrap();
+        std::fs::write(root.join("d/b.txt"), "b").unwrap();
+        std::fs::write(root.join("d/e/c.txt"), "c").unwrap();
+
+        // `d/e/c.txt` is zipped once, as part of `d`.
+        let sources = outermost_paths(vec![
+            root.join("d/e/c.txt"),
+            root.join("a.txt"),
+            root.join("d"),
+        ]);
+        let archive = root.join("selection.zip");
+        write_zip(&sources, &root, &archive, "selection.zip").unwrap();
+
+        let mut zip = zip::ZipArchive::new(std::fs::File::open(&archive).unwrap()).unwrap();
+        let mut names: Vec<String> = zip.file_names().map(String::from).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            ["a.txt", "d/", "d/b.txt", "d/e/", "d/e/c.txt", "d/e/empty/"]
+        );
+        let mut contents = String::new();
+        std::io::Read::read_to_string(&mut zip.by_name("d/e/c.txt").unwrap(), &mut contents)
+            .unwrap();
+        assert_eq!(contents, "c");
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn watched_files_report_changes() {
+        use futures::StreamExt;
+        use std::time::Duration;
+
+        let (_dir, root) = sandbox_root();
+        let file = root.join("watched.txt");
+        std::fs::write(&file, "v1").unwrap();
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let (tx, mut rx) = futures::channel::mpsc::unbounded();
+            let watch = tokio::spawn(watch_path(file.clone(), Duration::from_millis(5), tx));
+            let timeout = Duration::from_secs(5);
+
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            std::fs::write(&file, "version 2").unwrap();
+            let event = tokio::time::timeout(timeout, rx.next()).await.unwrap();
+            assert_eq!(event, Some(FileEvent::Modified));
+
+            std::fs::remove_file(&file).unwrap();
+            let event = tokio::time::timeout(timeout, rx.next()).await.unwrap();
+            assert_eq!(event, Some(FileEvent::Removed));
+
+            // Dropping the receiver ends the watch.
+            drop(rx);
+            tokio::time::timeout(timeout, watch).await.unwrap().unwrap();
+        });
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn image_info_reads_the_headers() {
+        let (_dir, root) = sandbox_root();
+        image::RgbImage::new(48, 32)
+            .save(root.join("photo.png"))
+            .unwrap();
+        image::RgbImage::new(7, 5)
+            .save(root.join("photo.jpg"))
+            .unwrap();
+        std::fs::write(root.join("notes.txt"), "not an image").unwrap();
+
+        let png = read_image_info(&root.join("photo.png"), "/photo.png").unwrap();
+        assert_eq!((png.width, png.height), (48, 32));
+        assert_eq!(png.format, "image/png");
+        assert_eq!(
+            (png.orientation, png.taken_at, png.camera),
+            (None, None, None)
+        );
+
+        let jpeg = read_image_info(&root.join("photo.jpg"), "/photo.jpg").unwrap();
+        assert_eq!((jpeg.width, jpeg.height), (7, 5));
+        assert_eq!(jpeg.format, "image/jpeg");
+
+        assert_eq!(
+            read_image_info(&root.join("notes.txt"), "/notes.txt"),
+            Err(ApiError::InvalidInput("/notes.txt: not an image".into()))
+        );
+    }
+
+    #[test]
+    fn fit_within_keeps_the_aspect_ratio() {
+        assert_eq!(fit_within(400, 200, 100), (100, 50));
+        assert_eq!(fit_within(300, 900, 90), (30, 90));
+        assert_eq!(fit_within(1000, 1, 100), (100, 1));
+        // Small images aren't scaled up.
+        assert_eq!(fit_within(40, 20, 100), (40, 20));
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn thumbnails_are_scaled_pngs() {
+        let (_dir, root) = sandbox_root();
+        let source = root.join("wide.png");
+        image::RgbImage::new(40, 20).save(&source).unwrap();
+
+        let bytes = render_thumbnail(&source, "/wide.png", 10).unwrap();
+        let thumbnail = image::load_from_memory(&bytes).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (10, 5));
+
+        std::fs::write(root.join("notes.txt"), "not an image").unwrap();
+        assert!(matches!(
+            render_thumbnail(&root.join("notes.txt"), "/notes.txt", 10),
+            Err(ApiError::InvalidInput(_))
+        ));
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn thumbnails_are_rendered_once_per_version() {
+        let (_dir, root) = sandbox_root();
+        let source = root.join("photo.png");
+        std::fs::write(&source, "v1").unwrap();
+
+        let calls = std::cell::Cell::new(0);
+        let render = |_: &std::path::Path, _: &str, max_dim: u32| {
+            calls.set(calls.get() + 1);
+            Ok(vec![calls.get() as u8; max_dim as usize])
+        };
+        let first = cached_thumbnail(&source, "/photo.png", 2, render).unwrap();
+        assert_eq!(first, [1, 1]);
+        assert_eq!(
+            cached_thumbnail(&source, "/photo.png", 2, render).unwrap(),
+            first
+        );
+        assert_eq!(calls.get(), 1);
+
+        // Another size or new contents miss the cache.
+        assert_eq!(
+            cached_thumbnail(&source, "/photo.png", 3, render).unwrap(),
+            [2, 2, 2]
+        );
+        std::fs::write(&source, "version 2").unwrap();
+        cached_thumbnail(&source, "/photo.png", 2, render).unwrap();
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[test]
+    fn frame_times_split_the_video_evenly() {
+        assert_eq!(frame_times(10.0, 4), [1.25, 3.75, 6.25, 8.75]);
+        assert_eq!(frame_times(3.0, 1), [1.5]);
+        assert!(frame_times(10.0, 0).is_empty());
+        assert!(frame_times(0.0, 4).is_empty());
+        assert!(frame_times(f64::NAN, 4).is_empty());
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn video_frames_are_extracted_once_per_version() {
+        let (_dir, root) = sandbox_root();
+        let video = root.join("clip.mp4");
+        std::fs::write(&video, "v1").unwrap();
+
+        let calls = std::cell::Cell::new(0);
+        let extract = |_: &std::path::Path, _: &str, count: usize| {
+            calls.set(calls.get() + 1);
+            Ok(vec![vec![calls.get() as u8]; count])
+        };
+        let first = cached_video_frames(&video, "/clip.mp4", 3, extract).unwrap();
+        assert_eq!(first, [[1], [1], [1]]);
+        assert_eq!(
+            cached_video_frames(&video, "/clip.mp4", 3, extract).unwrap(),
+            first
+        );
+        assert_eq!(calls.get(), 1);
+
+        // A different count or contents misses the cache.
+        assert_eq!(
+            cached_video_frames(&video, "/clip.mp4", 2, extract)
+                .unwrap()
+                .len(),
+            2
+        );
+        std::fs::write(&video, "version 2").unwrap();
+        cached_video_frames(&video, "/clip.mp4", 3, extract).unwrap();
+        assert_eq!(calls.get(), 3);
+
+        let failing =
+            |_: &std::path::Path, _: &str, _: usize| Err(ApiError::Unsupported("no ffmpeg".into()));
+        assert!(cached_video_frames(&video, "/clip.mp4", 5, failing).is_err());
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn outermost_paths_drop_nested_and_repeated_paths() {
+        let paths = ["/a/b/c", "/b", "/a", "/a-b", "/b", "/a/b"]
+            .map(std::path::PathBuf::from)
+            .to_vec();
+        assert_eq!(
+            outermost_paths(paths),
+            ["/a", "/a-b", "/b"].map(std::path::PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn numbered_names_go_before_the_extension() {
+        assert_eq!(numbered_name("report.txt", 2), "report (2).txt");
+        assert_eq!(numbered_name("archive.tar.gz", 3), "archive.tar (3).gz");
+        assert_eq!(numbered_name("notes", 2), "notes (2)");
+        assert_eq!(numbered_name(".bashrc", 2), ".bashrc (2)");
+    }
+
+    #[test]
+    fn copy_names_count_from_the_second_copy() {
+        assert_eq!(copy_name("report.txt", 1, false), "report copy.txt");
+        assert_eq!(copy_name("report.txt", 2, false), "report copy 2.txt");
+        assert_eq!(copy_name("v1.2", 1, true), "v1.2 copy");
+        assert_eq!(copy_name(".env", 3, false), ".env copy 3");
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn duplicates_take_the_next_free_name() {
+        let (_dir, root) = sandbox_root();
+        let report = root.join("report.txt");
+        std::fs::write(&report, "original").unwrap();
+
+        let first = duplicate(&report, "/report.txt").unwrap();
+        let second = duplicate(&report, "/report.txt").unwrap();
+        assert_eq!(first, root.join("report copy.txt"));
+        assert_eq!(second, root.join("report copy 2.txt"));
+        for path in [&report, &first, &second] {
+            assert_eq!(std::fs::read_to_string(path).unwrap(), "original");
+        }
+
+        // A name taken by something else is skipped, not overwritten.
+        std::fs::write(root.join("report copy 3.txt"), "mine").unwrap();
+        let fourth = duplicate(&report, "/report.txt").unwrap();
+        assert_eq!(fourth, root.join("report copy 4.txt"));
+        assert_eq!(
+            std::fs::read_to_string(root.join("report copy 3.txt")).unwrap(),
+            "mine"
+        );
+
+        std::fs::create_dir_all(root.join("v1.2/inner")).unwrap();
+        let copy = duplicate(&root.join("v1.2"), "/v1.2").unwrap();
+        assert_eq!(copy, root.join("v1.2 copy"));
+        assert!(copy.join("inner").is_dir());
+    }
+
+    #[cfg(all(feature = "server", unix))]
+    #[test]
+    fn copies_keep_symlinks_as_links() {
+        let (_dir, root) = sandbox_root();
+        std::fs::create_dir(root.join("d")).unwrap();
+        std::fs::write(root.join("d/file"), "contents").unwrap();
+        std::os::unix::fs::symlink("file", root.join("d/link")).unwrap();
+
+        copy_recursive(&root.join("d"), &root.join("copy")).unwrap();
+        assert_eq!(
+            std::fs::read_link(root.join("copy/link")).unwrap(),
+            std::path::PathBuf::from("file")
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("copy/link")).unwrap(),
+            "contents"
+        );
+
+        copy_recursive(&root.join("d/link"), &root.join("link copy")).unwrap();
+        assert!(std::fs::symlink_metadata(root.join("link copy"))
+            .unwrap()
+            .is_symlink());
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn manual_order_keeps_directories_first() {
+        let (_dir, root) = sandbox_root();
+        for name in ["a", "b", "c"] {
+            std::fs::write(root.join(name), "").unwrap();
+        }
+        for name in ["x", "y"] {
+            std::fs::create_dir(root.join(name)).unwrap();
+        }
+        std::fs::write(root.join(ORDER_FILE), "c\nb\ny").unwrap();
+
+        let entries = list_entries(&root, "/", SortKey::Manual).unwrap();
+        let names: Vec<&str> = entries.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, ["y", "x", "c", "b", "a"]);
+    }
+
+    /// Matches of `pattern` below `root` as `path:line_no`.
+    #[cfg(feature = "server")]
+    fn grep_matches(
+        root: &std::path::Path,
+        pattern: &str,
+        deadline: std::time::Instant,
+    ) -> Vec<String> {
+        let regex = grep_regex(pattern, GrepOptions::default()).unwrap();
+        let mut found = Vec::new();
+        let mut send = |found_match: GrepMatch| {
+            found.push(format!("{}:{}", found_match.path, found_match.line_no));
+            true
+        };
+        grep_tree(root.to_path_buf(), root, &regex, &mut send, deadline);
+        found
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn grep_visits_files_in_name_order() {
+        let (_dir, root) = sandbox_root();
+        std::fs::create_dir(root.join("d")).unwrap();
+        std::fs::write(root.join("b.txt"), "needle\nhay\nneedle\n").unwrap();
+        std::fs::write(root.join("d/a.txt"), "hay needle").unwrap();
+        std::fs::write(root.join("binary"), b"needle\0").unwrap();
+
+        let deadline = std::time::Instant::now() + GREP_TIME_BUDGET;
+        assert_eq!(
+            grep_matches(&root, "needle", deadline),
+            ["/b.txt:1", "/b.txt:3", "/d/a.txt:1"]
+        );
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn grep_stops_at_its_budget() {
+        let (_dir, root) = sandbox_root();
+        std::fs::write(
+            root.join("big.txt"),
+            "needle\n".repeat(GREP_MAX_MATCHES + 10),
+        )
+        .unwrap();
+
+        let deadline = std::time::Instant::now() + GREP_TIME_BUDGET;
+        assert_eq!(
+            grep_matches(&root, "needle", deadline).len(),
+            GREP_MAX_MATCHES
+        );
+
+        // Past the deadline, not even the first line of a file is searched.
+        let (_dir, root) = sandbox_root();
+        std::fs::write(root.join("big.txt"), "needle\n").unwrap();
+        let mut found = 0;
+        let regex = grep_regex("needle", GrepOptions::default()).unwrap();
+        let mut send = |_| {
+            found += 1;
+            true
+        };
+        let mut remaining = GREP_MAX_MATCHES;
+        let passed = std::time::Instant::now();
+        assert!(!grep_file(
+            &root.join("big.txt"),
+            "/big.txt",
+            &regex,
+            &mut send,
+            &mut remaining,
+            passed
+        ));
+        assert_eq!(found, 0);
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn ranges_are_clamped_to_the_file() {
+        let (_dir, root) = sandbox_root();
+        let file = root.join("data.bin");
+        std::fs::write(&file, b"0123456789").unwrap();
+
+        let read = |offset, length| read_file_range(&file, "/data.bin", offset, length);
+        assert_eq!(read(0, 4).unwrap(), b"0123");
+        assert_eq!(read(6, 100).unwrap(), b"6789");
+        // The end of the file is a valid, empty range; past it isn't.
+        assert_eq!(read(10, 4).unwrap(), b"");
+        assert!(matches!(read(11, 4), Err(ApiError::InvalidInput(_))));
+        assert!(matches!(
+            read_file_range(&root, "/", 0, 4),
+            Err(ApiError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            read_file_range(&root.join("missing"), "/missing", 0, 4),
+            Err(ApiError::NotFound(_))
+        ));
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn ranges_are_capped_at_the_maximum_read() {
+        let (_dir, root) = sandbox_root();
+        let file = root.join("big.bin");
+        let len = MAX_READ_RANGE + 10;
+        std::fs::File::create(&file).unwrap().set_len(len).unwrap();
+
+        let bytes = read_file_range(&file, "/big.bin", 0, u64::MAX).unwrap();
+        assert_eq!(bytes.len() as u64, MAX_READ_RANGE);
+        let bytes = read_file_range(&file, "/big.bin", MAX_READ_RANGE, u64::MAX).unwrap();
+        assert_eq!(bytes.len(), 10);
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn appends_keep_what_was_there() {
+        let (_dir, root) = sandbox_root();
+        let file = root.join("log.txt");
+
+        append_to(&file, "/log.txt", b"one\n", true).unwrap();
+        append_to(&file, "/log.txt", b"two\n", true).unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "one\ntwo\n");
+
+        let missing = root.join("missing.txt");
+        assert!(matches!(
+            append_to(&missing, "/missing.txt", b"x", false),
+            Err(ApiError::NotFound(_))
+        ));
+        assert!(!missing.exists());
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn concurrent_appends_dont_interleave() {
+        let (_dir, root) = sandbox_root();
+        let file = root.join("log.txt");
+
+        std::thread::scope(|scope| {
+            for line in ["a", "b", "c", "d"] {
+                let file = &file;
+                scope.spawn(move || {
+                    let data = format!("{}\n", line.repeat(64 * 1024));
+                    for _ in 0..8 {
+                        append_to(file, "/log.txt", data.as_bytes(), true).unwrap();
+                    }
+                });
+            }
+        });
+
+        let text = std::fs::read_to_string(&file).unwrap();
+        assert_eq!(text.lines().count(), 32);
+        for line in text.lines() {
+            assert_eq!(line.len(), 64 * 1024);
+            assert!(line.bytes().all(|byte| byte == line.as_bytes()[0]));
+        }
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn digests_match_the_reference_vectors() {
+        let abc = || std::io::Cursor::new(b"abc");
+        assert_eq!(
+            hex_digest::<sha1::Sha1>(abc()).unwrap(),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            hex_digest::<sha2::Sha256>(abc()).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hex_digest::<sha2::Sha512>(abc()).unwrap(),
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+             2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+        );
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn digests_span_chunk_boundaries() {
+        use sha2::Digest;
+
+        // A few chunks and a bit, so the last read is short.
+        let data: Vec<u8> = (0..200_000u32).map(|index| index as u8).collect();
+        let expected: String = sha2::Sha256::digest(&data)
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        assert_eq!(
+            hex_digest::<sha2::Sha256>(data.as_slice()).unwrap(),
+            expected
+        );
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn only_files_are_hashed() {
+        let (_dir, root) = sandbox_root();
+        std::fs::write(root.join("abc.txt"), "abc").unwrap();
+        assert_eq!(
+            hash_path(&root.join("abc.txt"), "/abc.txt", HashAlgo::Sha1).unwrap(),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert!(matches!(
+            hash_path(&root, "/", HashAlgo::Sha256),
+            Err(ApiError::InvalidInput(_))
+        ));
+    }
+
+    #[cfg(all(feature = "server", unix))]
+    #[test]
+    fn tree_sizes_add_up_files_without_following_links() {
+        let (_dir, root) = sandbox_root();
+        std::fs::create_dir_all(root.join("d/e")).unwrap();
+        std::fs::write(root.join("d/a"), [0; 100]).unwrap();
+        std::fs::write(root.join("d/e/b"), [0; 20]).unwrap();
+        std::os::unix::fs::symlink("/", root.join("d/e/link")).unwrap();
+        let link_len = std::fs::symlink_metadata(root.join("d/e/link"))
+            .unwrap()
+            .len();
+
+        assert_eq!(tree_size(&root.join("d/a")).unwrap(), 100);
+        assert_eq!(tree_size(&root.join("d")).unwrap(), 120 + link_len);
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn only_small_text_files_are_read_whole() {
+        let (_dir, root) = sandbox_root();
+        std::fs::write(root.join("notes.txt"), "héllo").unwrap();
+        std::fs::write(root.join("binary"), [0xff, 0xfe]).unwrap();
+        let large = std::fs::File::create(root.join("large")).unwrap();
+        large.set_len(MAX_READ_RANGE + 1).unwrap();
+
+        assert_eq!(
+            read_text(&root.join("notes.txt"), "/notes.txt").unwrap(),
+            "héllo"
+        );
+        for (path, name) in [(root.clone(), "/"), (root.join("binary"), "/binary")] {
+            assert!(matches!(
+                read_text(&path, name),
+                Err(ApiError::InvalidInput(_))
+            ));
+        }
+        assert!(matches!(
+            read_text(&root.join("large"), "/large"),
+            Err(ApiError::InvalidInput(message)) if message.contains("too large")
+        ));
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn uploads_never_replace_existing_files() {
+        let (_dir, root) = sandbox_root();
+        let first = save_upload(&root, "/", "image.png", b"one").unwrap();
+        let second = save_upload(&root, "/", "image.png", b"two").unwrap();
+        assert_eq!(first, root.join("image.png"));
+        assert_eq!(second, root.join("image (2).png"));
+        assert_eq!(std::fs::read(&first).unwrap(), b"one");
+        assert_eq!(std::fs::read(&second).unwrap(), b"two");
+
+        assert!(matches!(
+            save_upload(&first, "/image.png", "x", b""),
+            Err(ApiError::InvalidInput(_))
+        ));
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn recent_paths_are_capped_most_recent_first() {
+        let mut recent = std::collections::VecDeque::new();
+        for index in 0..RECENT_CAPACITY + 5 {
+            push_recent(&mut recent, std::path::Path::new(&format!("/{index}")));
+        }
+        assert_eq!(recent.len(), RECENT_CAPACITY);
+        assert_eq!(
+            recent[0],
+            std::path::Path::new(&format!("/{}", RECENT_CAPACITY + 4))
+        );
+        assert_eq!(recent[RECENT_CAPACITY - 1], std::path::Path::new("/5"));
+
+        // Touching a path again moves it up instead of repeating it.
+        push_recent(&mut recent, std::path::Path::new("/50"));
+        assert_eq!(recent.len(), RECENT_CAPACITY);
+        assert_eq!(recent[0], std::path::Path::new("/50"));
+        let repeats = recent
+            .iter()
+            .filter(|path| path.as_path() == std::path::Path::new("/50"));
+        assert_eq!(repeats.count(), 1);
+    }
+
+    /// `<root>/a/b/c.txt`, `<root>/d.txt` and the empty `<root>/e`.
+    #[cfg(feature = "server")]
+    fn tree_fixture() -> (tempfile::TempDir, std::path::PathBuf) {
+        let (dir, root) = sandbox_root();
+        std::fs::create_dir_all(root.join("a/b")).unwrap();
+        std::fs::write(root.join("a/b/c.txt"), "c").unwrap();
+        std::fs::write(root.join("d.txt"), "d").unwrap();
+        std::fs::create_dir(root.join("e")).unwrap();
+        (dir, root)
+    }
+
+    /// The tree below `root` with the full node budget.
+    #[cfg(feature = "server")]
+    fn build_tree(root: &std::path::Path, depth: usize) -> TreeNode {
+        let mut budget = DIR_TREE_BUDGET;
+        tree_node(root, "/".into(), depth, &mut budget).unwrap()
+    }
+
+    #[cfg(feature = "server")]
+    fn child_names(node: &TreeNode) -> Vec<&str> {
+        node.children
+            .iter()
+            .map(|child| child.name.as_str())
+            .collect()
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn trees_stop_at_the_depth_limit() {
+        let (_dir, root) = tree_fixture();
+
+        let tree = build_tree(&root, 1);
+        assert_eq!(child_names(&tree), ["a", "d.txt", "e"]);
+        assert!(tree.truncated);
+        assert!(tree.children[0].truncated);
+        assert!(tree.children[0].children.is_empty());
+        // Nothing was left out of the empty directory.
+        assert!(!tree.children[2].truncated);
+
+        let tree = build_tree(&root, 3);
+        assert!(!tree.truncated);
+        assert_eq!(child_names(&tree.children[0].children[0]), ["c.txt"]);
+        assert!(!tree.children[0].children[0].children[0].is_dir);
+        assert_eq!(tree.children[0].children[0].children[0].path, "/a/b/c.txt");
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn trees_stop_at_the_node_budget() {
+        let (_dir, root) = tree_fixture();
+        let mut budget = 2;
+
+        let tree = tree_node(&root, "/".into(), 3, &mut budget).unwrap();
+        assert_eq!(budget, 0);
+        assert!(tree.truncated);
+        assert_eq!(child_names(&tree), ["a"]);
+        assert_eq!(child_names(&tree.children[0]), ["b"]);
+        assert!(tree.children[0].children[0].children.is_empty());
+        assert!(tree.children[0].children[0].truncated);
+    }
+
+    #[cfg(all(feature = "server", unix))]
+    #[test]
+    fn trees_skip_symlinks() {
+        let (_dir, root) = tree_fixture();
+        std::os::unix::fs::symlink(root.join("a"), root.join("link")).unwrap();
+
+        let tree = build_tree(&root, 3);
+        assert_eq!(child_names(&tree), ["a", "d.txt", "e"]);
+    }
+}
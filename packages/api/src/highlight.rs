@@ -0,0 +1,160 @@
+//! Syntax highlighting for the code editor, done on the server so the client
+//! doesn't have to ship syntect's grammars.
+
+use dioxus::prelude::*;
+
+#[cfg(feature = "server")]
+use crate::ApiError;
+
+/// Longest code, in bytes, `highlight` agrees to highlight.
+pub const MAX_HIGHLIGHT_LEN: usize = 256 * 1024;
+
+#[cfg(feature = "server")]
+mod engine {
+    use crate::ApiError;
+    use std::collections::HashMap;
+    use std::sync::{LazyLock, Mutex};
+    use std::time::Instant;
+    use syntect::highlighting::ThemeSet;
+    use syntect::html::highlighted_html_for_string;
+    use syntect::parsing::SyntaxSet;
+
+    pub const LIGHT_THEME: &str = "InspiredGitHub";
+    pub const DARK_THEME: &str = "base16-ocean.dark";
+
+    /// Bytes of code and HTML kept in the cache. Past it, the least recently
+    /// used snippets are dropped.
+    const CACHE_BUDGET: usize = 16 * 1024 * 1024;
+
+    static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+    static THEMES: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+
+    /// Code, language and whether the dark theme was used.
+    type Key = (String, String, bool);
+
+    /// Highlighted HTML, with when each was last used, and the bytes held.
+    #[derive(Default)]
+    struct Cache {
+        entries: HashMap<Key, (String, Instant)>,
+        bytes: usize,
+    }
+
+    static CACHE: LazyLock<Mutex<Cache>> = LazyLock::new(Default::default);
+
+    fn cost((code, language, _): &Key, html: &str) -> usize {
+        code.len() + language.len() + html.len()
+    }
+
+    /// Highlights `code` as `language`, without the cache.
+    pub fn render(code: &str, language: &str, dark: bool) -> Result<String, ApiError> {
+        let syntax = SYNTAXES
+            .find_syntax_by_token(language)
+            .unwrap_or_else(|| SYNTAXES.find_syntax_plain_text());
+        let theme = &THEMES.themes[if dark { DARK_THEME } else { LIGHT_THEME }];
+        highlighted_html_for_string(code, &SYNTAXES, syntax, theme)
+            .map_err(|err| ApiError::Internal(format!("highlighting failed: {err}")))
+    }
+
+    /// Highlights `code` as `language`, from the cache when the same code was
+    /// highlighted the same way before.
+    pub fn cached(code: String, language: String, dark: bool) -> Result<String, ApiError> {
+        let key = (code, language, dark);
+        if let Some((html, used)) = CACHE.lock().unwrap().entries.get_mut(&key) {
+            *used = Instant::now();
+            return Ok(html.clone());
+        }
+
+        let html = render(&key.0, &key.1, dark)?;
+
+        let mut cache = CACHE.lock().unwrap();
+        let cost = cost(&key, &html);
+        while cache.bytes + cost > CACHE_BUDGET {
+            let Some(oldest) = cache
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            if let Some((html, _)) = cache.entries.remove(&oldest) {
+                cache.bytes -= self::cost(&oldest, &html);
+            }
+        }
+        if let Some((replaced, _)) = cache
+            .entries
+            .insert(key.clone(), (html.clone(), Instant::now()))
+        {
+            cache.bytes -= self::cost(&key, &replaced);
+        }
+        cache.bytes += cost;
+
+        Ok(html)
+    }
+}
+
+/// Highlights `code` as `language` (a name or file extension such as `"rust"`
+/// or `"rs"`), returning HTML with inline styles from a light or `dark`
+/// theme. Unknown languages are rendered as plain text; code longer than
+/// [`MAX_HIGHLIGHT_LEN`] is refused.
+#[post("/api/highlight")]
+pub async fn highlight(
+    code: String,
+    language: String,
+    dark: bool,
+) -> Result<String, ServerFnError> {
+    if code.len() > MAX_HIGHLIGHT_LEN {
+        return Err(ApiError::InvalidInput(format!(
+            "code longer than {MAX_HIGHLIGHT_LEN} bytes isn't highlighted"
+        ))
+        .into());
+    }
+
+    // Highlighting is CPU bound and can take a while on large inputs.
+    let html = tokio::task::spawn_blocking(move || engine::cached(code, language, dark))
+        .await
+        .map_err(|err| ApiError::Internal(err.to_string()))??;
+    Ok(html)
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::engine::*;
+
+    const SNIPPET: &str = "fn main() {\n    println!(\"hi\");\n}\n";
+
+    #[test]
+    fn rust_is_highlighted_in_both_themes() {
+        let light = render(SNIPPET, "rust", false).unwrap();
+        let dark = render(SNIPPET, "rs", true).unwrap();
+
+        for html in [&light, &dark] {
+            assert!(html.starts_with("<pre"));
+            // `fn` starts a keyword span.
+            assert!(html.contains(">fn"), "{html}");
+            assert!(html.contains("main"));
+        }
+        assert_ne!(light, dark);
+        assert_ne!(
+            light,
+            render(SNIPPET, "no such language", false).unwrap(),
+            "plain text isn't highlighted as Rust"
+        );
+    }
+
+    #[test]
+    fn cached_snippets_match_fresh_ones() {
+        let fresh = render(SNIPPET, "rust", true).unwrap();
+        for _ in 0..2 {
+            assert_eq!(
+                cached(SNIPPET.to_string(), "rust".to_string(), true).unwrap(),
+                fresh
+            );
+        }
+        // Same code in the other theme isn't served from the dark entry.
+        assert_ne!(
+            cached(SNIPPET.to_string(), "rust".to_string(), false).unwrap(),
+            fresh
+        );
+    }
+}
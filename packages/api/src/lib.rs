@@ -1,13 +1,42 @@
 //! This crate contains all shared fullstack server functions.
+#[cfg(any(feature = "echo", not(feature = "server")))]
 use dioxus::prelude::*;
+#[cfg(all(feature = "server", feature = "echo"))]
 use std::process::Command;
 
+mod error;
+pub use error::ApiError;
+
+#[cfg(all(feature = "server", any(feature = "fs", feature = "system")))]
+mod sandbox;
+
+mod highlight;
+pub use highlight::*;
+
+// Clients get every group of server functions; the features pick the ones
+// the server registers, and builds the dependencies of.
+#[cfg(any(feature = "fs", not(feature = "server")))]
+mod fs;
+#[cfg(any(feature = "fs", not(feature = "server")))]
+pub use fs::*;
+
+#[cfg(any(feature = "process", not(feature = "server")))]
+mod process;
+#[cfg(any(feature = "process", not(feature = "server")))]
+pub use process::*;
+
+#[cfg(any(feature = "system", not(feature = "server")))]
+mod system;
+#[cfg(any(feature = "system", not(feature = "server")))]
+pub use system::*;
+
 /// Echo the user input on the server.
+#[cfg(any(feature = "echo", not(feature = "server")))]
 #[post("/api/echo")]
 pub async fn echo(input: String) -> Result<String, ServerFnError> {
     Command::new("echo")
         .arg(&input)
-        .spawn()
-        .expect("error");
+        .status()
+        .map_err(|err| ApiError::io("echo", err))?;
     Ok(input)
 }
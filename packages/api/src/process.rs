@@ -0,0 +1,308 @@
+//! Process inspection: the processes running on the host, read from `/proc`,
+//! as a tree following their parent pids or as a flat list.
+
+#[cfg(feature = "server")]
+use crate::ApiError;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Pid of the synthetic root the process tree hangs off. It's the parent
+/// pid the kernel reports for `init` and `kthreadd`, and processes whose
+/// parent isn't listed are attached to it as well.
+pub const ROOT_PID: u32 = 0;
+
+/// Most nodes `process_tree` returns in one page.
+pub const MAX_PROCESS_PAGE: usize = 500;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub ppid: u32,
+    /// Executable name as the kernel reports it, truncated to 15 bytes.
+    pub name: String,
+    /// Full command line, empty for kernel threads.
+    pub command: String,
+    /// Resident memory, `None` for kernel threads.
+    pub rss_bytes: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessNode {
+    pub process: ProcessInfo,
+    /// Child processes by pid. Always empty in flat mode.
+    pub children: Vec<ProcessNode>,
+}
+
+/// A page of `process_tree` results.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessPage {
+    pub nodes: Vec<ProcessNode>,
+    /// Top-level nodes matching the filter across all pages.
+    pub total: usize,
+}
+
+/// Arranges `processes` into trees by parent pid, children sorted by pid.
+///
+/// The returned nodes are the children of the synthetic [`ROOT_PID`] root:
+/// processes whose parent pid is the root's or missing from `processes`.
+/// Processes only reachable through a cycle of parent pids are attached to
+/// the root at the lowest pid of the cycle, so nothing is dropped.
+pub fn build_process_tree(mut processes: Vec<ProcessInfo>) -> Vec<ProcessNode> {
+    fn attach(parent: u32, children: &mut HashMap<u32, Vec<ProcessInfo>>) -> Vec<ProcessNode> {
+        children
+            .remove(&parent)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|process| {
+                let children = attach(process.pid, children);
+                ProcessNode { process, children }
+            })
+            .collect()
+    }
+
+    processes.retain(|process| process.pid != ROOT_PID);
+    processes.sort_by_key(|process| process.pid);
+    let pids: HashSet<u32> = processes.iter().map(|process| process.pid).collect();
+    let mut children: HashMap<u32, Vec<ProcessInfo>> = HashMap::new();
+    for process in processes {
+        let parent = if process.ppid != process.pid && pids.contains(&process.ppid) {
+            process.ppid
+        } else {
+            ROOT_PID
+        };
+        children.entry(parent).or_default().push(process);
+    }
+
+    let mut roots = attach(ROOT_PID, &mut children);
+    // What's left hangs off cycles; each is cut above its lowest pid.
+    while let Some(process) = children
+        .values()
+        .flatten()
+        .min_by_key(|process| process.pid)
+        .cloned()
+    {
+        if let Some(siblings) = children.get_mut(&process.ppid) {
+            siblings.retain(|sibling| sibling.pid != process.pid);
+        }
+        let subtree = attach(process.pid, &mut children);
+        roots.push(ProcessNode {
+            process,
+            children: subtree,
+        });
+    }
+    roots.sort_by_key(|node| node.process.pid);
+    roots
+}
+
+/// Whether `process` matches the lowercased `filter`: its pid exactly, or
+/// part of its name or command line.
+fn matches(process: &ProcessInfo, filter: &str) -> bool {
+    process.pid.to_string() == filter
+        || process.name.to_lowercase().contains(filter)
+        || process.command.to_lowercase().contains(filter)
+}
+
+/// Keeps the nodes matching `filter` (a pid, or part of a name or command
+/// line, ignoring case) along with their ancestors, so the matches stay in
+/// place in the tree. An empty filter keeps everything.
+pub fn filter_process_tree(nodes: Vec<ProcessNode>, filter: &str) -> Vec<ProcessNode> {
+    fn keep(nodes: Vec<ProcessNode>, filter: &str) -> Vec<ProcessNode> {
+        nodes
+            .into_iter()
+            .filter_map(|node| {
+                let children = keep(node.children, filter);
+                (!children.is_empty() || matches(&node.process, filter)).then_some(ProcessNode {
+                    process: node.process,
+                    children,
+                })
+            })
+            .collect()
+    }
+
+    let filter = filter.trim().to_lowercase();
+    if filter.is_empty() {
+        return nodes;
+    }
+    keep(nodes, &filter)
+}
+
+/// Parses the pid, name and parent pid out of `/proc/<pid>/stat`. The name
+/// is wrapped in parentheses and may itself contain spaces and parentheses,
+/// so the fields after it are found from the last `)`.
+#[cfg(all(feature = "server", target_os = "linux"))]
+fn parse_stat(stat: &str) -> Option<(u32, String, u32)> {
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+    let pid = stat[..open].trim().parse().ok()?;
+    let name = stat.get(open + 1..close)?.to_string();
+    // State, then parent pid.
+    let ppid = stat[close + 1..].split_whitespace().nth(1)?.parse().ok()?;
+    Some((pid, name, ppid))
+}
+
+/// The process `pid`, or `None` if it exited while being read.
+#[cfg(all(feature = "server", target_os = "linux"))]
+fn read_process(pid: u32) -> Option<ProcessInfo> {
+    let dir = std::path::Path::new("/proc").join(pid.to_string());
+    let (pid, name, ppid) = parse_stat(&std::fs::read_to_string(dir.join("stat")).ok()?)?;
+    // Arguments are NUL-separated, with a trailing NUL.
+    let command = std::fs::read(dir.join("cmdline"))
+        .map(|bytes| {
+            String::from_utf8_lossy(&bytes)
+                .split('\0')
+                .filter(|arg| !arg.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default();
+    let rss_bytes = std::fs::read_to_string(dir.join("status"))
+        .ok()
+        .and_then(|status| {
+            let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+            let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+            Some(kib * 1024)
+        });
+    Some(ProcessInfo {
+        pid,
+        ppid,
+        name,
+        command,
+        rss_bytes,
+    })
+}
+
+/// Every process visible in `/proc`. Processes exiting during the scan are
+/// left out.
+#[cfg(feature = "server")]
+fn list_processes() -> Result<Vec<ProcessInfo>, ApiError> {
+    #[cfg(target_os = "linux")]
+    {
+        Ok(std::fs::read_dir("/proc")
+            .map_err(|err| ApiError::io("/proc", err))?
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+            .filter_map(read_process)
+            .collect())
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(ApiError::Unsupported(
+            "listing processes needs /proc, which this host doesn't have".to_string(),
+        ))
+    }
+}
+
+/// Returns up to `limit` (at most [`MAX_PROCESS_PAGE`]) processes matching
+/// `filter` (see [`filter_process_tree`]), starting at `offset`.
+///
+/// As a tree, pages count top-level nodes, each coming with the matching
+/// part of its subtree. With `flat`, pages count processes, sorted by pid
+/// and without children.
+#[post("/api/process_tree")]
+pub async fn process_tree(
+    filter: String,
+    flat: bool,
+    offset: usize,
+    limit: usize,
+) -> Result<ProcessPage, ServerFnError> {
+    let mut processes = list_processes()?;
+    let nodes = if flat {
+        processes.sort_by_key(|process| process.pid);
+        let nodes = processes
+            .into_iter()
+            .map(|process| ProcessNode {
+                process,
+                children: Vec::new(),
+            })
+            .collect();
+        filter_process_tree(nodes, &filter)
+    } else {
+        filter_process_tree(build_process_tree(processes), &filter)
+    };
+    Ok(ProcessPage {
+        total: nodes.len(),
+        nodes: nodes
+            .into_iter()
+            .skip(offset)
+            .take(limit.min(MAX_PROCESS_PAGE))
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, ppid: u32, name: &str) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            ppid,
+            name: name.to_string(),
+            command: String::new(),
+            rss_bytes: None,
+        }
+    }
+
+    /// `nodes` as `pid(children...)`, for compact comparisons.
+    fn shape(nodes: &[ProcessNode]) -> String {
+        nodes
+            .iter()
+            .map(|node| match node.children.as_slice() {
+                [] => node.process.pid.to_string(),
+                children => format!("{}({})", node.process.pid, shape(children)),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn processes_hang_off_their_parents() {
+        let tree = build_process_tree(vec![
+            process(30, 1, "child"),
+            process(1, 0, "init"),
+            process(2, 0, "kthreadd"),
+            process(10, 1, "sshd"),
+            process(11, 10, "bash"),
+            // Its parent already exited.
+            process(40, 99, "orphan"),
+            process(0, 0, "root"),
+        ]);
+        assert_eq!(shape(&tree), "1(10(11) 30) 2 40");
+    }
+
+    #[test]
+    fn parent_cycles_keep_every_process() {
+        let tree = build_process_tree(vec![
+            process(1, 0, "init"),
+            process(5, 6, "a"),
+            process(6, 5, "b"),
+            process(7, 7, "self"),
+        ]);
+        assert_eq!(shape(&tree), "1 5(6) 7");
+    }
+
+    #[test]
+    fn filters_keep_matches_with_their_ancestors() {
+        let mut processes = vec![
+            process(1, 0, "init"),
+            process(10, 1, "sshd"),
+            process(11, 10, "bash"),
+            process(12, 1, "cron"),
+        ];
+        processes[3].command = "/usr/sbin/CRON -f".to_string();
+        let tree = build_process_tree(processes);
+
+        assert_eq!(
+            shape(&filter_process_tree(tree.clone(), "BASH")),
+            "1(10(11))"
+        );
+        assert_eq!(
+            shape(&filter_process_tree(tree.clone(), "sbin/cron")),
+            "1(12)"
+        );
+        // Pids match whole, not in part.
+        assert_eq!(shape(&filter_process_tree(tree.clone(), " 1 ")), "1");
+        assert_eq!(shape(&filter_process_tree(tree.clone(), "nothing")), "");
+        assert_eq!(filter_process_tree(tree.clone(), "  "), tree);
+    }
+}
@@ -0,0 +1,359 @@
+//! Confines file-system server functions to a single root directory.
+//!
+//! Every path received from a client is interpreted relative to the sandbox
+//! root and canonicalized before use, so `..` segments and symlinks can't be
+//! used to reach files outside of it.
+//!
+//! A client session can narrow its own root to a directory below the global
+//! one with [`set_session_root`]. The session is identified by a signed,
+//! `HttpOnly` cookie, and its root applies to every path it sends from then
+//! on. Requests carrying a session the server didn't start, or no longer
+//! remembers, are refused rather than given the global root.
+
+use crate::ApiError;
+use dioxus::fullstack::FullstackContext;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::Instant;
+
+/// Environment variable naming the directory file functions are confined to.
+/// Defaults to the server's working directory when unset.
+pub const ROOT_ENV: &str = "JADEOS_ROOT";
+
+/// Cookie carrying the id of a session with its own root.
+const SESSION_COOKIE: &str = "jadeos_session";
+
+/// Most sessions whose roots are remembered. Past it, the least recently
+/// used session is forgotten, and its requests are refused from then on.
+#[cfg(feature = "fs")]
+const MAX_SESSIONS: usize = 1024;
+
+/// Roots set by sessions, with when each was last used, by session id. Each
+/// root is canonical and inside the global root.
+static SESSION_ROOTS: LazyLock<Mutex<HashMap<String, (PathBuf, Instant)>>> =
+    LazyLock::new(Default::default);
+
+/// Key session ids are signed with. Fresh on every start, so ids minted by
+/// an earlier run of the server are refused.
+static SESSION_KEY: LazyLock<String> = LazyLock::new(random_hex);
+
+/// Id of the session making the current request, read from its cookie.
+fn session_id() -> Option<String> {
+    let context = FullstackContext::current()?;
+    let parts = context.parts_mut();
+    parts
+        .headers
+        .get_all(http::header::COOKIE)
+        .iter()
+        .filter_map(|header| header.to_str().ok())
+        .flat_map(|header| header.split(';'))
+        .find_map(|cookie| {
+            let (name, value) = cookie.trim().split_once('=')?;
+            (name == SESSION_COOKIE).then(|| value.to_string())
+        })
+}
+
+/// 64 unguessable hex digits.
+fn random_hex() -> String {
+    use sha2::{Digest, Sha256};
+    use std::hash::{BuildHasher, RandomState};
+
+    // Every `RandomState` is seeded from the OS's random source; hashing a
+    // few of them with the time gives a value nobody can predict.
+    let mut hasher = Sha256::new();
+    for _ in 0..4 {
+        hasher.update(RandomState::new().hash_one(0u8).to_le_bytes());
+    }
+    if let Ok(since_epoch) = std::time::UNIX_EPOCH.elapsed() {
+        hasher.update(since_epoch.as_nanos().to_le_bytes());
+    }
+    hex(&hasher.finalize())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Signature of the session `nonce` under [`SESSION_KEY`].
+fn signature(nonce: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    hasher.update(SESSION_KEY.as_bytes());
+    hasher.update(b".");
+    hasher.update(nonce.as_bytes());
+    hex(&hasher.finalize())
+}
+
+/// A fresh, unguessable session id, signed so [`is_minted`] can tell it from
+/// ids made up by a client.
+#[cfg(feature = "fs")]
+fn new_session_id() -> String {
+    let nonce = random_hex();
+    let signature = signature(&nonce);
+    format!("{nonce}.{signature}")
+}
+
+/// Whether `id` was made by [`new_session_id`] since the server started.
+fn is_minted(id: &str) -> bool {
+    let Some((nonce, claimed)) = id.split_once('.') else {
+        return false;
+    };
+    let expected = signature(nonce);
+    // Compared in constant time, so the signature can't be guessed a digit
+    // at a time.
+    claimed.len() == expected.len()
+        && claimed
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Narrows the current session's root to `path`, a directory resolved
+/// against the session's current root, so a session can only ever move its
+/// root further down. Starts a session if the request didn't carry one.
+#[cfg(feature = "fs")]
+pub fn set_session_root(path: &str) -> Result<(), ApiError> {
+    let resolved = resolve(path)?;
+    if !resolved.is_dir() {
+        return Err(ApiError::InvalidInput(format!("{path}: not a directory")));
+    }
+
+    // `resolve` has refused the request unless its session, if any, is one
+    // the server remembers.
+    let id = match session_id() {
+        Some(id) => id,
+        None => {
+            let id = new_session_id();
+            let cookie = format!("{SESSION_COOKIE}={id}; Path=/; HttpOnly; SameSite=Strict");
+            let context = FullstackContext::current()
+                .ok_or_else(|| ApiError::Internal("no request to start a session for".into()))?;
+            let value = http::HeaderValue::from_str(&cookie)
+                .map_err(|err| ApiError::Internal(err.to_string()))?;
+            context.add_response_header(http::header::SET_COOKIE, value);
+            id
+        }
+    };
+    remember_root(&mut SESSION_ROOTS.lock().unwrap(), id, resolved);
+    Ok(())
+}
+
+/// Records `root` for the session `id`, forgetting the least recently used
+/// session if that makes more than [`MAX_SESSIONS`].
+#[cfg(feature = "fs")]
+fn remember_root(roots: &mut HashMap<String, (PathBuf, Instant)>, id: String, root: PathBuf) {
+    roots.insert(id, (root, Instant::now()));
+    if roots.len() > MAX_SESSIONS {
+        let oldest = roots
+            .iter()
+            .min_by_key(|(_, (_, used))| *used)
+            .map(|(id, _)| id.clone());
+        if let Some(oldest) = oldest {
+            roots.remove(&oldest);
+        }
+    }
+}
+
+/// Returns the canonical root of the current session: the one it set with
+/// [`set_session_root`], or the global root if the request carries no
+/// session.
+pub fn root() -> Result<PathBuf, ApiError> {
+    match session_id() {
+        Some(id) => session_root(&mut SESSION_ROOTS.lock().unwrap(), &id),
+        None => global_root(),
+    }
+}
+
+/// Returns the root the session `id` set, marking it used.
+///
+/// Forged ids and sessions that have since been forgotten are refused: their
+/// requests were confined to a root nobody remembers, and the global one
+/// would be wider.
+fn session_root(
+    roots: &mut HashMap<String, (PathBuf, Instant)>,
+    id: &str,
+) -> Result<PathBuf, ApiError> {
+    if !is_minted(id) {
+        return Err(ApiError::PermissionDenied("unknown session".into()));
+    }
+    let (root, used) = roots
+        .get_mut(id)
+        .ok_or_else(|| ApiError::PermissionDenied("session expired".into()))?;
+    *used = Instant::now();
+    Ok(root.clone())
+}
+
+/// Returns the canonical global sandbox root.
+fn global_root() -> Result<PathBuf, ApiError> {
+    let root = std::env::var_os(ROOT_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."));
+    root.canonicalize()
+        .map_err(|err| ApiError::Internal(format!("sandbox root unavailable: {err}")))
+}
+
+/// Resolves a client supplied `path` to a canonical path inside the sandbox.
+///
+/// The final component doesn't need to exist yet, which lets functions that
+/// create files share the same check; its parent directory does.
+pub fn resolve(path: &str) -> Result<PathBuf, ApiError> {
+    resolve_in(&root()?, path)
+}
+
+/// Like [`resolve`], against the canonical `root` instead of the current
+/// session's.
+///
+/// A missing final component is refused when it's a dangling symlink: the
+/// caller would create whatever the link points to, wherever that is.
+pub fn resolve_in(root: &Path, path: &str) -> Result<PathBuf, ApiError> {
+    let relative = Path::new(path.trim_start_matches('/'));
+    let joined = root.join(relative);
+
+    let resolved = match joined.canonicalize() {
+        Ok(resolved) => resolved,
+        Err(_) => {
+            let name = match relative.components().next_back() {
+                Some(Component::Normal(name)) => name.to_owned(),
+                _ => return Err(outside(path)),
+            };
+            let parent = joined
+                .parent()
+                .ok_or_else(|| outside(path))?
+                .canonicalize()
+                .map_err(|err| ApiError::io(path, err))?;
+            let resolved = parent.join(name);
+            if std::fs::symlink_metadata(&resolved).is_ok_and(|metadata| metadata.is_symlink()) {
+                return Err(outside(path));
+            }
+            resolved
+        }
+    };
+
+    if resolved.starts_with(root) {
+        Ok(resolved)
+    } else {
+        Err(outside(path))
+    }
+}
+
+fn outside(path: &str) -> ApiError {
+    ApiError::OutsideSandbox(format!("{path}: outside of the sandbox"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sandbox() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("root");
+        std::fs::create_dir(&root).unwrap();
+        let root = root.canonicalize().unwrap();
+        (dir, root)
+    }
+
+    #[test]
+    fn resolves_paths_inside_the_root() {
+        let (_dir, root) = sandbox();
+        std::fs::create_dir(root.join("docs")).unwrap();
+        assert_eq!(resolve_in(&root, "/docs").unwrap(), root.join("docs"));
+        assert_eq!(
+            resolve_in(&root, "docs/new.txt").unwrap(),
+            root.join("docs/new.txt")
+        );
+    }
+
+    #[test]
+    fn rejects_parent_segments_out_of_the_root() {
+        let (_dir, root) = sandbox();
+        assert_eq!(resolve_in(&root, "/../secret"), Err(outside("/../secret")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlinks_out_of_the_root() {
+        let (dir, root) = sandbox();
+        std::fs::write(dir.path().join("secret"), "").unwrap();
+        std::os::unix::fs::symlink(dir.path().join("secret"), root.join("link")).unwrap();
+        assert_eq!(resolve_in(&root, "/link"), Err(outside("/link")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_dangling_symlinks() {
+        let (dir, root) = sandbox();
+        std::os::unix::fs::symlink(dir.path().join("missing"), root.join("link")).unwrap();
+        assert_eq!(resolve_in(&root, "/link"), Err(outside("/link")));
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn forgets_the_least_recently_used_session() {
+        let hour_ago = Instant::now() - std::time::Duration::from_secs(3600);
+        let mut roots: HashMap<String, (PathBuf, Instant)> = (0..MAX_SESSIONS)
+            .map(|i| {
+                let used = hour_ago + std::time::Duration::from_millis(i as u64);
+                (i.to_string(), (PathBuf::from("/"), used))
+            })
+            .collect();
+        // Session 0 was created first but used since.
+        roots.get_mut("0").unwrap().1 = Instant::now();
+
+        remember_root(&mut roots, "new".to_string(), PathBuf::from("/"));
+        assert_eq!(roots.len(), MAX_SESSIONS);
+        assert!(roots.contains_key("0"));
+        assert!(roots.contains_key("new"));
+        assert!(!roots.contains_key("1"));
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn session_roots_confine_their_session() {
+        let (_dir, root) = sandbox();
+        std::fs::write(root.join("secret.txt"), "").unwrap();
+        std::fs::create_dir_all(root.join("home/docs")).unwrap();
+        let mut roots = HashMap::new();
+        let id = new_session_id();
+
+        remember_root(&mut roots, id.clone(), root.join("home"));
+        let session_root = session_root(&mut roots, &id).unwrap();
+        assert_eq!(session_root, root.join("home"));
+        assert_eq!(resolve_in(&session_root, "/").unwrap(), root.join("home"));
+        for path in ["..", "/..", "/../secret.txt", "docs/../../secret.txt"] {
+            assert_eq!(resolve_in(&session_root, path), Err(outside(path)));
+        }
+        // Host paths are read relative to the session root too.
+        let absolute = root.join("secret.txt");
+        let resolved = resolve_in(&session_root, &absolute.to_string_lossy());
+        assert!(resolved
+            .ok()
+            .is_none_or(|path| path.starts_with(&session_root)));
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn refuses_unknown_and_forgotten_sessions() {
+        let mut roots = HashMap::new();
+        let id = new_session_id();
+        assert!(is_minted(&id));
+
+        // Minted, but forgotten or never given a root.
+        assert!(matches!(
+            session_root(&mut roots, &id),
+            Err(ApiError::PermissionDenied(_))
+        ));
+
+        // Made up by the client, including one reusing a real nonce.
+        remember_root(&mut roots, "forged".to_string(), PathBuf::from("/"));
+        let (nonce, _) = id.split_once('.').unwrap();
+        for forged in ["forged".to_string(), format!("{nonce}.{}", "0".repeat(64))] {
+            assert!(!is_minted(&forged));
+            assert!(matches!(
+                session_root(&mut roots, &forged),
+                Err(ApiError::PermissionDenied(_))
+            ));
+        }
+    }
+}
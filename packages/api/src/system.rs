@@ -0,0 +1,226 @@
+//! Host information: how much space and how many inodes are left, and
+//! whether a quota limits them.
+
+#[cfg(feature = "server")]
+use crate::{sandbox, ApiError};
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Where the numbers of a [`QuotaInfo`] come from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QuotaSource {
+    /// The server user's disk quota.
+    UserQuota,
+    /// The whole file system, because no quota applies to the user.
+    FileSystem,
+    /// Neither could be read on this host.
+    Unavailable,
+}
+
+/// Space and inode usage for a path. Each field is `None` when the host
+/// doesn't report it, or, for limits, when there is none.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaInfo {
+    pub source: QuotaSource,
+    pub used_bytes: Option<u64>,
+    pub limit_bytes: Option<u64>,
+    pub used_inodes: Option<u64>,
+    pub limit_inodes: Option<u64>,
+}
+
+#[cfg(feature = "server")]
+impl QuotaInfo {
+    fn unavailable() -> Self {
+        Self {
+            source: QuotaSource::Unavailable,
+            used_bytes: None,
+            limit_bytes: None,
+            used_inodes: None,
+            limit_inodes: None,
+        }
+    }
+}
+
+/// Block device the file system holding `path` is mounted from, read from
+/// `/proc/self/mountinfo`.
+#[cfg(all(feature = "server", target_os = "linux"))]
+fn mount_source(path: &std::path::Path) -> Option<String> {
+    // Mount points escape whitespace and backslashes as octal sequences.
+    fn unescape(field: &str) -> String {
+        let mut out = String::new();
+        let mut rest = field;
+        while let Some(index) = rest.find('\\') {
+            out.push_str(&rest[..index]);
+            let code = rest.get(index + 1..index + 4);
+            match code.and_then(|code| u8::from_str_radix(code, 8).ok()) {
+                Some(byte) => {
+                    out.push(byte as char);
+                    rest = &rest[index + 4..];
+                }
+                None => {
+                    out.push('\\');
+                    rest = &rest[index + 1..];
+                }
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+
+    let mountinfo = std::fs::read_to_string("/proc/self/mountinfo").ok()?;
+    mountinfo
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(' ').collect();
+            let mount_point = unescape(fields.get(4)?);
+            // Optional fields end at the `-` separator, followed by the file
+            // system type and the source.
+            let separator = fields.iter().position(|&field| field == "-")?;
+            let source = unescape(fields.get(separator + 2)?);
+            path.starts_with(&mount_point)
+                .then_some((mount_point.len(), source))
+        })
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, source)| source)
+}
+
+/// Kernel `struct if_dqblk`, filled by `Q_GETQUOTA`.
+#[cfg(all(feature = "server", target_os = "linux"))]
+#[repr(C)]
+#[derive(Default)]
+struct DiskQuota {
+    /// Limits in 1 KiB blocks.
+    bhardlimit: u64,
+    bsoftlimit: u64,
+    /// Bytes in use.
+    curspace: u64,
+    ihardlimit: u64,
+    isoftlimit: u64,
+    curinodes: u64,
+    _btime: u64,
+    _itime: u64,
+    _valid: u32,
+}
+
+/// The server user's quota on the file system holding `path`, if quotas are
+/// enabled there and limit the user at all.
+#[cfg(all(feature = "server", target_os = "linux"))]
+fn user_quota(path: &std::path::Path) -> Option<QuotaInfo> {
+    const USRQUOTA: libc::c_int = 0;
+    const Q_GETQUOTA: libc::c_int = 0x80_0007;
+    const SUBCMDSHIFT: libc::c_int = 8;
+
+    let device = std::ffi::CString::new(mount_source(path)?).ok()?;
+    let mut quota = DiskQuota::default();
+    // SAFETY: `device` is a valid C string and `quota` matches the layout
+    // the kernel writes for `Q_GETQUOTA`.
+    let result = unsafe {
+        libc::quotactl(
+            (Q_GETQUOTA << SUBCMDSHIFT) | USRQUOTA,
+            device.as_ptr(),
+            libc::getuid() as libc::c_int,
+            &mut quota as *mut DiskQuota as *mut libc::c_char,
+        )
+    };
+    if result != 0 {
+        return None;
+    }
+
+    // A hard limit of 0 means none; fall back to the soft one.
+    let limit = |hard: u64, soft: u64| [hard, soft].into_iter().find(|&limit| limit > 0);
+    let limit_bytes = limit(quota.bhardlimit, quota.bsoftlimit).map(|blocks| blocks * 1024);
+    let limit_inodes = limit(quota.ihardlimit, quota.isoftlimit);
+    if limit_bytes.is_none() && limit_inodes.is_none() {
+        return None;
+    }
+    Some(QuotaInfo {
+        source: QuotaSource::UserQuota,
+        used_bytes: Some(quota.curspace),
+        limit_bytes,
+        used_inodes: Some(quota.curinodes),
+        limit_inodes,
+    })
+}
+
+/// Usage of the whole file system holding `path`.
+#[cfg(all(feature = "server", unix))]
+fn file_system_usage(path: &std::path::Path) -> Option<QuotaInfo> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `path` is a valid C string and `stat` is only read once
+    // `statvfs` reported filling it.
+    let stat = unsafe {
+        if libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) != 0 {
+            return None;
+        }
+        stat.assume_init()
+    };
+
+    let block = stat.f_frsize as u64;
+    let blocks = stat.f_blocks as u64;
+    let files = stat.f_files as u64;
+    // File systems without a fixed inode table (btrfs, ...) report zero.
+    let has_inodes = files > 0;
+    Some(QuotaInfo {
+        source: QuotaSource::FileSystem,
+        used_bytes: Some(blocks.saturating_sub(stat.f_bfree as u64) * block),
+        limit_bytes: Some(blocks * block),
+        used_inodes: has_inodes.then(|| files.saturating_sub(stat.f_ffree as u64)),
+        limit_inodes: has_inodes.then_some(files),
+    })
+}
+
+/// Space and inode usage at `path`: the server user's quota where one
+/// applies, otherwise the file system's totals. Hosts where neither can be
+/// read get a [`QuotaSource::Unavailable`] result with every field `None`.
+#[post("/api/quota_info")]
+pub async fn quota_info(path: String) -> Result<QuotaInfo, ServerFnError> {
+    let resolved = sandbox::resolve(&path)?;
+    if !resolved.exists() {
+        return Err(ApiError::NotFound(format!("{path}: no such file or directory")).into());
+    }
+
+    Ok(usage_at(&resolved))
+}
+
+/// What [`quota_info`] reports for the canonical `path`.
+#[cfg(feature = "server")]
+fn usage_at(path: &std::path::Path) -> QuotaInfo {
+    #[cfg(target_os = "linux")]
+    if let Some(quota) = user_quota(path) {
+        return quota;
+    }
+    #[cfg(unix)]
+    if let Some(usage) = file_system_usage(path) {
+        return usage;
+    }
+    QuotaInfo::unavailable()
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usage_degrades_without_a_quota() {
+        let dir = tempfile::tempdir().unwrap();
+        let usage = usage_at(dir.path());
+        match usage.source {
+            QuotaSource::Unavailable => assert_eq!(usage, QuotaInfo::unavailable()),
+            _ => {
+                assert!(usage.used_bytes <= usage.limit_bytes);
+                // Inodes are reported together or not at all.
+                assert_eq!(usage.used_inodes.is_some(), usage.limit_inodes.is_some());
+                assert!(usage.used_inodes <= usage.limit_inodes);
+            }
+        }
+
+        // Nothing can be read about a path that doesn't exist.
+        assert_eq!(
+            usage_at(&dir.path().join("missing")),
+            QuotaInfo::unavailable()
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use crate::Spinner;
+use dioxus::prelude::*;
+
+#[derive(PartialEq, Clone, Props)]
+pub struct ButtonProps {
+    #[props(default = "bg-slate-800 text-white".to_string())]
+    color: String,
+    #[props(default = "".to_string())]
+    class: String,
+    #[props(default)]
+    disabled: bool,
+    /// Disables the button and shows a `Spinner` in place of its content,
+    /// e.g. while the server function it triggered is pending.
+    #[props(default)]
+    loading: bool,
+    onclick: Option<EventHandler<MouseEvent>>,
+    children: Element,
+}
+
+/// A button that ignores clicks while `disabled` or `loading`.
+#[component]
+pub fn Button(props: ButtonProps) -> Element {
+    let inactive = props.disabled || props.loading;
+    let onclick = props.onclick;
+
+    rsx! {
+        button {
+            class: "relative inline-flex items-center justify-center rounded-md px-4 py-2 disabled:opacity-60 {props.color} {props.class}",
+            disabled: inactive,
+            aria_busy: props.loading,
+            onclick: move |evt| {
+                if inactive {
+                    return;
+                }
+                if let Some(onclick) = onclick {
+                    onclick.call(evt);
+                }
+            },
+            // The content stays in the layout while loading so the button
+            // keeps its width.
+            span { class: if props.loading { "invisible" } else { "" }, {props.children} }
+            if props.loading {
+                span { class: "absolute inset-0 flex items-center justify-center",
+                    Spinner {}
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dioxus::core::ElementId;
+    use dioxus_html::{PlatformEventData, SerializedHtmlEventConverter, SerializedMouseData};
+    use std::any::Any;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    thread_local! {
+        static CLICKS: Cell<u32> = const { Cell::new(0) };
+    }
+
+    /// Clicks the only button rendered by `app`, returning how many times
+    /// its handler ran.
+    fn clicks(app: fn() -> Element) -> u32 {
+        dioxus_html::set_event_converter(Box::new(SerializedHtmlEventConverter));
+        CLICKS.set(0);
+        let mut dom = VirtualDom::new(app);
+        dom.rebuild_in_place();
+
+        let data = PlatformEventData::new(Box::<SerializedMouseData>::default());
+        let event = Event::new(Rc::new(data) as Rc<dyn Any>, true);
+        dom.runtime().handle_event("click", event, ElementId(1));
+        dom.render_immediate_to_vec();
+        CLICKS.get()
+    }
+
+    #[test]
+    fn clicks_are_ignored_while_loading() {
+        assert_eq!(
+            clicks(|| rsx! {
+                Button { onclick: move |_| CLICKS.set(CLICKS.get() + 1), "Save" }
+            }),
+            1
+        );
+        assert_eq!(
+            clicks(|| rsx! {
+                Button { loading: true, onclick: move |_| CLICKS.set(CLICKS.get() + 1), "Save" }
+            }),
+            0
+        );
+        assert_eq!(
+            clicks(|| rsx! {
+                Button { disabled: true, onclick: move |_| CLICKS.set(CLICKS.get() + 1), "Save" }
+            }),
+            0
+        );
+    }
+}
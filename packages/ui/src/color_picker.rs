@@ -0,0 +1,68 @@
+use dioxus::prelude::*;
+
+/// `color` as the lowercase `#rrggbb` a color input takes, expanding the
+/// `#rgb` shorthand. `None` for anything else, such as a named color.
+pub fn normalize_hex(color: &str) -> Option<String> {
+    let digits = color.trim().strip_prefix('#')?;
+    if !digits.chars().all(|digit| digit.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digits = match digits.len() {
+        3 => digits.chars().flat_map(|digit| [digit, digit]).collect(),
+        6 => digits.to_string(),
+        _ => return None,
+    };
+    Some(format!("#{}", digits.to_ascii_lowercase()))
+}
+
+#[derive(PartialEq, Clone, Props)]
+pub struct ColorPickerProps {
+    label: String,
+    /// Color as `#rrggbb` or `#rgb`.
+    value: String,
+    onchange: EventHandler<String>,
+}
+
+#[component]
+pub fn ColorPicker(props: ColorPickerProps) -> Element {
+    let onchange = props.onchange;
+    // A color input shows anything it can't read as black, and would
+    // report that back on the next change.
+    let input = normalize_hex(&props.value).unwrap_or_else(|| "#000000".to_string());
+
+    rsx! {
+        label { class: "flex items-center justify-between gap-3",
+            span { "{props.label}" }
+            span { class: "flex items-center gap-2",
+                span { class: "font-mono text-xs text-slate-500", "{props.value}" }
+                input {
+                    r#type: "color",
+                    class: "h-7 w-10 cursor-pointer rounded border border-slate-300 bg-transparent",
+                    value: "{input}",
+                    oninput: move |evt| {
+                        if let Some(color) = normalize_hex(&evt.value()) {
+                            onchange.call(color);
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_are_normalized_to_lowercase_six_digit_hex() {
+        assert_eq!(normalize_hex("#38BDF8").as_deref(), Some("#38bdf8"));
+        assert_eq!(normalize_hex(" #fff ").as_deref(), Some("#ffffff"));
+        assert_eq!(normalize_hex("#1e293b").as_deref(), Some("#1e293b"));
+
+        assert_eq!(normalize_hex("38bdf8"), None);
+        assert_eq!(normalize_hex("#38bdf"), None);
+        assert_eq!(normalize_hex("#gggggg"), None);
+        assert_eq!(normalize_hex("red"), None);
+    }
+}
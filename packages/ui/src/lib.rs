@@ -8,3 +8,27 @@ pub use navbar::Navbar;
 
 mod echo;
 pub use echo::Echo;
+
+mod toast;
+pub use toast::{toast_error, use_toasts, Toast, ToastAction, ToastLevel, ToastProvider, Toasts};
+
+mod button;
+pub use button::Button;
+
+mod card;
+pub use card::{Card, CardBody, CardFooter, CardHeader};
+
+mod color_picker;
+pub use color_picker::ColorPicker;
+
+mod modal;
+pub use modal::Modal;
+
+mod number_input;
+pub use number_input::NumberInput;
+
+mod popover;
+pub use popover::Popover;
+
+mod spinner;
+pub use spinner::Spinner;
@@ -0,0 +1,38 @@
+use dioxus::prelude::*;
+
+#[derive(PartialEq, Clone, Props)]
+pub struct ModalProps {
+    #[props(default = "bg-white".to_string())]
+    color: String,
+    #[props(default = "".to_string())]
+    class: String,
+    /// Called when the backdrop is clicked.
+    onclose: Option<EventHandler<()>>,
+    children: Element,
+}
+
+/// Screen-modal dialog: a dimmed backdrop over the whole desktop with the
+/// content centered on top.
+#[component]
+pub fn Modal(props: ModalProps) -> Element {
+    let onclose = props.onclose;
+
+    rsx! {
+        div {
+            class: "fixed inset-0 flex items-center justify-center bg-slate-900/50",
+            style: "z-index: 15000;",
+            onclick: move |_| {
+                if let Some(onclose) = onclose {
+                    onclose.call(());
+                }
+            },
+            div {
+                class: "relative flex flex-col rounded-lg shadow-lg p-4 {props.color} {props.class}",
+                role: "dialog",
+                aria_modal: "true",
+                onclick: move |evt| evt.stop_propagation(),
+                {props.children}
+            }
+        }
+    }
+}
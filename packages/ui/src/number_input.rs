@@ -0,0 +1,113 @@
+use dioxus::prelude::*;
+
+/// Decimal places of `value` as written, e.g. 2 for `0.05`.
+fn decimals(value: f64) -> i32 {
+    let written = value.to_string();
+    written
+        .split_once('.')
+        .map_or(0, |(_, fraction)| fraction.len() as i32)
+}
+
+/// What a [`NumberInput`] reports for `input`: the number rounded to the
+/// nearest `step` counted from `min`, then clamped to `min..=max`. `None`
+/// while `input` isn't a number, e.g. halfway through typing one.
+pub fn parse_number(input: &str, min: f64, max: f64, step: f64) -> Option<f64> {
+    let value = input
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())?;
+    let stepped = if step > 0.0 {
+        let stepped = min + ((value - min) / step).round() * step;
+        // Keep to the precision of the bounds and step, so 0.05 steps
+        // don't drift to 0.15000000000000002.
+        let scale = 10f64.powi(decimals(step).max(decimals(min)));
+        (stepped * scale).round() / scale
+    } else {
+        value
+    };
+    Some(stepped.clamp(min, max))
+}
+
+#[derive(PartialEq, Clone, Props)]
+pub struct NumberInputProps {
+    label: String,
+    value: f64,
+    min: f64,
+    max: f64,
+    #[props(default = 1.0)]
+    step: f64,
+    onchange: EventHandler<f64>,
+}
+
+/// A slider with a number field next to it, both kept to `step`s within
+/// `min..=max`.
+#[component]
+pub fn NumberInput(props: NumberInputProps) -> Element {
+    let (min, max, step) = (props.min, props.max, props.step);
+    let onchange = props.onchange;
+    let parse = move |value: String| {
+        if let Some(value) = parse_number(&value, min, max, step) {
+            onchange.call(value);
+        }
+    };
+
+    rsx! {
+        label { class: "flex items-center justify-between gap-3",
+            span { "{props.label}" }
+            span { class: "flex items-center gap-2",
+                input {
+                    r#type: "range",
+                    class: "w-28",
+                    min: "{min}",
+                    max: "{max}",
+                    step: "{step}",
+                    value: "{props.value}",
+                    oninput: move |evt| parse(evt.value()),
+                }
+                input {
+                    r#type: "number",
+                    class: "w-16 rounded border border-slate-300 px-1",
+                    min: "{min}",
+                    max: "{max}",
+                    step: "{step}",
+                    value: "{props.value}",
+                    onchange: move |evt| parse(evt.value()),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_numbers_are_clamped_to_the_range() {
+        assert_eq!(parse_number("12", 0.0, 24.0, 1.0), Some(12.0));
+        assert_eq!(parse_number("30", 0.0, 24.0, 1.0), Some(24.0));
+        assert_eq!(parse_number("-5", 0.0, 24.0, 1.0), Some(0.0));
+        assert_eq!(parse_number(" 7 ", 0.0, 24.0, 1.0), Some(7.0));
+    }
+
+    #[test]
+    fn typed_numbers_round_to_the_nearest_step_from_min() {
+        assert_eq!(parse_number("130", 0.0, 1000.0, 50.0), Some(150.0));
+        assert_eq!(parse_number("120", 0.0, 1000.0, 50.0), Some(100.0));
+        // Steps count from `min`, as they do for a range input.
+        assert_eq!(parse_number("14", 5.0, 100.0, 10.0), Some(15.0));
+        assert_eq!(parse_number("0.16", 0.0, 1.0, 0.05), Some(0.15));
+        // A step past `max` is clamped back to it.
+        assert_eq!(parse_number("26", 0.0, 25.0, 10.0), Some(25.0));
+    }
+
+    #[test]
+    fn anything_but_a_number_is_ignored() {
+        assert_eq!(parse_number("", 0.0, 24.0, 1.0), None);
+        assert_eq!(parse_number("-", 0.0, 24.0, 1.0), None);
+        assert_eq!(parse_number("abc", 0.0, 24.0, 1.0), None);
+        assert_eq!(parse_number("NaN", 0.0, 24.0, 1.0), None);
+        assert_eq!(parse_number("inf", 0.0, 24.0, 1.0), None);
+    }
+}
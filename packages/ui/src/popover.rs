@@ -0,0 +1,28 @@
+use dioxus::prelude::*;
+
+#[derive(PartialEq, Clone, Props)]
+pub struct PopoverProps {
+    open: bool,
+    #[props(default = "bg-white".to_string())]
+    color: String,
+    #[props(default = "".to_string())]
+    class: String,
+    children: Element,
+}
+
+/// Floating panel above its (relatively positioned) parent, shown while
+/// `open`.
+#[component]
+pub fn Popover(props: PopoverProps) -> Element {
+    if !props.open {
+        return rsx! {};
+    }
+
+    rsx! {
+        div {
+            class: "absolute bottom-full left-1/2 mb-2 -translate-x-1/2 rounded-lg shadow-lg p-2 {props.color} {props.class}",
+            role: "tooltip",
+            {props.children}
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use dioxus::prelude::*;
+
+#[derive(PartialEq, Clone, Props)]
+pub struct SpinnerProps {
+    #[props(default = "h-4 w-4".to_string())]
+    size: String,
+    #[props(default = "".to_string())]
+    class: String,
+}
+
+#[component]
+pub fn Spinner(props: SpinnerProps) -> Element {
+    rsx! {
+        div {
+            class: "animate-spin rounded-full border-2 border-current border-t-transparent {props.size} {props.class}",
+            role: "status",
+            aria_label: "Loading",
+        }
+    }
+}
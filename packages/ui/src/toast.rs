@@ -0,0 +1,222 @@
+use api::ApiError;
+use dioxus::prelude::*;
+
+const TOAST_CSS: Asset = asset!("/assets/styling/toast.css");
+
+/// Most toasts [`Toasts::history`] keeps.
+pub const HISTORY_LEN: usize = 200;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToastLevel {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl ToastLevel {
+    fn class(self) -> &'static str {
+        match self {
+            ToastLevel::Info => "toast-info",
+            ToastLevel::Success => "toast-success",
+            ToastLevel::Warning => "toast-warning",
+            ToastLevel::Error => "toast-error",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToastAction {
+    /// Offers to report an unexpected failure.
+    Report,
+}
+
+impl ToastAction {
+    fn label(self) -> &'static str {
+        match self {
+            ToastAction::Report => "Report",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Toast {
+    pub level: ToastLevel,
+    pub message: String,
+    pub action: Option<ToastAction>,
+}
+
+impl Toast {
+    pub fn new(level: ToastLevel, message: impl Into<String>) -> Self {
+        Self {
+            level,
+            message: message.into(),
+            action: None,
+        }
+    }
+}
+
+/// Maps a server function error onto the toast shown to the user.
+///
+/// Expected failures (missing files, denied access, bad input) are warnings
+/// carrying the server's message; anything internal is an error with a
+/// "Report" action, since the user can't fix it themselves.
+pub fn toast_error(err: &ApiError) -> Toast {
+    let (level, message, action) = match err {
+        ApiError::NotFound(message) => (ToastLevel::Warning, format!("Not found: {message}"), None),
+        ApiError::PermissionDenied(message) => (
+            ToastLevel::Warning,
+            format!("Permission denied: {message}"),
+            None,
+        ),
+        ApiError::OutsideSandbox(_) => (
+            ToastLevel::Warning,
+            "That location is outside of your files".to_string(),
+            None,
+        ),
+        ApiError::InvalidInput(message) => (ToastLevel::Warning, message.clone(), None),
+        ApiError::Conflict(message) => (ToastLevel::Warning, format!("Conflict: {message}"), None),
+        ApiError::Unsupported(message) => {
+            (ToastLevel::Info, format!("Not supported: {message}"), None)
+        }
+        ApiError::Internal(message) => (
+            ToastLevel::Error,
+            format!("Something went wrong: {message}"),
+            Some(ToastAction::Report),
+        ),
+    };
+
+    Toast {
+        level,
+        message,
+        action,
+    }
+}
+
+/// Handle to the toast stack provided by [`ToastProvider`].
+#[derive(Clone, Copy, PartialEq)]
+pub struct Toasts {
+    stack: Signal<Vec<(usize, Toast)>>,
+    history: Signal<Vec<Toast>>,
+    next_id: Signal<usize>,
+}
+
+impl Toasts {
+    fn new() -> Self {
+        Self {
+            stack: Signal::new(Vec::new()),
+            history: Signal::new(Vec::new()),
+            next_id: Signal::new(0),
+        }
+    }
+
+    pub fn push(&mut self, toast: Toast) {
+        let id = *self.next_id.peek();
+        self.next_id += 1;
+        {
+            let mut history = self.history.write();
+            history.push(toast.clone());
+            let excess = history.len().saturating_sub(HISTORY_LEN);
+            history.drain(..excess);
+        }
+        self.stack.write().push((id, toast));
+    }
+
+    /// Shows the toast for a failed server function call.
+    pub fn error(&mut self, err: &ServerFnError) {
+        self.push(toast_error(&ApiError::from(err)));
+    }
+
+    pub fn dismiss(&mut self, id: usize) {
+        self.stack.write().retain(|(other, _)| *other != id);
+    }
+
+    /// Toasts currently shown, oldest first, with the id to dismiss them by.
+    pub fn list(&self) -> Vec<(usize, Toast)> {
+        self.stack.read().clone()
+    }
+
+    /// The last [`HISTORY_LEN`] toasts pushed, oldest first, including
+    /// dismissed ones.
+    pub fn history(&self) -> Vec<Toast> {
+        self.history.read().clone()
+    }
+}
+
+pub fn use_toasts() -> Toasts {
+    use_context()
+}
+
+/// Provides [`Toasts`] to its children and renders the stack above them.
+#[component]
+pub fn ToastProvider(children: Element) -> Element {
+    let mut toasts = use_context_provider(Toasts::new);
+
+    rsx! {
+        document::Link { rel: "stylesheet", href: TOAST_CSS }
+        {children}
+        div { id: "toasts",
+            for (id, toast) in toasts.list() {
+                div { key: "{id}", class: "toast {toast.level.class()}",
+                    span { "{toast.message}" }
+                    if let Some(action) = toast.action {
+                        button {
+                            onclick: move |_| {
+                                dioxus::logger::tracing::error!("reported from toast: {}", toast.message);
+                                toasts.dismiss(id);
+                            },
+                            "{action.label()}"
+                        }
+                    }
+                    button { onclick: move |_| toasts.dismiss(id), "×" }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_internal_errors_offer_a_report() {
+        let toast = toast_error(&ApiError::Internal("disk on fire".to_string()));
+        assert_eq!(toast.level, ToastLevel::Error);
+        assert_eq!(toast.message, "Something went wrong: disk on fire");
+        assert_eq!(toast.action, Some(ToastAction::Report));
+
+        let toast = toast_error(&ApiError::OutsideSandbox("/etc/passwd".to_string()));
+        assert_eq!(toast.level, ToastLevel::Warning);
+        assert!(!toast.message.contains("/etc/passwd"));
+        assert_eq!(toast.action, None);
+
+        let toast = toast_error(&ApiError::Unsupported("ffmpeg".to_string()));
+        assert_eq!(toast.level, ToastLevel::Info);
+        assert_eq!(toast.action, None);
+    }
+
+    #[test]
+    fn the_history_keeps_dismissed_toasts_up_to_its_length() {
+        let mut dom = VirtualDom::new(|| rsx! {});
+        dom.rebuild_in_place();
+        dom.in_scope(ScopeId::ROOT, || {
+            let mut toasts = Toasts::new();
+            for index in 0..HISTORY_LEN + 2 {
+                toasts.push(Toast::new(ToastLevel::Info, format!("toast {index}")));
+            }
+            for (id, _) in toasts.list() {
+                toasts.dismiss(id);
+            }
+            assert!(toasts.list().is_empty());
+
+            let history = toasts.history();
+            assert_eq!(history.len(), HISTORY_LEN);
+            assert_eq!(history[0].message, "toast 2");
+            assert_eq!(
+                history[HISTORY_LEN - 1].message,
+                format!("toast {}", HISTORY_LEN + 1)
+            );
+        });
+    }
+}
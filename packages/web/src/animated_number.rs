@@ -0,0 +1,100 @@
+//! A number that counts up or down to its new value instead of jumping.
+
+use dioxus::core::Task;
+use dioxus::prelude::*;
+
+use crate::browser::next_frame;
+use crate::hooks::use_reduced_motion;
+
+/// Eases out cubically: fast at first, settling gently on the target.
+fn ease_out(t: f64) -> f64 {
+    1.0 - (1.0 - t).powi(3)
+}
+
+/// The value shown `elapsed_ms` into counting from `from` to `to` over
+/// `duration_ms`, and whether the count is over.
+fn animated_value(from: f64, to: f64, elapsed_ms: f64, duration_ms: u32) -> (f64, bool) {
+    let t = (elapsed_ms / duration_ms as f64).clamp(0.0, 1.0);
+    if t >= 1.0 {
+        return (to, true);
+    }
+    (from + (to - from) * ease_out(t), false)
+}
+
+#[derive(PartialEq, Clone, Props)]
+pub struct AnimatedNumberProps {
+    value: f64,
+    /// How long moving to a new `value` takes.
+    #[props(default = 400)]
+    duration_ms: u32,
+    /// Formats the displayed value, e.g. as bytes or a percentage. Defaults
+    /// to rounding to a whole number.
+    #[props(default)]
+    format: Option<Callback<f64, String>>,
+    #[props(default = "".to_string())]
+    class: String,
+}
+
+/// A number that counts from its previous to its new `value` over
+/// `duration_ms`, one animation frame at a time. With reduced motion it
+/// jumps straight to the new value.
+#[component]
+pub fn AnimatedNumber(props: AnimatedNumberProps) -> Element {
+    let mut displayed = use_signal(|| props.value);
+    let mut animation = use_signal(|| None::<Task>);
+    let reduced_motion = use_reduced_motion();
+
+    let value = props.value;
+    let duration_ms = props.duration_ms;
+    use_effect(use_reactive!(|value, duration_ms| {
+        if let Some(task) = animation.write().take() {
+            task.cancel();
+        }
+        let from = *displayed.peek();
+        if reduced_motion() || duration_ms == 0 || from == value {
+            displayed.set(value);
+            return;
+        }
+        // Retargeting mid-animation starts from the value currently shown,
+        // so the number never jumps back.
+        animation.set(Some(spawn(async move {
+            let Some(start) = next_frame().await else {
+                displayed.set(value);
+                return;
+            };
+            while let Some(now) = next_frame().await {
+                let (shown, done) = animated_value(from, value, now - start, duration_ms);
+                displayed.set(shown);
+                if done {
+                    return;
+                }
+            }
+            displayed.set(value);
+        })));
+    }));
+
+    let text = match &props.format {
+        Some(format) => format.call(displayed()),
+        None => format!("{:.0}", displayed()),
+    };
+
+    rsx! {
+        span { class: "tabular-nums {props.class}", "{text}" }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_ease_out_and_end_exactly_on_the_target() {
+        assert_eq!(animated_value(0.0, 100.0, 0.0, 400), (0.0, false));
+        // Half the time covers most of the distance.
+        assert_eq!(animated_value(0.0, 100.0, 200.0, 400), (87.5, false));
+        assert_eq!(animated_value(100.0, 0.0, 200.0, 400), (12.5, false));
+        assert_eq!(animated_value(0.0, 100.0, 400.0, 400), (100.0, true));
+        // A late frame doesn't overshoot.
+        assert_eq!(animated_value(0.0, 100.0, 650.0, 400), (100.0, true));
+    }
+}
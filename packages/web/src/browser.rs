@@ -0,0 +1,511 @@
+//! The browser APIs the desktop uses, behind the `web` feature.
+//!
+//! Builds without it (the server and native tests) get inert stand-ins that
+//! find no browser, so the web-only crates stay out of them. The server only
+//! renders the app; its effects and event handlers, where these are called,
+//! run in the browser.
+
+#[cfg(feature = "web")]
+pub use web::*;
+
+#[cfg(not(feature = "web"))]
+pub use inert::*;
+
+#[cfg(feature = "web")]
+mod web {
+    use dioxus::prelude::*;
+    use dioxus_web::WebEventExt;
+    use std::future::Future;
+    use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+    use wasm_bindgen_futures::JsFuture;
+    use web_sys::{Element, HtmlElement, MediaQueryList, MediaQueryListEvent};
+
+    /// Milliseconds since the Unix epoch.
+    pub fn now() -> f64 {
+        js_sys::Date::now()
+    }
+
+    /// Resolves after `ms` milliseconds.
+    pub fn sleep(ms: u32) -> impl Future<Output = ()> {
+        gloo_timers::future::TimeoutFuture::new(ms)
+    }
+
+    /// Resolves with the frame timestamp, in milliseconds, at the next
+    /// `requestAnimationFrame`.
+    pub async fn next_frame() -> Option<f64> {
+        let (tx, rx) = futures_channel::oneshot::channel();
+        let callback = Closure::once(move |time: f64| {
+            let _ = tx.send(time);
+        });
+        web_sys::window()?
+            .request_animation_frame(callback.as_ref().unchecked_ref())
+            .ok()?;
+        rx.await.ok()
+    }
+
+    fn storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok().flatten()
+    }
+
+    /// The `localStorage` item `key`.
+    pub fn load(key: &str) -> Option<String> {
+        storage()?.get_item(key).ok().flatten()
+    }
+
+    /// Sets the `localStorage` item `key`, silently failing when storage is
+    /// unavailable or full.
+    pub fn store(key: &str, value: &str) {
+        if let Some(storage) = storage() {
+            let _ = storage.set_item(key, value);
+        }
+    }
+
+    /// Current size of the browser viewport.
+    pub fn viewport_size() -> Option<(f64, f64)> {
+        let window = web_sys::window()?;
+        Some((
+            window.inner_width().ok()?.as_f64()?,
+            window.inner_height().ok()?.as_f64()?,
+        ))
+    }
+
+    fn element_by_id(id: &str) -> Option<Element> {
+        web_sys::window()?.document()?.get_element_by_id(id)
+    }
+
+    /// Rendered size of the element with DOM id `id`.
+    pub fn element_size(id: &str) -> Option<(f64, f64)> {
+        let rect = element_by_id(id)?.get_bounding_client_rect();
+        Some((rect.width(), rect.height()))
+    }
+
+    /// Inner size of the closest element matching `selector` around the
+    /// element with DOM id `id`.
+    pub fn closest_size(id: &str, selector: &str) -> Option<(f64, f64)> {
+        let closest = element_by_id(id)?.closest(selector).ok()??;
+        Some((
+            closest.client_width() as f64,
+            closest.client_height() as f64,
+        ))
+    }
+
+    /// Value of attribute `name` on the topmost element carrying it at
+    /// `point`, in client coordinates, looking through the elements above it.
+    pub fn attribute_at(point: (f64, f64), name: &str) -> Option<String> {
+        let document = web_sys::window()?.document()?;
+        document
+            .elements_from_point(point.0 as f32, point.1 as f32)
+            .iter()
+            .filter_map(|element| element.dyn_into::<Element>().ok())
+            .find_map(|element| element.get_attribute(name))
+    }
+
+    /// Rendered size of a mounted element.
+    pub fn mounted_size(mounted: &MountedData) -> Option<(f64, f64)> {
+        let rect = mounted.try_as_web_event()?.get_bounding_client_rect();
+        Some((rect.width(), rect.height()))
+    }
+
+    /// Captures `pointer_id` to a mounted element. Returns whether it was.
+    pub fn capture_pointer(mounted: &MountedData, pointer_id: i32) -> bool {
+        mounted
+            .try_as_web_event()
+            .is_some_and(|element| element.set_pointer_capture(pointer_id).is_ok())
+    }
+
+    pub fn release_pointer(mounted: &MountedData, pointer_id: i32) {
+        if let Some(element) = mounted.try_as_web_event() {
+            let _ = element.release_pointer_capture(pointer_id);
+        }
+    }
+
+    fn style_of(element: &Element, property: &str) -> Option<String> {
+        web_sys::window()?
+            .get_computed_style(element)
+            .ok()??
+            .get_property_value(property)
+            .ok()
+    }
+
+    /// Computed value of CSS `property` on a mounted element.
+    pub fn computed_style(mounted: &MountedData, property: &str) -> Option<String> {
+        style_of(&mounted.try_as_web_event()?, property)
+    }
+
+    /// Computed value of CSS `property` on the first child of a mounted
+    /// element.
+    pub fn child_computed_style(mounted: &MountedData, property: &str) -> Option<String> {
+        style_of(
+            &mounted.try_as_web_event()?.first_element_child()?,
+            property,
+        )
+    }
+
+    fn html_element(mounted: &MountedData) -> Option<HtmlElement> {
+        mounted.try_as_web_event()?.dyn_into().ok()
+    }
+
+    /// Width the content of a mounted element takes, and the width the
+    /// element has for it.
+    pub fn content_widths(mounted: &MountedData) -> Option<(f64, f64)> {
+        let element = html_element(mounted)?;
+        Some((element.scroll_width() as f64, element.client_width() as f64))
+    }
+
+    /// Sets the inline font size of a mounted element, or removes it.
+    pub fn set_font_size(mounted: &MountedData, px: Option<f64>) {
+        let Some(element) = html_element(mounted) else {
+            return;
+        };
+        let style = element.style();
+        let _ = match px {
+            Some(px) => style.set_property("font-size", &format!("{px}px")),
+            None => style.remove_property("font-size").map(drop),
+        };
+    }
+
+    /// Caret position, in UTF-16 code units, of a mounted `textarea`.
+    pub fn selection_start(mounted: &MountedData) -> Option<u32> {
+        mounted
+            .try_as_web_event()?
+            .dyn_into::<web_sys::HtmlTextAreaElement>()
+            .ok()?
+            .selection_start()
+            .ok()?
+    }
+
+    /// Appends to a mounted `container` a copy of the element with DOM id
+    /// `id`, scaled down to fit in `fit`. Fails for elements that aren't
+    /// laid out.
+    pub fn clone_scaled(id: &str, container: &MountedData, fit: (f64, f64)) -> Option<()> {
+        let container = container.try_as_web_event()?;
+        let source = element_by_id(id)?;
+        let rect = source.get_bounding_client_rect();
+        if rect.width() <= 0.0 || rect.height() <= 0.0 {
+            return None;
+        }
+
+        let scale = (fit.0 / rect.width()).min(fit.1 / rect.height());
+        let copy = source
+            .clone_node_with_deep(true)
+            .ok()?
+            .dyn_into::<Element>()
+            .ok()?;
+        copy.remove_attribute("id").ok()?;
+        copy.set_attribute(
+            "style",
+            &format!(
+                "position:absolute; left:0; top:0; width:{}px; height:{}px; transform:scale({scale}); transform-origin:top left; pointer-events:none;",
+                rect.width(),
+                rect.height(),
+            ),
+        )
+        .ok()?;
+        container.append_child(&copy).ok()?;
+        Some(())
+    }
+
+    /// Name of the CSS property a `transitionend` event is for.
+    pub fn transition_property(data: &TransitionData) -> Option<String> {
+        data.downcast::<web_sys::TransitionEvent>()
+            .map(|evt| evt.property_name())
+    }
+
+    /// `ms` since the epoch as a date and time in the user's locale.
+    pub fn locale_time(ms: u64) -> String {
+        js_sys::Date::new(&JsValue::from_f64(ms as f64))
+            .to_locale_string("default", &JsValue::UNDEFINED)
+            .into()
+    }
+
+    /// The page's URL hash, including the `#`.
+    pub fn location_hash() -> Option<String> {
+        web_sys::window()?.location().hash().ok()
+    }
+
+    /// Replaces the current history entry's hash, or drops it when `hash` is
+    /// empty.
+    pub fn replace_hash(hash: &str) {
+        let Some(window) = web_sys::window() else {
+            return;
+        };
+        let location = window.location();
+        let url = match hash {
+            "" => format!(
+                "{}{}",
+                location.pathname().unwrap_or_default(),
+                location.search().unwrap_or_default()
+            ),
+            hash => hash.to_string(),
+        };
+        if let Ok(history) = window.history() {
+            let _ = history.replace_state_with_url(&JsValue::NULL, "", Some(&url));
+        }
+    }
+
+    /// An image file pasted from the clipboard.
+    pub struct PastedFile(web_sys::File);
+
+    impl PastedFile {
+        pub fn mime(&self) -> String {
+            self.0.type_()
+        }
+
+        /// The file's contents, or `None` if it couldn't be read.
+        pub async fn read(self) -> Option<Vec<u8>> {
+            let buffer = JsFuture::from(self.0.array_buffer()).await.ok()?;
+            Some(js_sys::Uint8Array::new(&buffer).to_vec())
+        }
+    }
+
+    /// First file on the clipboard of a paste event whose MIME type passes
+    /// `accept`.
+    pub fn pasted_file(data: &ClipboardData, accept: impl Fn(&str) -> bool) -> Option<PastedFile> {
+        let evt = data
+            .try_as_web_event()?
+            .dyn_into::<web_sys::ClipboardEvent>()
+            .ok()?;
+        let items = evt.clipboard_data()?.items();
+        (0..items.length())
+            .filter_map(|index| items.get(index))
+            .filter(|item| item.kind() == "file" && accept(&item.type_()))
+            .find_map(|item| item.get_as_file().ok().flatten())
+            .map(PastedFile)
+    }
+
+    fn media_query_list(query: &str) -> Option<MediaQueryList> {
+        web_sys::window()?.match_media(query).ok().flatten()
+    }
+
+    /// Whether the media `query` currently matches.
+    pub fn media_matches(query: &str) -> bool {
+        media_query_list(query).is_some_and(|list| list.matches())
+    }
+
+    /// `change` listener on a media query, removed when this is dropped.
+    pub struct MediaListener {
+        list: MediaQueryList,
+        onchange: Closure<dyn FnMut(MediaQueryListEvent)>,
+    }
+
+    impl MediaListener {
+        /// Calls `onchange` with whether `query` matches whenever that flips.
+        pub fn attach(query: &str, mut onchange: impl FnMut(bool) + 'static) -> Option<Self> {
+            let list = media_query_list(query)?;
+            let onchange =
+                Closure::<dyn FnMut(MediaQueryListEvent)>::new(move |evt: MediaQueryListEvent| {
+                    onchange(evt.matches())
+                });
+            list.add_event_listener_with_callback("change", onchange.as_ref().unchecked_ref())
+                .ok()?;
+            Some(Self { list, onchange })
+        }
+    }
+
+    impl Drop for MediaListener {
+        fn drop(&mut self) {
+            let _ = self.list.remove_event_listener_with_callback(
+                "change",
+                self.onchange.as_ref().unchecked_ref(),
+            );
+        }
+    }
+
+    /// Listener for `event` on the browser window, removed when this is
+    /// dropped.
+    pub struct WindowListener {
+        event: &'static str,
+        listener: Closure<dyn FnMut()>,
+    }
+
+    impl WindowListener {
+        pub fn attach(event: &'static str, listener: impl FnMut() + 'static) -> Option<Self> {
+            let listener = Closure::<dyn FnMut()>::new(listener);
+            web_sys::window()?
+                .add_event_listener_with_callback(event, listener.as_ref().unchecked_ref())
+                .ok()?;
+            Some(Self { event, listener })
+        }
+    }
+
+    impl Drop for WindowListener {
+        fn drop(&mut self) {
+            if let Some(window) = web_sys::window() {
+                let _ = window.remove_event_listener_with_callback(
+                    self.event,
+                    self.listener.as_ref().unchecked_ref(),
+                );
+            }
+        }
+    }
+
+    /// `ResizeObserver` on a mounted element, disconnected when this is
+    /// dropped.
+    pub struct SizeObserver {
+        observer: web_sys::ResizeObserver,
+        _onresize: Closure<dyn FnMut(js_sys::Array)>,
+    }
+
+    impl SizeObserver {
+        /// Calls `onresize` whenever the mounted element changes size.
+        pub fn observe(
+            mounted: &MountedData,
+            mut onresize: impl FnMut() + 'static,
+        ) -> Option<Self> {
+            let element = mounted.try_as_web_event()?;
+            let onresize =
+                Closure::<dyn FnMut(js_sys::Array)>::new(move |_: js_sys::Array| onresize());
+            let observer = web_sys::ResizeObserver::new(onresize.as_ref().unchecked_ref()).ok()?;
+            observer.observe(&element);
+            Some(Self {
+                observer,
+                _onresize: onresize,
+            })
+        }
+    }
+
+    impl Drop for SizeObserver {
+        fn drop(&mut self) {
+            self.observer.disconnect();
+        }
+    }
+}
+
+#[cfg(not(feature = "web"))]
+mod inert {
+    use dioxus::prelude::*;
+    use std::future::Future;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    pub fn now() -> f64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0.0, |elapsed| elapsed.as_secs_f64() * 1000.0)
+    }
+
+    /// Never resolves: there's no event loop to wake it.
+    pub fn sleep(_ms: u32) -> impl Future<Output = ()> {
+        std::future::pending()
+    }
+
+    pub async fn next_frame() -> Option<f64> {
+        None
+    }
+
+    pub fn load(_key: &str) -> Option<String> {
+        None
+    }
+
+    pub fn store(_key: &str, _value: &str) {}
+
+    pub fn viewport_size() -> Option<(f64, f64)> {
+        None
+    }
+
+    pub fn element_size(_id: &str) -> Option<(f64, f64)> {
+        None
+    }
+
+    pub fn closest_size(_id: &str, _selector: &str) -> Option<(f64, f64)> {
+        None
+    }
+
+    pub fn attribute_at(_point: (f64, f64), _name: &str) -> Option<String> {
+        None
+    }
+
+    pub fn mounted_size(_mounted: &MountedData) -> Option<(f64, f64)> {
+        None
+    }
+
+    pub fn capture_pointer(_mounted: &MountedData, _pointer_id: i32) -> bool {
+        false
+    }
+
+    pub fn release_pointer(_mounted: &MountedData, _pointer_id: i32) {}
+
+    pub fn computed_style(_mounted: &MountedData, _property: &str) -> Option<String> {
+        None
+    }
+
+    pub fn child_computed_style(_mounted: &MountedData, _property: &str) -> Option<String> {
+        None
+    }
+
+    pub fn content_widths(_mounted: &MountedData) -> Option<(f64, f64)> {
+        None
+    }
+
+    pub fn set_font_size(_mounted: &MountedData, _px: Option<f64>) {}
+
+    pub fn selection_start(_mounted: &MountedData) -> Option<u32> {
+        None
+    }
+
+    pub fn clone_scaled(_id: &str, _container: &MountedData, _fit: (f64, f64)) -> Option<()> {
+        None
+    }
+
+    pub fn transition_property(_data: &TransitionData) -> Option<String> {
+        None
+    }
+
+    /// No locale to format with outside the browser.
+    pub fn locale_time(_ms: u64) -> String {
+        String::new()
+    }
+
+    pub fn location_hash() -> Option<String> {
+        None
+    }
+
+    pub fn replace_hash(_hash: &str) {}
+
+    /// Never made: there's no clipboard to paste from.
+    pub enum PastedFile {}
+
+    impl PastedFile {
+        pub fn mime(&self) -> String {
+            match *self {}
+        }
+
+        pub async fn read(self) -> Option<Vec<u8>> {
+            match self {}
+        }
+    }
+
+    pub fn pasted_file(
+        _data: &ClipboardData,
+        _accept: impl Fn(&str) -> bool,
+    ) -> Option<PastedFile> {
+        None
+    }
+
+    pub fn media_matches(_query: &str) -> bool {
+        false
+    }
+
+    pub struct MediaListener;
+
+    impl MediaListener {
+        pub fn attach(_query: &str, _onchange: impl FnMut(bool) + 'static) -> Option<Self> {
+            None
+        }
+    }
+
+    pub struct WindowListener;
+
+    impl WindowListener {
+        pub fn attach(_event: &'static str, _listener: impl FnMut() + 'static) -> Option<Self> {
+            None
+        }
+    }
+
+    pub struct SizeObserver;
+
+    impl SizeObserver {
+        pub fn observe(_mounted: &MountedData, _onresize: impl FnMut() + 'static) -> Option<Self> {
+            None
+        }
+    }
+}
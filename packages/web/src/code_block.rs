@@ -0,0 +1,38 @@
+//! Syntax-highlighted code, following the system's light/dark color scheme.
+
+use crate::hooks::use_dark_mode;
+use crate::server_fn::{use_server_fn, ServerFnOptions};
+use dioxus::prelude::*;
+
+/// Renders `code` highlighted as `language` by the `highlight` server
+/// function. Plain text is shown until the highlighted version arrives, or
+/// if highlighting fails.
+#[component]
+pub fn CodeBlock(
+    code: ReadSignal<String>,
+    /// Name or file extension of the language, e.g. `"rust"` or `"rs"`.
+    language: ReadSignal<String>,
+    #[props(default)] class: String,
+) -> Element {
+    let dark = use_dark_mode();
+    let highlighted = use_server_fn(
+        move || (code.cloned(), language.cloned(), dark()),
+        |(code, language, dark)| api::highlight(code, language, dark),
+        ServerFnOptions {
+            cache: true,
+            ..Default::default()
+        },
+    );
+
+    match highlighted.data() {
+        Some(html) => rsx! {
+            div {
+                class: "overflow-auto text-sm [&>pre]:p-3 {class}",
+                dangerous_inner_html: html,
+            }
+        },
+        None => rsx! {
+            pre { class: "overflow-auto p-3 text-sm {class}", "{code}" }
+        },
+    }
+}
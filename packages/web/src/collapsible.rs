@@ -0,0 +1,114 @@
+//! Smooth height collapse. CSS can't transition to or from `height: auto`,
+//! so [`Collapsible`] measures its content and animates between 0 and that
+//! height instead, measuring again whenever the content resizes.
+
+use crate::browser::{self, SizeObserver};
+use crate::hooks::use_reduced_motion;
+use dioxus::prelude::*;
+
+/// CSS height of the box. Until the content is measured an open box just
+/// follows it, which also keeps it from animating open on mount.
+fn collapsed_height(open: bool, content_height: Option<f64>) -> String {
+    match (open, content_height) {
+        (false, _) => "0px".to_string(),
+        (true, Some(height)) => format!("{height}px"),
+        (true, None) => "auto".to_string(),
+    }
+}
+
+/// Length of the transition towards `open`, from the state the last one
+/// `settled` on. Nothing animates once settled, so content resizes are
+/// followed immediately.
+fn transition_ms(open: bool, settled: bool, reduced_motion: bool, duration_ms: u32) -> u32 {
+    if reduced_motion || settled == open {
+        0
+    } else {
+        duration_ms
+    }
+}
+
+/// Shows `children` when `open`, sliding them open and shut.
+///
+/// Once open, the box follows its content's height as it grows or shrinks,
+/// without animating, so content resized by the user (a splitter, a
+/// textarea) doesn't trail behind the pointer. Closed content stays mounted
+/// but is hidden from the tab order and assistive technology once the
+/// transition ends.
+#[component]
+pub fn Collapsible(
+    open: bool,
+    /// Length of the open and close transition.
+    #[props(default = 200)]
+    duration_ms: u32,
+    #[props(default)] class: String,
+    children: Element,
+) -> Element {
+    let reduced_motion = use_reduced_motion();
+    let mut content_height = use_signal(|| None::<f64>);
+    let mut observer = use_signal(|| None::<SizeObserver>);
+    // What the last finished transition settled on; it differs from `open`
+    // while one is running.
+    let mut settled = use_signal(|| open);
+
+    let onmounted = move |evt: Event<MountedData>| {
+        let content = evt.data();
+        let Some((_, height)) = browser::mounted_size(&content) else {
+            return;
+        };
+        content_height.set(Some(height));
+
+        let target = content.clone();
+        let resize_observer = SizeObserver::observe(&content, move || {
+            let height = browser::mounted_size(&target).map(|(_, height)| height);
+            content_height.set(height);
+        });
+        observer.set(resize_observer);
+    };
+
+    let height = collapsed_height(open, content_height());
+    let duration = transition_ms(open, settled(), reduced_motion(), duration_ms);
+    // `visibility` flips at the end of the transition when closing and at
+    // its start when opening.
+    let visibility = if open { "visible" } else { "hidden" };
+
+    rsx! {
+        div {
+            class: "overflow-hidden {class}",
+            style: "height:{height}; visibility:{visibility}; transition: height {duration}ms ease, visibility {duration}ms;",
+            aria_hidden: !open,
+            // `visibility` changes with every toggle, unlike the height of
+            // empty content, so its end marks the transition's.
+            ontransitionend: move |evt: Event<TransitionData>| {
+                if browser::transition_property(&evt.data()).as_deref() == Some("visibility") {
+                    settled.set(open);
+                }
+            },
+            div { onmounted, {children} }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closed_boxes_have_no_height_and_open_ones_their_contents() {
+        assert_eq!(collapsed_height(false, Some(120.0)), "0px");
+        assert_eq!(collapsed_height(false, None), "0px");
+        assert_eq!(collapsed_height(true, Some(120.0)), "120px");
+        assert_eq!(collapsed_height(true, None), "auto");
+    }
+
+    #[test]
+    fn only_toggling_animates() {
+        // Opening and closing, until the transition ends.
+        assert_eq!(transition_ms(true, false, false, 200), 200);
+        assert_eq!(transition_ms(false, true, false, 200), 200);
+        // Settled either way.
+        assert_eq!(transition_ms(true, true, false, 200), 0);
+        assert_eq!(transition_ms(false, false, false, 200), 0);
+        // Reduced motion jumps straight there.
+        assert_eq!(transition_ms(true, false, true, 200), 0);
+    }
+}
@@ -0,0 +1,188 @@
+//! A console panel docked to the bottom of the desktop, IDE style. It's
+//! resized from its top edge, toggled with Ctrl+` and remembers its height
+//! and whether it was open in `localStorage`. [`LogViewer`] lists what the
+//! desktop reported in it.
+
+use crate::browser::{self, viewport_size};
+use crate::collapsible::Collapsible;
+use crate::geometry::ResizeHandle;
+use crate::hooks::use_throttle;
+use crate::resizable::Resizable;
+use crate::shortcuts::{use_shortcut, ShortcutScope};
+use dioxus::logger::tracing;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use ui::{use_toasts, ToastLevel};
+
+const CONSOLE_KEY: &str = "jadeos.console";
+/// Shortest time between two saves of the panel's state.
+const SAVE_INTERVAL_MS: u32 = 250;
+
+/// Lowest height the panel can be resized to.
+const MIN_HEIGHT: f64 = 80.0;
+/// Highest share of the viewport the panel can take.
+const MAX_VIEWPORT_SHARE: f64 = 0.8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConsoleState {
+    pub open: bool,
+    /// Height of the open panel, in pixels.
+    pub height: f64,
+}
+
+impl Default for ConsoleState {
+    fn default() -> Self {
+        Self {
+            open: false,
+            height: 240.0,
+        }
+    }
+}
+
+/// Tallest the panel may be in a `viewport`.
+fn max_height(viewport: Option<(f64, f64)>) -> f64 {
+    viewport.map_or(f64::INFINITY, |(_, height)| {
+        (height * MAX_VIEWPORT_SHARE).max(MIN_HEIGHT)
+    })
+}
+
+fn load_state() -> ConsoleState {
+    decode_state(browser::load(CONSOLE_KEY))
+}
+
+/// The state saved as `json`, or the default when nothing (readable) was.
+fn decode_state(json: Option<String>) -> ConsoleState {
+    let Some(json) = json else {
+        return ConsoleState::default();
+    };
+    serde_json::from_str(&json).unwrap_or_else(|err| {
+        tracing::warn!("ignoring unreadable console state: {err}");
+        ConsoleState::default()
+    })
+}
+
+/// The docked panel, showing `children` below a header with a close button.
+#[component]
+pub fn ConsolePanel(children: Element) -> Element {
+    let mut state = use_signal(load_state);
+    // Resizing changes the height on every pointer move.
+    let saved = use_throttle(state, SAVE_INTERVAL_MS);
+    use_effect(move || {
+        let state = saved.read();
+        if let Ok(json) = serde_json::to_string(&*state) {
+            browser::store(CONSOLE_KEY, &json);
+        }
+    });
+    use_shortcut("Ctrl+`", ShortcutScope::Global, move || {
+        let open = state.peek().open;
+        state.write().open = !open;
+    });
+
+    let current = state();
+    // The stored height may come from a taller screen.
+    let max = max_height(viewport_size());
+    let height = current.height.clamp(MIN_HEIGHT, max);
+
+    // The panel slides shut but stays mounted, so a resize in progress
+    // when it closes still ends normally.
+    rsx! {
+        div {
+            class: "fixed inset-x-0 bottom-0",
+            style: "z-index: 8500;",
+            Collapsible { open: current.open,
+                Resizable {
+                    size: Some((0.0, height)),
+                    min_size: (0.0, MIN_HEIGHT),
+                    max_size: Some((f64::INFINITY, max)),
+                    handles: vec![ResizeHandle::North],
+                    onresize: move |(_, height): (f64, f64)| state.write().height = height,
+                    class: "flex flex-col border-t border-slate-300 bg-slate-900 text-slate-100",
+                    div { class: "flex items-center justify-between border-b border-slate-700 px-3 py-1 text-xs",
+                        span { class: "font-semibold uppercase tracking-wide text-slate-400", "Console" }
+                        button {
+                            class: "rounded px-1 text-slate-400 hover:bg-slate-700 hover:text-white",
+                            title: "Close (Ctrl+`)",
+                            onclick: move |_| state.write().open = false,
+                            "×"
+                        }
+                    }
+                    div { class: "min-h-0 flex-1 overflow-auto p-2 text-sm", {children} }
+                }
+            }
+        }
+    }
+}
+
+/// Tag and text color of a log line at `level`.
+fn level_label(level: ToastLevel) -> (&'static str, &'static str) {
+    match level {
+        ToastLevel::Info => ("info", "text-sky-300"),
+        ToastLevel::Success => ("ok", "text-emerald-300"),
+        ToastLevel::Warning => ("warn", "text-amber-300"),
+        ToastLevel::Error => ("error", "text-red-400"),
+    }
+}
+
+/// Everything the desktop has shown as a toast, oldest first, so messages
+/// can still be read after they were dismissed.
+#[component]
+pub fn LogViewer() -> Element {
+    let history = use_toasts().history();
+
+    rsx! {
+        if history.is_empty() {
+            p { class: "text-slate-500", "Nothing logged yet." }
+        }
+        ul { class: "font-mono text-xs",
+            for toast in history {
+                {
+                    let (label, color) = level_label(toast.level);
+                    rsx! {
+                        li { class: "flex gap-2",
+                            span { class: "w-10 shrink-0 {color}", "{label}" }
+                            span { class: "whitespace-pre-wrap", "{toast.message}" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_survives_a_reload() {
+        let state = ConsoleState {
+            open: true,
+            height: 312.0,
+        };
+        let saved = serde_json::to_string(&state).unwrap();
+        assert_eq!(decode_state(Some(saved)), state);
+
+        // First visit, or a state from another version.
+        assert_eq!(decode_state(None), ConsoleState::default());
+        assert_eq!(
+            decode_state(Some("{\"open\":true}".to_string())),
+            ConsoleState {
+                open: true,
+                ..ConsoleState::default()
+            }
+        );
+        assert_eq!(
+            decode_state(Some("not json".to_string())),
+            ConsoleState::default()
+        );
+    }
+
+    #[test]
+    fn stored_heights_are_capped_by_the_viewport() {
+        assert_eq!(max_height(Some((1280.0, 800.0))), 640.0);
+        // Never below the minimum, however small the screen.
+        assert_eq!(max_height(Some((320.0, 50.0))), MIN_HEIGHT);
+        assert_eq!(max_height(None), f64::INFINITY);
+    }
+}
@@ -0,0 +1,285 @@
+//! Window chrome skins. The active [`WindowDecoration`] is shared through
+//! context, so switching it re-skins every `Movable` at once.
+
+use crate::browser;
+use crate::desktop_size;
+use crate::snap::SnapLayoutMenu;
+use crate::window_manager::{use_window_manager, WindowId};
+use dioxus::core::Task;
+use dioxus::prelude::*;
+
+/// How long the maximize button has to be hovered before the snap layouts
+/// show.
+const SNAP_MENU_DELAY_MS: u32 = 400;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WindowDecoration {
+    /// Rounded frame, centered title and round buttons on the left.
+    #[default]
+    Modern,
+    /// Square frame, gradient title bar and boxed buttons on the right.
+    Classic,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TitleBarAction {
+    Minimize,
+    Maximize,
+    Close,
+}
+
+impl WindowDecoration {
+    pub const ALL: [WindowDecoration; 2] = [WindowDecoration::Modern, WindowDecoration::Classic];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            WindowDecoration::Modern => "Modern",
+            WindowDecoration::Classic => "Classic",
+        }
+    }
+
+    pub fn rounded(self) -> &'static str {
+        match self {
+            WindowDecoration::Modern => "rounded-[var(--jade-radius)]",
+            WindowDecoration::Classic => "rounded-none",
+        }
+    }
+
+    pub fn border(self) -> &'static str {
+        match self {
+            WindowDecoration::Modern => "border border-slate-200",
+            WindowDecoration::Classic => "border-2 border-slate-500",
+        }
+    }
+
+    pub fn shadow(self) -> &'static str {
+        match self {
+            WindowDecoration::Modern => "shadow-[0_4px_12px_rgb(0_0_0/var(--jade-shadow))]",
+            WindowDecoration::Classic => "shadow-[3px_3px_0_0_rgba(0,0,0,0.35)]",
+        }
+    }
+
+    fn title_bar_class(self) -> &'static str {
+        match self {
+            WindowDecoration::Modern => {
+                "flex h-8 shrink-0 items-center gap-2 rounded-t-[var(--jade-radius)] bg-slate-100 px-3 text-sm text-slate-700"
+            }
+            WindowDecoration::Classic => {
+                "flex h-6 shrink-0 items-center gap-1 bg-gradient-to-r from-blue-900 to-blue-500 px-1 text-xs font-bold text-white"
+            }
+        }
+    }
+
+    fn title_class(self) -> &'static str {
+        match self {
+            WindowDecoration::Modern => "flex-1 truncate text-center",
+            WindowDecoration::Classic => "flex-1 truncate",
+        }
+    }
+
+    /// Whether the buttons come before the title.
+    fn buttons_leading(self) -> bool {
+        self == WindowDecoration::Modern
+    }
+
+    /// The buttons in display order.
+    fn actions(self) -> [TitleBarAction; 3] {
+        use TitleBarAction::*;
+        match self {
+            WindowDecoration::Modern => [Close, Minimize, Maximize],
+            WindowDecoration::Classic => [Minimize, Maximize, Close],
+        }
+    }
+
+    /// The buttons shown on a window, in display order: all of them, less
+    /// Close when the window isn't `closable`.
+    fn buttons(self, closable: bool) -> impl Iterator<Item = TitleBarAction> {
+        self.actions()
+            .into_iter()
+            .filter(move |&action| action != TitleBarAction::Close || closable)
+    }
+
+    fn button_class(self, action: TitleBarAction) -> &'static str {
+        match (self, action) {
+            (WindowDecoration::Modern, TitleBarAction::Close) => {
+                "h-3 w-3 rounded-full bg-red-400 hover:bg-red-500"
+            }
+            (WindowDecoration::Modern, TitleBarAction::Minimize) => {
+                "h-3 w-3 rounded-full bg-amber-400 hover:bg-amber-500"
+            }
+            (WindowDecoration::Modern, TitleBarAction::Maximize) => {
+                "h-3 w-3 rounded-full bg-green-400 hover:bg-green-500"
+            }
+            (WindowDecoration::Classic, _) => {
+                "flex h-4 w-4 items-center justify-center border border-t-white border-l-white border-b-slate-700 border-r-slate-700 bg-slate-300 text-[10px] leading-none text-black"
+            }
+        }
+    }
+
+    fn button_label(self, action: TitleBarAction) -> &'static str {
+        match (self, action) {
+            (WindowDecoration::Modern, _) => "",
+            (WindowDecoration::Classic, TitleBarAction::Minimize) => "_",
+            (WindowDecoration::Classic, TitleBarAction::Maximize) => "□",
+            (WindowDecoration::Classic, TitleBarAction::Close) => "×",
+        }
+    }
+}
+
+pub fn use_window_decoration() -> Signal<WindowDecoration> {
+    use_context()
+}
+
+/// Title bar of window `id`, laid out by the active decoration. Windows not
+/// owned by the `WindowManager`'s caller (`closable: false`) get no close
+/// button. Double-clicking the bar toggles maximizing, hovering the maximize
+/// button offers snap layouts, and the pin shows the window on every
+/// workspace.
+#[component]
+pub fn TitleBar(id: WindowId, closable: bool) -> Element {
+    let decoration = use_window_decoration()();
+    let mut manager = use_window_manager();
+    let title = manager
+        .read()
+        .get(id)
+        .map(|window| window.title.clone())
+        .unwrap_or_default();
+    let sticky = manager.read().is_sticky(id);
+
+    let mut toggle_maximize = move || {
+        if let Some(desktop) = desktop_size(id) {
+            manager.write().toggle_maximize(id, desktop);
+        }
+    };
+
+    let mut layouts_open = use_signal(|| false);
+    let mut pending = use_signal(|| None::<Task>);
+    let mut close_layouts = move || {
+        if let Some(task) = pending.write().take() {
+            task.cancel();
+        }
+        layouts_open.set(false);
+    };
+
+    let buttons = rsx! {
+        div { class: "flex shrink-0 items-center gap-1.5",
+            for action in decoration.buttons(closable) {
+                div {
+                    key: "{action:?}",
+                    class: "relative flex",
+                    onmouseenter: move |_| {
+                        if action == TitleBarAction::Maximize {
+                            close_layouts();
+                            pending.set(Some(spawn(async move {
+                                browser::sleep(SNAP_MENU_DELAY_MS).await;
+                                layouts_open.set(true);
+                            })));
+                        }
+                    },
+                    onmouseleave: move |_| close_layouts(),
+                    // Keep presses from starting a window drag.
+                    onpointerdown: move |evt| evt.stop_propagation(),
+                    ondoubleclick: move |evt| evt.stop_propagation(),
+                    button {
+                        class: decoration.button_class(action),
+                        title: "{action:?}",
+                        onclick: move |_| match action {
+                            TitleBarAction::Minimize => manager.write().minimize(id),
+                            TitleBarAction::Maximize => {
+                                close_layouts();
+                                toggle_maximize();
+                            }
+                            TitleBarAction::Close => manager.write().close(id),
+                        },
+                        {decoration.button_label(action)}
+                    }
+                    if action == TitleBarAction::Maximize && layouts_open() {
+                        SnapLayoutMenu { id, onselect: move |_| close_layouts() }
+                    }
+                }
+            }
+        }
+    };
+
+    rsx! {
+        div {
+            class: decoration.title_bar_class(),
+            ondoubleclick: move |_| toggle_maximize(),
+            if decoration.buttons_leading() {
+                {buttons.clone()}
+            }
+            span { class: decoration.title_class(), "{title}" }
+            button {
+                class: if sticky { "shrink-0 opacity-100" } else { "shrink-0 opacity-30 hover:opacity-70" },
+                title: if sticky { "Show on this workspace only" } else { "Show on all workspaces" },
+                aria_pressed: sticky,
+                onpointerdown: move |evt| evt.stop_propagation(),
+                ondoubleclick: move |evt| evt.stop_propagation(),
+                onclick: move |_| manager.write().set_sticky(id, !sticky),
+                "📌"
+            }
+            if !decoration.buttons_leading() {
+                {buttons}
+            }
+        }
+    }
+}
+
+/// Switches the active decoration.
+#[component]
+pub fn DecorationPicker() -> Element {
+    let mut decoration = use_window_decoration();
+
+    rsx! {
+        div {
+            class: "fixed top-3 right-3 flex gap-1 rounded-lg bg-slate-800/80 p-1 text-xs",
+            style: "z-index: 9000;",
+            for option in WindowDecoration::ALL {
+                button {
+                    key: "{option:?}",
+                    class: if decoration() == option {
+                        "rounded-md bg-slate-500 px-2 py-1 text-white"
+                    } else {
+                        "rounded-md px-2 py-1 text-slate-300"
+                    },
+                    onclick: move |_| decoration.set(option),
+                    "{option.name()}"
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use TitleBarAction::*;
+
+    #[test]
+    fn each_decoration_lays_its_buttons_out_in_its_own_order() {
+        let modern = WindowDecoration::Modern.buttons(true).collect::<Vec<_>>();
+        assert_eq!(modern, [Close, Minimize, Maximize]);
+        assert!(WindowDecoration::Modern.buttons_leading());
+
+        let classic = WindowDecoration::Classic.buttons(true).collect::<Vec<_>>();
+        assert_eq!(classic, [Minimize, Maximize, Close]);
+        assert!(!WindowDecoration::Classic.buttons_leading());
+    }
+
+    #[test]
+    fn windows_that_cannot_be_closed_get_no_close_button() {
+        for decoration in WindowDecoration::ALL {
+            let buttons = decoration.buttons(false).collect::<Vec<_>>();
+            assert_eq!(buttons.len(), 2, "{decoration:?}");
+            assert!(!buttons.contains(&Close), "{decoration:?}");
+        }
+    }
+
+    #[test]
+    fn classic_buttons_are_labelled_and_modern_ones_are_not() {
+        for action in [Minimize, Maximize, Close] {
+            assert_eq!(WindowDecoration::Modern.button_label(action), "");
+            assert_ne!(WindowDecoration::Classic.button_label(action), "");
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! Keeping the URL hash in sync with the focused window, so a bookmark or a
+//! shared link reopens the same app in the same place.
+//!
+//! The hash names the focused window's app followed by the path-like state
+//! the app chose to expose with [`use_deep_link_state`], e.g.
+//! `#/files/home/docs` for the file manager showing `/home/docs`. Loading a
+//! page with such a hash focuses a window of that app, opening one if there
+//! is none, and hands it the state.
+
+use crate::browser;
+use crate::shortcuts::CurrentWindow;
+use crate::window_manager::{use_window_manager, WindowId, WindowManager};
+use dioxus::logger::tracing;
+use dioxus::prelude::*;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+use std::collections::HashMap;
+
+/// Characters escaped in link state, like `encodeURI` does, plus `#`.
+const STATE: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'[')
+    .add(b'\\')
+    .add(b']')
+    .add(b'^')
+    .add(b'`')
+    .add(b'{')
+    .add(b'|')
+    .add(b'}');
+
+/// App ids can't contain a `/`, which separates them from the state.
+const APP_ID: &AsciiSet = &STATE.add(b'/');
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeepLink {
+    pub app_id: String,
+    /// Path-like app state, starting with `/`; `None` for just the app.
+    pub state: Option<String>,
+}
+
+impl DeepLink {
+    /// Parses a location hash such as `#/files/home/docs`.
+    pub fn parse(hash: &str) -> Option<Self> {
+        let link = hash.strip_prefix('#').unwrap_or(hash).strip_prefix('/')?;
+        let (app_id, state) = match link.find('/') {
+            Some(index) => (&link[..index], Some(&link[index..])),
+            None => (link, None),
+        };
+        if app_id.is_empty() {
+            return None;
+        }
+        let decode = |value: &str| {
+            percent_decode_str(value)
+                .decode_utf8()
+                .ok()
+                .map(|value| value.into_owned())
+        };
+        Some(Self {
+            app_id: decode(app_id)?,
+            state: match state.filter(|state| *state != "/") {
+                Some(state) => Some(decode(state)?),
+                None => None,
+            },
+        })
+    }
+
+    /// The location hash encoding this link.
+    pub fn to_hash(&self) -> String {
+        let mut hash = format!("#/{}", utf8_percent_encode(&self.app_id, APP_ID));
+        if let Some(state) = self.state.as_deref().filter(|state| !state.is_empty()) {
+            if !state.starts_with('/') {
+                hash.push('/');
+            }
+            hash.extend(utf8_percent_encode(state, STATE));
+        }
+        hash
+    }
+}
+
+/// Link state of each window, provided by [`use_deep_link`].
+#[derive(Clone, Copy)]
+pub struct DeepLinks {
+    states: Signal<HashMap<WindowId, Signal<String>>>,
+    /// State from the loaded URL, waiting for its window's app to mount and
+    /// claim it.
+    pending: Signal<HashMap<WindowId, String>>,
+}
+
+impl DeepLinks {
+    fn new() -> Self {
+        Self {
+            states: Signal::new(HashMap::new()),
+            pending: Signal::new(HashMap::new()),
+        }
+    }
+
+    /// The link to window `id`, if it runs an app.
+    pub fn link(&self, manager: &WindowManager, id: WindowId) -> Option<DeepLink> {
+        Some(DeepLink {
+            app_id: manager.get(id)?.app_id.clone()?,
+            state: self.states.read().get(&id).map(|state| state()),
+        })
+    }
+}
+
+/// Focuses a window of the linked app, launching one if needed, and queues
+/// the link's state for it. Links to apps not in `known_apps` are ignored.
+pub fn apply_deep_link(
+    manager: &mut WindowManager,
+    link: DeepLink,
+    known_apps: &[&str],
+) -> Option<(WindowId, Option<String>)> {
+    if !known_apps.contains(&link.app_id.as_str()) {
+        tracing::warn!("ignoring link to unknown app `{}`", link.app_id);
+        return None;
+    }
+    let existing = manager.mru().iter().copied().find(|&id| {
+        manager
+            .get(id)
+            .is_some_and(|window| window.app_id.as_deref() == Some(link.app_id.as_str()))
+    });
+    let id = match existing {
+        Some(id) => id,
+        None => manager.launch(link.app_id.clone(), link.app_id).id,
+    };
+    manager.restore(id);
+    if !manager.is_sticky(id) {
+        manager.switch_workspace(manager.workspace_of(id));
+    }
+    manager.focus(id);
+    Some((id, link.state))
+}
+
+/// Opens the app linked by the page's hash once, then keeps the hash
+/// pointing at the focused window. Call after the initial windows are open,
+/// so the link reuses one of them instead of opening a duplicate.
+pub fn use_deep_link(known_apps: &'static [&'static str]) {
+    let mut manager = use_window_manager();
+    let links = use_context_provider(DeepLinks::new);
+
+    use_hook(move || {
+        let link = browser::location_hash().and_then(|hash| DeepLink::parse(&hash))?;
+        let (id, state) = apply_deep_link(&mut manager.write(), link, known_apps)?;
+        let mut pending = links.pending;
+        pending.write().extend(state.map(|state| (id, state)));
+        Some(())
+    });
+
+    use_effect(move || {
+        let manager = manager.read();
+        let hash = manager
+            .focused()
+            .and_then(|id| links.link(&manager, id))
+            .map(|link| link.to_hash())
+            .unwrap_or_default();
+        // Replacing rather than pushing, so following the focus doesn't fill
+        // the back button with every window switch.
+        if browser::location_hash().unwrap_or_default() != hash {
+            browser::replace_hash(&hash);
+        }
+    });
+}
+
+/// Exposes an app's path-like `state` (such as the current directory) in
+/// the URL while its window is focused, starting from the linked state if
+/// the page was opened with a link to this window. Does nothing outside a
+/// window.
+pub fn use_deep_link_state(mut state: Signal<String>) {
+    let window = try_use_context::<CurrentWindow>();
+    let links = try_use_context::<DeepLinks>();
+
+    use_hook(move || {
+        let (Some(CurrentWindow(id)), Some(mut links)) = (window, links) else {
+            return;
+        };
+        if let Some(linked) = links.pending.write().remove(&id) {
+            state.set(linked);
+        }
+        links.states.write().insert(id, state);
+    });
+
+    use_drop(move || {
+        if let (Some(CurrentWindow(id)), Some(mut links)) = (window, links) {
+            links.states.write().remove(&id);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(app_id: &str, state: Option<&str>) -> DeepLink {
+        DeepLink {
+            app_id: app_id.to_string(),
+            state: state.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn hashes_parse_into_an_app_and_its_state() {
+        assert_eq!(
+            DeepLink::parse("#/files/home/docs"),
+            Some(link("files", Some("/home/docs")))
+        );
+        assert_eq!(DeepLink::parse("#/notes"), Some(link("notes", None)));
+        assert_eq!(DeepLink::parse("#/notes/"), Some(link("notes", None)));
+        assert_eq!(DeepLink::parse("/notes"), Some(link("notes", None)));
+        assert_eq!(
+            DeepLink::parse("#/files/home/my%20docs%23"),
+            Some(link("files", Some("/home/my docs#")))
+        );
+        assert_eq!(DeepLink::parse(""), None);
+        assert_eq!(DeepLink::parse("#"), None);
+        assert_eq!(DeepLink::parse("#/"), None);
+        assert_eq!(DeepLink::parse("#files"), None);
+        assert_eq!(DeepLink::parse("#/files/%FF"), None);
+    }
+
+    #[test]
+    fn links_survive_a_round_trip_through_the_hash() {
+        for link in [
+            link("files", Some("/home/my docs/50% #1")),
+            link("files", Some("/")),
+            link("a/b", Some("/ünïcode")),
+            link("notes", None),
+        ] {
+            let hash = link.to_hash();
+            let parsed = DeepLink::parse(&hash).unwrap();
+            // `/` alone is the same as no state.
+            let state = link.state.clone().filter(|state| state != "/");
+            assert_eq!(parsed, DeepLink { state, ..link }, "{hash}");
+        }
+        assert_eq!(
+            link("files", Some("/my docs/50% #1")).to_hash(),
+            "#/files/my%20docs/50%25%20%231"
+        );
+        assert_eq!(link("files", Some("home")).to_hash(), "#/files/home");
+    }
+}
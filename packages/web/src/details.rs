@@ -0,0 +1,306 @@
+//! Properties panel docked on the right of the file manager, showing the
+//! metadata of the current selection and a preview of images and source
+//! files.
+
+use crate::animated_number::AnimatedNumber;
+use crate::browser;
+use crate::code_block::CodeBlock;
+use crate::geometry::ResizeHandle;
+use crate::resizable::Resizable;
+use crate::server_fn::{use_server_fn, ServerFnOptions};
+use dioxus::prelude::*;
+use ui::Spinner;
+
+const MIN_WIDTH: f64 = 180.0;
+const MAX_WIDTH: f64 = 480.0;
+const THUMBNAIL_SIZE: u32 = 256;
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "bmp"];
+
+fn is_image(path: &str) -> bool {
+    path.rsplit_once('.').is_some_and(|(_, ext)| {
+        IMAGE_EXTENSIONS
+            .iter()
+            .any(|known| known.eq_ignore_ascii_case(ext))
+    })
+}
+
+/// Source files whose start the panel shows highlighted.
+const SOURCE_EXTENSIONS: &[&str] = &[
+    "c", "css", "go", "h", "html", "js", "json", "py", "rs", "sh", "toml", "ts",
+];
+/// Bytes of a source file shown in its preview.
+const SOURCE_PREVIEW_LEN: u64 = 4096;
+
+/// The language of `path`, by extension, if it's source code.
+fn source_language(path: &str) -> Option<String> {
+    let (_, ext) = path.rsplit_once('.')?;
+    let ext = ext.to_lowercase();
+    SOURCE_EXTENSIONS.contains(&ext.as_str()).then_some(ext)
+}
+
+/// Formats a byte count with a binary unit, e.g. `"1.5 MiB"`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// `data:` URL of a PNG, so thumbnails can be shown without a blob URL to
+/// revoke.
+fn png_data_url(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut url = String::from("data:image/png;base64,");
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &byte)| n | (byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                url.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                url.push('=');
+            }
+        }
+    }
+    url
+}
+
+/// What the Size row shows: the length of a file, or the total size of a
+/// directory once `dir_size` is done with it.
+fn displayed_size(
+    is_dir: bool,
+    len: u64,
+    total: Option<u64>,
+    failed: bool,
+) -> Result<u64, &'static str> {
+    match (is_dir, total) {
+        (false, _) => Ok(len),
+        (true, Some(total)) => Ok(total),
+        (true, None) if failed => Err("Unknown"),
+        (true, None) => Err("Calculating…"),
+    }
+}
+
+/// Collapsible panel showing `stat_path` details of `selection`, the total
+/// size of selected directories and a thumbnail of selected images. Dragging
+/// its left edge resizes it.
+#[component]
+pub fn DetailsPanel(selection: ReadSignal<Option<String>>) -> Element {
+    let mut collapsed = use_signal(|| false);
+    // Kept here so it survives collapsing the panel.
+    let mut width = use_signal(|| 260.0);
+
+    let stat = use_server_fn(
+        move || selection.cloned(),
+        |path: Option<String>| async move {
+            match path {
+                Some(path) => api::stat_path(path).await.map(Some),
+                None => Ok(None),
+            }
+        },
+        ServerFnOptions::default(),
+    );
+    let size = use_server_fn(
+        move || {
+            stat.data()
+                .flatten()
+                .filter(|stat| stat.is_dir)
+                .map(|stat| stat.path)
+        },
+        |path: Option<String>| async move {
+            match path {
+                Some(path) => api::dir_size(path).await.map(Some),
+                None => Ok(None),
+            }
+        },
+        ServerFnOptions::default(),
+    );
+    let thumbnail = use_server_fn(
+        move || selection.cloned().filter(|path| is_image(path)),
+        |path: Option<String>| async move {
+            match path {
+                Some(path) => api::thumbnail(path, THUMBNAIL_SIZE).await.map(Some),
+                None => Ok(None),
+            }
+        },
+        ServerFnOptions {
+            cache: true,
+            ..Default::default()
+        },
+    );
+    let source = use_server_fn(
+        move || {
+            selection
+                .cloned()
+                .filter(|path| source_language(path).is_some())
+        },
+        |path: Option<String>| async move {
+            match path {
+                Some(path) => api::read_range(path, 0, SOURCE_PREVIEW_LEN)
+                    .await
+                    .map(|bytes| Some(String::from_utf8_lossy(&bytes).into_owned())),
+                None => Ok(None),
+            }
+        },
+        ServerFnOptions::default(),
+    );
+
+    if collapsed() {
+        return rsx! {
+            div { class: "flex h-full w-6 flex-col items-center border-l border-slate-200 bg-slate-50",
+                button {
+                    class: "mt-2 text-slate-500 hover:text-slate-800",
+                    title: "Show details",
+                    onclick: move |_| collapsed.set(false),
+                    "‹"
+                }
+            }
+        };
+    }
+
+    let body = match (selection(), stat.data().flatten(), stat.error()) {
+        (None, _, _) => rsx! {
+            p { class: "text-sm text-slate-500", "Nothing selected" }
+        },
+        (Some(_), _, Some(err)) => rsx! {
+            p { class: "text-sm text-red-600", "{err}" }
+        },
+        (Some(_), None, None) => rsx! {
+            Spinner {}
+        },
+        (Some(_), Some(stat), None) => {
+            let name = stat.path.rsplit('/').next().unwrap_or_default().to_string();
+            let kind = match (stat.is_symlink, stat.is_dir) {
+                (true, _) => "Symbolic link",
+                (false, true) => "Folder",
+                (false, false) => "File",
+            };
+            let size_bytes = displayed_size(
+                stat.is_dir,
+                stat.len,
+                size.data().flatten(),
+                size.error().is_some(),
+            );
+            let modified = stat
+                .modified_ms
+                .map(browser::locale_time)
+                .unwrap_or_default();
+            let preview = source.data().flatten().zip(source_language(&stat.path));
+
+            rsx! {
+                if let Some(bytes) = thumbnail.data().flatten() {
+                    img {
+                        class: "mb-3 max-h-48 w-full rounded object-contain",
+                        src: png_data_url(&bytes),
+                        alt: "{name}",
+                    }
+                }
+                if let Some((code, language)) = preview {
+                    CodeBlock { class: "mb-3 max-h-48 rounded bg-white", code, language }
+                }
+                h3 { class: "mb-2 break-all font-semibold text-slate-800", "{name}" }
+                dl { class: "grid grid-cols-[auto_1fr] gap-x-3 gap-y-1 text-sm",
+                    dt { class: "text-slate-500", "Kind" }
+                    dd { "{kind}" }
+                    dt { class: "text-slate-500", "Size" }
+                    dd {
+                        match size_bytes {
+                            Ok(bytes) => rsx! {
+                                AnimatedNumber {
+                                    value: bytes as f64,
+                                    format: |bytes: f64| format_bytes(bytes as u64),
+                                }
+                            },
+                            Err(text) => rsx! { "{text}" },
+                        }
+                    }
+                    dt { class: "text-slate-500", "Modified" }
+                    dd { "{modified}" }
+                    dt { class: "text-slate-500", "Location" }
+                    dd { class: "break-all", "{stat.path}" }
+                    if stat.readonly {
+                        dt { class: "text-slate-500", "Access" }
+                        dd { "Read only" }
+                    }
+                }
+            }
+        }
+    };
+
+    rsx! {
+        Resizable {
+            class: "flex h-full shrink-0 flex-col border-l border-slate-200 bg-slate-50",
+            // Only the width is resizable, so the height is never used.
+            size: (width(), 0.0),
+            min_size: (MIN_WIDTH, 0.0),
+            max_size: (MAX_WIDTH, f64::INFINITY),
+            handles: vec![ResizeHandle::West],
+            onresize: move |(new_width, _)| width.set(new_width),
+            div { class: "flex items-center justify-between border-b border-slate-200 px-3 py-2",
+                span { class: "text-sm font-medium text-slate-700", "Details" }
+                button {
+                    class: "text-slate-500 hover:text-slate-800",
+                    title: "Hide details",
+                    onclick: move |_| collapsed.set(true),
+                    "›"
+                }
+            }
+            div { class: "flex-1 overflow-auto p-3", {body} }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn previews_are_picked_by_extension() {
+        assert!(is_image("/photos/cat.PNG"));
+        assert!(is_image("a.b/c.webp"));
+        assert!(!is_image("/photos/png"));
+        assert!(!is_image("notes.txt"));
+
+        assert_eq!(source_language("src/Main.RS").as_deref(), Some("rs"));
+        assert_eq!(source_language("Cargo.toml").as_deref(), Some("toml"));
+        assert_eq!(source_language("README.md"), None);
+        assert_eq!(source_language("Makefile"), None);
+    }
+
+    #[test]
+    fn byte_counts_use_binary_units() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MiB");
+        // There is no unit past TiB.
+        assert_eq!(format_bytes(2048 * 1024u64.pow(4)), "2048.0 TiB");
+    }
+
+    #[test]
+    fn thumbnails_are_base64_encoded_with_padding() {
+        assert_eq!(png_data_url(b""), "data:image/png;base64,");
+        assert_eq!(png_data_url(b"Man"), "data:image/png;base64,TWFu");
+        assert_eq!(png_data_url(b"Ma"), "data:image/png;base64,TWE=");
+        assert_eq!(png_data_url(b"M"), "data:image/png;base64,TQ==");
+    }
+
+    #[test]
+    fn directories_show_their_total_once_it_is_known() {
+        assert_eq!(displayed_size(false, 42, None, false), Ok(42));
+        assert_eq!(displayed_size(true, 4096, None, false), Err("Calculating…"));
+        assert_eq!(displayed_size(true, 4096, Some(10_000), false), Ok(10_000));
+        assert_eq!(displayed_size(true, 4096, None, true), Err("Unknown"));
+    }
+}
@@ -0,0 +1,193 @@
+//! Typed drag-and-drop between components, including across `Movable`
+//! windows, which all live in the same document.
+//!
+//! The browser only lets drop targets inspect the dragged data on drop, so
+//! the payload itself is kept in [`DragContext`] and the data transfer just
+//! marks the drag as ours.
+
+use dioxus::prelude::*;
+use std::any::Any;
+use std::rc::Rc;
+
+const DRAG_FORMAT: &str = "application/x-jadeos-payload";
+
+/// The payload of the drag in progress, if any.
+#[derive(Clone, Copy)]
+pub struct DragContext {
+    payload: Signal<Option<Rc<dyn Any>>>,
+}
+
+impl DragContext {
+    pub fn new() -> Self {
+        Self {
+            payload: Signal::new(None),
+        }
+    }
+
+    fn start<T: 'static>(&mut self, payload: T) {
+        self.payload.set(Some(Rc::new(payload)));
+    }
+
+    fn clear(&mut self) {
+        self.payload.set(None);
+    }
+
+    /// The current payload, if there is one and it's a `T`.
+    fn get<T: Clone + 'static>(&self) -> Option<T> {
+        self.payload
+            .peek()
+            .as_ref()
+            .and_then(|payload| payload.downcast_ref::<T>().cloned())
+    }
+
+    /// How a drop target for `T` reacts to the drag in progress: `None`
+    /// when there is none.
+    fn hover<T: Clone + 'static>(&self) -> Option<Hover> {
+        self.payload.peek().as_ref()?;
+        Some(match self.get::<T>() {
+            Some(_) => Hover::Accept,
+            None => Hover::Reject,
+        })
+    }
+
+    /// Ends the drag in progress if its payload is a `T`, returning it.
+    /// Other drags are left running for a target that takes them.
+    fn take<T: Clone + 'static>(&mut self) -> Option<T> {
+        let payload = self.get::<T>()?;
+        self.clear();
+        Some(payload)
+    }
+}
+
+pub fn use_drag_context() -> DragContext {
+    use_context()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Hover {
+    Accept,
+    Reject,
+}
+
+/// Makes its children draggable, carrying `payload` to a matching
+/// [`DropZone`].
+#[component]
+pub fn Draggable<T: Clone + PartialEq + 'static>(
+    payload: T,
+    #[props(default)] class: String,
+    children: Element,
+) -> Element {
+    let mut drag = use_drag_context();
+
+    rsx! {
+        div {
+            class: "{class}",
+            draggable: true,
+            // Keep the enclosing window from starting its own drag.
+            onpointerdown: move |evt: Event<PointerData>| evt.stop_propagation(),
+            ondragstart: move |evt: Event<DragData>| {
+                let _ = evt.data_transfer().set_data(DRAG_FORMAT, std::any::type_name::<T>());
+                drag.start(payload.clone());
+            },
+            ondragend: move |_| drag.clear(),
+            {children}
+        }
+    }
+}
+
+/// Drop target accepting payloads of type `T`.
+///
+/// While hovered it shows whether the dragged payload would be accepted;
+/// payloads of any other type are rejected. Drops made holding Ctrl, the
+/// usual copy gesture, go to `onaccept_copy` when it's set.
+#[component]
+pub fn DropZone<T: Clone + PartialEq + 'static>(
+    onaccept: EventHandler<T>,
+    #[props(default)] onaccept_copy: Option<EventHandler<T>>,
+    #[props(default)] class: String,
+    children: Element,
+) -> Element {
+    let mut drag = use_drag_context();
+    let mut hover = use_signal(|| Option::<Hover>::None);
+
+    let hover_class = match hover() {
+        Some(Hover::Accept) => "ring-2 ring-emerald-400",
+        Some(Hover::Reject) => "ring-2 ring-red-400 cursor-not-allowed",
+        None => "",
+    };
+
+    rsx! {
+        div {
+            class: "{class} {hover_class}",
+            ondragover: move |evt: Event<DragData>| {
+                let Some(reaction) = drag.hover::<T>() else {
+                    return;
+                };
+                if reaction == Hover::Accept {
+                    // Cancelling dragover is what allows the drop.
+                    evt.prevent_default();
+                }
+                hover.set(Some(reaction));
+            },
+            ondragleave: move |_| hover.set(None),
+            ondrop: move |evt: Event<DragData>| {
+                evt.prevent_default();
+                hover.set(None);
+                if let Some(payload) = drag.take::<T>() {
+                    match onaccept_copy {
+                        Some(onaccept_copy) if evt.modifiers().ctrl() => {
+                            onaccept_copy.call(payload)
+                        }
+                        _ => onaccept.call(payload),
+                    }
+                }
+            },
+            {children}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Card(u32);
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct File(String);
+
+    fn with_context(test: impl FnOnce(DragContext)) {
+        let mut dom = VirtualDom::new(|| rsx! {});
+        dom.rebuild_in_place();
+        dom.in_scope(ScopeId::ROOT, || test(DragContext::new()));
+    }
+
+    #[test]
+    fn targets_only_accept_payloads_of_their_type() {
+        with_context(|mut drag| {
+            assert_eq!(drag.hover::<Card>(), None);
+
+            drag.start(Card(7));
+            assert_eq!(drag.hover::<Card>(), Some(Hover::Accept));
+            assert_eq!(drag.hover::<File>(), Some(Hover::Reject));
+
+            drag.clear();
+            assert_eq!(drag.hover::<Card>(), None);
+        });
+    }
+
+    #[test]
+    fn dropping_ends_the_drag_only_on_a_matching_target() {
+        with_context(|mut drag| {
+            drag.start(File("notes.txt".to_string()));
+
+            assert_eq!(drag.take::<Card>(), None);
+            assert_eq!(drag.hover::<File>(), Some(Hover::Accept));
+
+            assert_eq!(drag.take::<File>(), Some(File("notes.txt".to_string())));
+            assert_eq!(drag.hover::<File>(), None);
+            assert_eq!(drag.take::<File>(), None);
+        });
+    }
+}
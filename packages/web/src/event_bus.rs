@@ -0,0 +1,219 @@
+//! Topic based messaging between apps running in different windows, e.g. a
+//! file manager asking the editor to open a file:
+//!
+//! ```rust,ignore
+//! // In the file manager:
+//! let bus = use_event_bus();
+//! bus.emit(OPEN_FILE, "notes/todo.md".to_string());
+//!
+//! // In the editor:
+//! use_subscribe(OPEN_FILE, move |path: String| open(path));
+//! ```
+//!
+//! Payloads are typed; a subscriber only receives payloads of the type it
+//! subscribed with.
+
+use dioxus::prelude::*;
+use std::any::Any;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Topic the file manager emits a file's path on to have it opened in
+/// Notes.
+pub const OPEN_FILE: &str = "open-file";
+
+type Handler = Rc<dyn Fn(&dyn Any)>;
+
+#[derive(Default)]
+struct Subscribers {
+    by_topic: HashMap<&'static str, Vec<(usize, Handler)>>,
+    next_id: usize,
+}
+
+#[derive(Clone, Copy)]
+pub struct EventBus {
+    subscribers: Signal<Subscribers>,
+}
+
+/// Returned by [`EventBus::subscribe`]; pass it to [`EventBus::unsubscribe`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Subscription {
+    topic: &'static str,
+    id: usize,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self {
+            subscribers: Signal::new(Subscribers::default()),
+        }
+    }
+
+    /// Delivers `payload` to every subscriber of `topic` expecting a `T`.
+    pub fn emit<T: 'static>(&self, topic: &'static str, payload: T) {
+        // Collect first so handlers may (un)subscribe while being called.
+        let handlers: Vec<Handler> = self
+            .subscribers
+            .peek()
+            .by_topic
+            .get(topic)
+            .map(|handlers| {
+                handlers
+                    .iter()
+                    .map(|(_, handler)| handler.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        for handler in handlers {
+            handler(&payload);
+        }
+    }
+
+    pub fn subscribe<T: Clone + 'static>(
+        &mut self,
+        topic: &'static str,
+        handler: impl Fn(T) + 'static,
+    ) -> Subscription {
+        let mut subscribers = self.subscribers.write();
+        let id = subscribers.next_id;
+        subscribers.next_id += 1;
+
+        let handler: Handler = Rc::new(move |payload: &dyn Any| {
+            if let Some(payload) = payload.downcast_ref::<T>() {
+                handler(payload.clone());
+            }
+        });
+        subscribers
+            .by_topic
+            .entry(topic)
+            .or_default()
+            .push((id, handler));
+
+        Subscription { topic, id }
+    }
+
+    pub fn unsubscribe(&mut self, subscription: Subscription) {
+        if let Some(handlers) = self
+            .subscribers
+            .write()
+            .by_topic
+            .get_mut(subscription.topic)
+        {
+            handlers.retain(|(id, _)| *id != subscription.id);
+        }
+    }
+}
+
+pub fn use_event_bus() -> EventBus {
+    use_context()
+}
+
+/// Subscribes `handler` to `topic` for as long as the calling component is
+/// mounted.
+pub fn use_subscribe<T: Clone + 'static>(topic: &'static str, handler: impl Fn(T) + 'static) {
+    let mut bus = use_event_bus();
+    let subscription = use_hook(move || bus.subscribe(topic, handler));
+    use_drop(move || bus.unsubscribe(subscription));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    const TOPIC: &str = "test";
+
+    thread_local! {
+        static RECEIVED: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn received() -> Vec<String> {
+        RECEIVED.with_borrow_mut(std::mem::take)
+    }
+
+    fn subscribers(bus: &EventBus) -> usize {
+        bus.subscribers
+            .peek()
+            .by_topic
+            .get(TOPIC)
+            .map_or(0, Vec::len)
+    }
+
+    #[component]
+    fn Listener() -> Element {
+        use_subscribe(TOPIC, |payload: String| {
+            RECEIVED.with_borrow_mut(|received| received.push(format!("listener {payload}")));
+        });
+        rsx! {}
+    }
+
+    fn app() -> Element {
+        use_context_provider(EventBus::new);
+        let mounted = use_context_provider(|| Signal::new(true));
+        rsx! {
+            if mounted() {
+                Listener {}
+            }
+        }
+    }
+
+    #[test]
+    fn every_subscriber_of_a_topic_receives_its_payloads() {
+        let mut dom = VirtualDom::new(|| rsx! {});
+        dom.rebuild_in_place();
+        dom.in_scope(ScopeId::ROOT, || {
+            let mut bus = EventBus::new();
+            for name in ["first", "second"] {
+                bus.subscribe(TOPIC, move |payload: String| {
+                    RECEIVED.with_borrow_mut(|received| received.push(format!("{name} {payload}")));
+                });
+            }
+            // Neither the topic nor the payload type match.
+            bus.subscribe("other", |_: String| panic!("wrong topic"));
+            bus.subscribe(TOPIC, |_: u32| panic!("wrong payload type"));
+
+            bus.emit(TOPIC, "hello".to_string());
+            assert_eq!(received(), ["first hello", "second hello"]);
+        });
+    }
+
+    #[test]
+    fn unsubscribed_handlers_are_removed() {
+        let mut dom = VirtualDom::new(|| rsx! {});
+        dom.rebuild_in_place();
+        dom.in_scope(ScopeId::ROOT, || {
+            let mut bus = EventBus::new();
+            let first = bus.subscribe(TOPIC, |payload: String| {
+                RECEIVED.with_borrow_mut(|received| received.push(format!("first {payload}")));
+            });
+            bus.subscribe(TOPIC, |payload: String| {
+                RECEIVED.with_borrow_mut(|received| received.push(format!("second {payload}")));
+            });
+
+            bus.unsubscribe(first);
+            assert_eq!(subscribers(&bus), 1);
+            bus.emit(TOPIC, "hello".to_string());
+            assert_eq!(received(), ["second hello"]);
+        });
+    }
+
+    #[test]
+    fn subscriptions_end_when_their_component_unmounts() {
+        let mut dom = VirtualDom::new(app);
+        dom.rebuild_in_place();
+        let bus = dom.in_scope(ScopeId::APP, consume_context::<EventBus>);
+        dom.in_scope(ScopeId::APP, || bus.emit(TOPIC, "hello".to_string()));
+        assert_eq!(received(), ["listener hello"]);
+
+        dom.in_scope(ScopeId::APP, || {
+            consume_context::<Signal<bool>>().set(false);
+        });
+        dom.render_immediate_to_vec();
+        dom.in_scope(ScopeId::APP, || {
+            assert_eq!(subscribers(&bus), 0);
+            bus.emit(TOPIC, "again".to_string());
+        });
+        assert!(received().is_empty());
+    }
+}
@@ -0,0 +1,360 @@
+//! The file manager app: a directory listing driven by a breadcrumb, with a
+//! details panel for the selected entry.
+
+use crate::deep_link::use_deep_link_state;
+use crate::details::DetailsPanel;
+use crate::dnd::{Draggable, DropZone};
+use crate::event_bus::{use_event_bus, OPEN_FILE};
+use crate::file_ops::{use_file_transfer, TransferOp};
+use crate::server_fn::{use_server_fn, ServerFnOptions};
+use crate::session::use_session_state;
+use crate::shortcuts::CurrentWindow;
+use crate::window_manager::use_window_manager;
+use api::{DirPage, SortKey, MAX_DIR_PAGE};
+use dioxus::prelude::*;
+use ui::use_toasts;
+use ui::Spinner;
+
+/// Path of the directory `path` is in; the root is its own parent.
+pub fn parent_path(path: &str) -> String {
+    match path.trim_end_matches('/').rsplit_once('/') {
+        Some(("", _)) | None => "/".to_string(),
+        Some((parent, _)) => parent.to_string(),
+    }
+}
+
+/// `path` with `name` appended.
+pub fn join_path(path: &str, name: &str) -> String {
+    format!("{}/{name}", path.trim_end_matches('/'))
+}
+
+/// Each ancestor of `path`, from the root down to `path` itself, as
+/// `(label, path)`.
+pub fn path_segments(path: &str) -> Vec<(String, String)> {
+    let mut segments = vec![("/".to_string(), "/".to_string())];
+    let mut current = String::new();
+    for name in path.split('/').filter(|name| !name.is_empty()) {
+        current = join_path(&current, name);
+        segments.push((name.to_string(), current.clone()));
+    }
+    segments
+}
+
+/// The path typed into the breadcrumb, resolved against `current` unless
+/// it's absolute.
+fn typed_path(current: &str, typed: &str) -> String {
+    if typed.starts_with('/') {
+        typed.to_string()
+    } else {
+        join_path(current, typed)
+    }
+}
+
+/// Clickable path of the current directory. Clicking the empty space after
+/// the last segment switches to a text field where a path can be typed;
+/// paths that don't name a directory are reported inline.
+#[component]
+pub fn Breadcrumb(path: ReadSignal<String>, onnavigate: EventHandler<String>) -> Element {
+    let mut editing = use_signal(|| false);
+    let mut draft = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+    let mut checking = use_signal(|| false);
+
+    let mut submit = move || {
+        let typed = typed_path(&path(), &draft());
+        checking.set(true);
+        spawn(async move {
+            match api::stat_path(typed.clone()).await {
+                Ok(stat) if stat.is_dir => {
+                    error.set(None);
+                    editing.set(false);
+                    onnavigate.call(path_segments(&stat.path).pop().unwrap().1);
+                }
+                Ok(_) => error.set(Some(format!("{typed} is not a folder"))),
+                Err(err) => error.set(Some(api::ApiError::from(&err).message().to_string())),
+            }
+            checking.set(false);
+        });
+    };
+
+    if editing() {
+        return rsx! {
+            div { class: "flex flex-col gap-1",
+                input {
+                    class: "w-full rounded border border-slate-300 px-2 py-1 text-sm outline-none focus:border-sky-500",
+                    value: "{draft}",
+                    disabled: checking(),
+                    autofocus: true,
+                    oninput: move |evt| {
+                        draft.set(evt.value());
+                        error.set(None);
+                    },
+                    onkeydown: move |evt| match evt.key() {
+                        Key::Enter => submit(),
+                        Key::Escape => {
+                            error.set(None);
+                            editing.set(false);
+                        }
+                        _ => {}
+                    },
+                    onblur: move |_| {
+                        if !checking() {
+                            error.set(None);
+                            editing.set(false);
+                        }
+                    },
+                }
+                if let Some(message) = error() {
+                    p { class: "text-xs text-red-600", role: "alert", "{message}" }
+                }
+            }
+        };
+    }
+
+    let segments = path_segments(&path());
+    let last = segments.len() - 1;
+
+    rsx! {
+        nav {
+            class: "flex min-h-8 flex-1 cursor-text items-center gap-1 text-sm",
+            aria_label: "Path",
+            onclick: move |_| {
+                draft.set(path());
+                editing.set(true);
+            },
+            for (index, (label, target)) in segments.into_iter().enumerate() {
+                Fragment { key: "{target}",
+                    if index > 1 {
+                        span { class: "text-slate-400", "›" }
+                    }
+                    button {
+                        class: if index == last {
+                            "rounded px-1 font-medium text-slate-800"
+                        } else {
+                            "rounded px-1 text-slate-600 hover:bg-slate-200"
+                        },
+                        aria_current: if index == last { "page" } else { "false" },
+                        onclick: move |evt| {
+                            evt.stop_propagation();
+                            onnavigate.call(target.clone());
+                        },
+                        "{label}"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A file manager entry being dragged, by path.
+#[derive(Clone, Debug, PartialEq)]
+struct DraggedEntry(String);
+
+/// Whether dropping `from` on the folder `to_dir` would leave it where it
+/// is: dropped on itself or on the folder it's already in.
+fn stays_put(from: &str, to_dir: &str) -> bool {
+    from == to_dir || parent_path(from) == to_dir
+}
+
+/// Browses the sandbox, starting at `start`, or at the home directory the
+/// server picks when there is none. Entries can be dragged onto folders to
+/// move them, or copy them with Ctrl held.
+#[component]
+pub fn FileManager(start: Option<String>) -> Element {
+    // Empty until the user navigates away from home.
+    let mut path = use_signal(|| start.unwrap_or_default());
+    use_deep_link_state(path);
+    // Reopen the folder the window showed when the session was saved.
+    let window = try_use_context::<CurrentWindow>();
+    if let Some(CurrentWindow(id)) = window {
+        use_session_state(id, path);
+    }
+    let mut selection = use_signal(|| None::<String>);
+    let mut listing = use_server_fn(
+        move || path.cloned(),
+        |path| async move {
+            if path.is_empty() {
+                return api::home_listing(0, MAX_DIR_PAGE).await;
+            }
+            let entries = api::list_dir(path.clone(), SortKey::Name).await?;
+            Ok(DirPage {
+                path,
+                total: entries.len(),
+                entries,
+            })
+        },
+        ServerFnOptions::default(),
+    );
+    // The directory shown, once the server told where home is.
+    let current = use_memo(move || match path() {
+        path if path.is_empty() => listing.data().map(|page| page.path).unwrap_or(path),
+        path => path,
+    });
+
+    // Reflect the current folder in the window title.
+    let mut manager = use_window_manager();
+    use_effect(move || {
+        let path = current();
+        if let Some(CurrentWindow(id)) = window {
+            let name = path_segments(&path).pop().map(|(label, _)| label);
+            manager
+                .write()
+                .set_title(id, format!("Files — {}", name.unwrap_or_default()));
+        }
+    });
+
+    let bus = use_event_bus();
+    let transfer = use_file_transfer();
+    let mut toasts = use_toasts();
+    // Dropping an entry on a folder moves it there, or copies it with Ctrl
+    // held, asking the user about name conflicts. Transfers flash the
+    // window when they finish if the user went on to another one.
+    let drop_into = move |op: TransferOp, DraggedEntry(from): DraggedEntry, to_dir: String| {
+        if stays_put(&from, &to_dir) {
+            return;
+        }
+        spawn(async move {
+            for result in transfer.run(op, vec![from], to_dir).await {
+                if let Err(err) = result {
+                    toasts.error(&err);
+                }
+            }
+            listing.refetch();
+            if let Some(CurrentWindow(id)) = window {
+                manager.write().flash(id);
+            }
+        });
+    };
+    let mut navigate = move |to: String| {
+        selection.set(None);
+        path.set(to);
+    };
+
+    let body = match (listing.data(), listing.error()) {
+        (_, Some(err)) => rsx! {
+            p { class: "p-3 text-sm text-red-600", "{err}" }
+        },
+        (None, None) => rsx! {
+            div { class: "p-3", Spinner {} }
+        },
+        (Some(page), None) => rsx! {
+            ul { class: "flex-1 overflow-auto p-1 text-sm",
+                for entry in page.entries {
+                    li {
+                        key: "{entry.path}",
+                        class: if selection() == Some(entry.path.clone()) {
+                            "cursor-default rounded bg-sky-100 px-2 py-1"
+                        } else {
+                            "cursor-default rounded px-2 py-1 hover:bg-slate-100"
+                        },
+                        onclick: {
+                            let path = entry.path.clone();
+                            move |_| selection.set(Some(path.clone()))
+                        },
+                        ondoubleclick: {
+                            let path = entry.path.clone();
+                            move |_| {
+                                if entry.is_dir {
+                                    navigate(path.clone());
+                                } else {
+                                    bus.emit(OPEN_FILE, path.clone());
+                                }
+                            }
+                        },
+                        Draggable::<DraggedEntry> { payload: DraggedEntry(entry.path.clone()),
+                            if entry.is_dir {
+                                DropZone::<DraggedEntry> {
+                                    class: "rounded",
+                                    onaccept: {
+                                        let path = entry.path.clone();
+                                        move |dragged| {
+                                            drop_into(TransferOp::Move, dragged, path.clone())
+                                        }
+                                    },
+                                    onaccept_copy: {
+                                        let path = entry.path.clone();
+                                        move |dragged| {
+                                            drop_into(TransferOp::Copy, dragged, path.clone())
+                                        }
+                                    },
+                                    "📁 {entry.name}"
+                                }
+                            } else {
+                                "📄 {entry.name}"
+                            }
+                        }
+                    }
+                }
+            }
+        },
+    };
+
+    rsx! {
+        div { class: "flex h-full min-h-80 w-full min-w-[36rem] flex-col bg-white",
+            div { class: "flex items-center gap-2 border-b border-slate-200 px-2 py-1",
+                button {
+                    class: "rounded px-2 text-slate-600 hover:bg-slate-200 disabled:opacity-40",
+                    title: "Up",
+                    disabled: current() == "/",
+                    onclick: move |_| navigate(parent_path(&current())),
+                    "↑"
+                }
+                Breadcrumb { path: current, onnavigate: navigate }
+                // Reloads keep showing the previous listing meanwhile.
+                if listing.loading() && listing.data().is_some() {
+                    Spinner { class: "shrink-0 text-slate-400" }
+                }
+            }
+            div { class: "flex min-h-0 flex-1",
+                div { class: "flex min-w-0 flex-1 flex-col", {body} }
+                DetailsPanel { selection }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parents_stop_at_the_root() {
+        assert_eq!(parent_path("/home/ada/notes.txt"), "/home/ada");
+        assert_eq!(parent_path("/home/ada/"), "/home");
+        assert_eq!(parent_path("/home"), "/");
+        assert_eq!(parent_path("/"), "/");
+    }
+
+    #[test]
+    fn breadcrumbs_list_every_ancestor_from_the_root() {
+        let segments = |path| {
+            path_segments(path)
+                .into_iter()
+                .map(|(label, path)| format!("{label}={path}"))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(segments("/"), ["/=/"]);
+        assert_eq!(
+            segments("/home/ada/"),
+            ["/=/", "home=/home", "ada=/home/ada"]
+        );
+        // Doubled separators don't make empty segments.
+        assert_eq!(segments("//tmp//x"), ["/=/", "tmp=/tmp", "x=/tmp/x"]);
+    }
+
+    #[test]
+    fn typed_paths_are_relative_to_the_current_folder() {
+        assert_eq!(typed_path("/home/ada", "src"), "/home/ada/src");
+        assert_eq!(typed_path("/home/ada/", "src/ui"), "/home/ada/src/ui");
+        assert_eq!(typed_path("/", "tmp"), "/tmp");
+        assert_eq!(typed_path("/home/ada", "/etc"), "/etc");
+    }
+
+    #[test]
+    fn dropping_an_entry_where_it_already_is_does_nothing() {
+        assert!(stays_put("/home/ada/notes.txt", "/home/ada"));
+        assert!(stays_put("/home/ada/src", "/home/ada/src"));
+        assert!(!stays_put("/home/ada/notes.txt", "/home/ada/src"));
+        assert!(!stays_put("/home/ada/src", "/home"));
+    }
+}
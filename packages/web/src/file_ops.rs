@@ -0,0 +1,286 @@
+//! Moving and copying files in batches, asking the user how to resolve name
+//! conflicts as they come up.
+
+use api::{ApiError, ConflictPolicy};
+use dioxus::prelude::*;
+use futures_channel::oneshot;
+use std::cell::RefCell;
+use std::future::Future;
+use std::rc::Rc;
+use ui::{Button, Modal};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransferOp {
+    Move,
+    Copy,
+}
+
+/// The user's answer to a conflict.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Resolution {
+    pub policy: ConflictPolicy,
+    /// Use `policy` for the remaining conflicts of the batch without asking.
+    pub apply_to_all: bool,
+}
+
+const SKIP: Resolution = Resolution {
+    policy: ConflictPolicy::Skip,
+    apply_to_all: false,
+};
+
+#[derive(Clone)]
+struct PendingConflict {
+    name: String,
+    /// Entries of the batch still to be transferred after this one.
+    remaining: usize,
+    reply: Rc<RefCell<Option<oneshot::Sender<Resolution>>>>,
+}
+
+/// Runs transfers and holds the conflict [`ConflictDialog`] is showing.
+#[derive(Clone, Copy)]
+pub struct FileTransfer {
+    conflict: Signal<Option<PendingConflict>>,
+}
+
+impl FileTransfer {
+    pub fn new() -> Self {
+        Self {
+            conflict: Signal::new(None),
+        }
+    }
+
+    /// Transfers each of `paths` into `to_dir`, returning per entry its new
+    /// path, `None` if it was skipped, or the error.
+    ///
+    /// Conflicts are first attempted with `ConflictPolicy::Fail`; on a
+    /// conflict the user picks a resolution, optionally for the rest of the
+    /// batch, and the entry is retried with it.
+    pub async fn run(
+        self,
+        op: TransferOp,
+        paths: Vec<String>,
+        to_dir: String,
+    ) -> Vec<Result<Option<String>, ServerFnError>> {
+        run_with(
+            &paths,
+            |path, policy| transfer(op, path, &to_dir, policy),
+            |name, remaining| self.ask(name, remaining),
+        )
+        .await
+    }
+
+    async fn ask(mut self, name: String, remaining: usize) -> Resolution {
+        let (reply, answer) = oneshot::channel();
+        self.conflict.set(Some(PendingConflict {
+            name,
+            remaining,
+            reply: Rc::new(RefCell::new(Some(reply))),
+        }));
+        let resolution = answer.await.unwrap_or(SKIP);
+        self.conflict.set(None);
+        resolution
+    }
+
+    fn answer(&self, resolution: Resolution) {
+        if let Some(pending) = self.conflict.peek().as_ref() {
+            if let Some(reply) = pending.reply.borrow_mut().take() {
+                let _ = reply.send(resolution);
+            }
+        }
+    }
+}
+
+/// [`FileTransfer::run`] transferring each path with `transfer` and asking
+/// about conflicts with `ask`, so tests can stand in for the server and the
+/// dialog.
+async fn run_with<'a, T, A>(
+    paths: &'a [String],
+    transfer: impl Fn(&'a str, ConflictPolicy) -> T,
+    ask: impl Fn(String, usize) -> A,
+) -> Vec<Result<Option<String>, ServerFnError>>
+where
+    T: Future<Output = Result<Option<String>, ServerFnError>>,
+    A: Future<Output = Resolution>,
+{
+    let mut apply_to_all: Option<ConflictPolicy> = None;
+    let mut results = Vec::with_capacity(paths.len());
+
+    for (index, path) in paths.iter().enumerate() {
+        let policy = apply_to_all.unwrap_or(ConflictPolicy::Fail);
+        let mut result = transfer(path, policy).await;
+
+        let conflicted =
+            matches!(&result, Err(err) if matches!(ApiError::from(err), ApiError::Conflict(_)));
+        if conflicted {
+            let name = path.rsplit('/').next().unwrap_or(path).to_string();
+            let resolution = ask(name, paths.len() - index - 1).await;
+            if resolution.apply_to_all {
+                apply_to_all = Some(resolution.policy);
+            }
+            result = transfer(path, resolution.policy).await;
+        }
+
+        results.push(result);
+    }
+
+    results
+}
+
+async fn transfer(
+    op: TransferOp,
+    from: &str,
+    to_dir: &str,
+    policy: ConflictPolicy,
+) -> Result<Option<String>, ServerFnError> {
+    match op {
+        TransferOp::Move => api::move_path(from.to_string(), to_dir.to_string(), policy).await,
+        TransferOp::Copy => api::copy_path(from.to_string(), to_dir.to_string(), policy).await,
+    }
+}
+
+pub fn use_file_transfer() -> FileTransfer {
+    use_context()
+}
+
+/// Asks how to resolve the conflict of the running transfer, if any.
+#[component]
+pub fn ConflictDialog() -> Element {
+    let transfer = use_file_transfer();
+    let mut apply_to_all = use_signal(|| false);
+
+    let Some(conflict) = transfer.conflict.read().clone() else {
+        return rsx! {};
+    };
+
+    let mut resolve = move |policy: ConflictPolicy| {
+        transfer.answer(Resolution {
+            policy,
+            apply_to_all: apply_to_all(),
+        });
+        apply_to_all.set(false);
+    };
+
+    rsx! {
+        Modal {
+            onclose: move |_| resolve(ConflictPolicy::Skip),
+            class: "w-96 gap-4",
+            p { class: "text-slate-800",
+                "An item named "
+                b { "{conflict.name}" }
+                " already exists in this location."
+            }
+            if conflict.remaining > 0 {
+                label { class: "flex items-center gap-2 text-sm text-slate-600",
+                    input {
+                        r#type: "checkbox",
+                        checked: apply_to_all(),
+                        onchange: move |evt| apply_to_all.set(evt.checked()),
+                    }
+                    "Do this for any conflicts among the remaining items ({conflict.remaining})"
+                }
+            }
+            div { class: "flex justify-end gap-2",
+                Button { onclick: move |_| resolve(ConflictPolicy::Replace), "Replace" }
+                Button { onclick: move |_| resolve(ConflictPolicy::KeepBoth), "Keep Both" }
+                Button {
+                    color: "bg-slate-200 text-slate-800",
+                    onclick: move |_| resolve(ConflictPolicy::Skip),
+                    "Skip"
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::ready;
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+
+    fn now<T>(future: impl Future<Output = T>) -> T {
+        match pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("future is still pending"),
+        }
+    }
+
+    /// What running a batch did.
+    struct Batch {
+        /// Conflicts asked about, by name and entries remaining.
+        asked: Vec<(String, usize)>,
+        /// Transfers attempted, by path and policy.
+        transfers: Vec<(String, ConflictPolicy)>,
+    }
+
+    /// Runs a batch where every path conflicts, answering each question
+    /// with the next of `answers`.
+    fn run_conflicting(paths: &[&str], answers: &[Resolution]) -> Batch {
+        let paths: Vec<String> = paths.iter().map(|path| path.to_string()).collect();
+        let asked = RefCell::new(Vec::new());
+        let transfers = RefCell::new(Vec::new());
+
+        let transfer = |path: &str, policy| {
+            transfers.borrow_mut().push((path.to_string(), policy));
+            ready(match policy {
+                ConflictPolicy::Fail => Err(ApiError::Conflict(path.to_string()).into()),
+                ConflictPolicy::Skip => Ok(None),
+                _ => Ok(Some(format!("/dest/{path}"))),
+            })
+        };
+        let ask = |name: String, remaining| {
+            let mut asked = asked.borrow_mut();
+            asked.push((name, remaining));
+            ready(answers[asked.len() - 1])
+        };
+        let results = now(run_with(&paths, transfer, ask));
+        assert!(results.iter().all(Result::is_ok));
+
+        Batch {
+            asked: asked.into_inner(),
+            transfers: transfers.into_inner(),
+        }
+    }
+
+    #[test]
+    fn apply_to_all_reuses_the_first_choice_for_later_conflicts() {
+        let replace_all = Resolution {
+            policy: ConflictPolicy::Replace,
+            apply_to_all: true,
+        };
+        let Batch { asked, transfers } =
+            run_conflicting(&["/a/one", "/a/two", "/a/three"], &[replace_all]);
+
+        assert_eq!(asked, [("one".to_string(), 2)]);
+        assert_eq!(
+            transfers,
+            [
+                ("/a/one".to_string(), ConflictPolicy::Fail),
+                ("/a/one".to_string(), ConflictPolicy::Replace),
+                ("/a/two".to_string(), ConflictPolicy::Replace),
+                ("/a/three".to_string(), ConflictPolicy::Replace),
+            ]
+        );
+    }
+
+    #[test]
+    fn each_conflict_is_asked_about_without_apply_to_all() {
+        let keep_both = Resolution {
+            policy: ConflictPolicy::KeepBoth,
+            apply_to_all: false,
+        };
+        let Batch { asked, transfers } = run_conflicting(&["/a/one", "/a/two"], &[keep_both, SKIP]);
+
+        assert_eq!(asked, [("one".to_string(), 1), ("two".to_string(), 0)]);
+        assert_eq!(
+            transfers,
+            [
+                ("/a/one".to_string(), ConflictPolicy::Fail),
+                ("/a/one".to_string(), ConflictPolicy::KeepBoth),
+                ("/a/two".to_string(), ConflictPolicy::Fail),
+                ("/a/two".to_string(), ConflictPolicy::Skip),
+            ]
+        );
+    }
+}
@@ -0,0 +1,690 @@
+//! Pure geometry helpers used by `Movable` and `Resizable` for resizing and
+//! placement.
+
+/// A rectangle as its `(position, size)`.
+pub type Rect = ((f64, f64), (f64, f64));
+
+/// Parses a computed CSS length such as `"320px"`.
+///
+/// Returns `None` for keywords like `auto`/`none` and for zero lengths, which
+/// mean the content doesn't constrain that axis.
+pub fn parse_css_px(value: &str) -> Option<f64> {
+    let px = value.trim().strip_suffix("px")?.parse::<f64>().ok()?;
+    (px > 0.0).then_some(px)
+}
+
+/// Resolves the effective minimum size of a window, preferring the content's
+/// own `min-width`/`min-height` and falling back to `fallback` per axis.
+pub fn effective_min_size(
+    content_min: (Option<f64>, Option<f64>),
+    fallback: (f64, f64),
+) -> (f64, f64) {
+    (
+        content_min.0.unwrap_or(fallback.0),
+        content_min.1.unwrap_or(fallback.1),
+    )
+}
+
+/// Clamps `size` per axis between `min` and an optional `max`.
+///
+/// When `max` is smaller than `min` the minimum wins, so content can never be
+/// squeezed below its own constraints.
+pub fn clamp_size(size: (f64, f64), min: (f64, f64), max: Option<(f64, f64)>) -> (f64, f64) {
+    let (max_w, max_h) = max.unwrap_or((f64::INFINITY, f64::INFINITY));
+    (size.0.min(max_w).max(min.0), size.1.min(max_h).max(min.1))
+}
+
+/// One of the eight grips around a window's frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResizeHandle {
+    North,
+    South,
+    East,
+    West,
+    NorthEast,
+    NorthWest,
+    SouthEast,
+    SouthWest,
+}
+
+impl ResizeHandle {
+    pub const ALL: [ResizeHandle; 8] = [
+        ResizeHandle::North,
+        ResizeHandle::South,
+        ResizeHandle::East,
+        ResizeHandle::West,
+        ResizeHandle::NorthEast,
+        ResizeHandle::NorthWest,
+        ResizeHandle::SouthEast,
+        ResizeHandle::SouthWest,
+    ];
+
+    /// `-1` when the handle drags the left edge, `1` for the right edge and
+    /// `0` when it doesn't touch either.
+    pub fn horizontal(self) -> f64 {
+        match self {
+            ResizeHandle::West | ResizeHandle::NorthWest | ResizeHandle::SouthWest => -1.0,
+            ResizeHandle::East | ResizeHandle::NorthEast | ResizeHandle::SouthEast => 1.0,
+            ResizeHandle::North | ResizeHandle::South => 0.0,
+        }
+    }
+
+    /// `-1` when the handle drags the top edge, `1` for the bottom edge and
+    /// `0` when it doesn't touch either.
+    pub fn vertical(self) -> f64 {
+        match self {
+            ResizeHandle::North | ResizeHandle::NorthEast | ResizeHandle::NorthWest => -1.0,
+            ResizeHandle::South | ResizeHandle::SouthEast | ResizeHandle::SouthWest => 1.0,
+            ResizeHandle::East | ResizeHandle::West => 0.0,
+        }
+    }
+
+    /// Inline style placing the grip on the frame, with the matching cursor.
+    pub fn style(self) -> &'static str {
+        match self {
+            ResizeHandle::North => "top:-3px; left:6px; right:6px; height:6px; cursor:ns-resize;",
+            ResizeHandle::South => {
+                "bottom:-3px; left:6px; right:6px; height:6px; cursor:ns-resize;"
+            }
+            ResizeHandle::East => "right:-3px; top:6px; bottom:6px; width:6px; cursor:ew-resize;",
+            ResizeHandle::West => "left:-3px; top:6px; bottom:6px; width:6px; cursor:ew-resize;",
+            ResizeHandle::NorthEast => {
+                "top:-3px; right:-3px; width:9px; height:9px; cursor:nesw-resize;"
+            }
+            ResizeHandle::NorthWest => {
+                "top:-3px; left:-3px; width:9px; height:9px; cursor:nwse-resize;"
+            }
+            ResizeHandle::SouthEast => {
+                "bottom:-3px; right:-3px; width:9px; height:9px; cursor:nwse-resize;"
+            }
+            ResizeHandle::SouthWest => {
+                "bottom:-3px; left:-3px; width:9px; height:9px; cursor:nesw-resize;"
+            }
+        }
+    }
+}
+
+/// Corner grip closest to `point`, given relative to the top-left of a
+/// rectangle of `size`.
+pub fn nearest_corner(point: (f64, f64), size: (f64, f64)) -> ResizeHandle {
+    match (point.0 < size.0 / 2.0, point.1 < size.1 / 2.0) {
+        (true, true) => ResizeHandle::NorthWest,
+        (false, true) => ResizeHandle::NorthEast,
+        (true, false) => ResizeHandle::SouthWest,
+        (false, false) => ResizeHandle::SouthEast,
+    }
+}
+
+/// Rounds each axis of `size` to the nearest multiple of `grid`.
+pub fn snap_size(size: (f64, f64), grid: f64) -> (f64, f64) {
+    if grid <= 0.0 {
+        return size;
+    }
+    (
+        (size.0 / grid).round() * grid,
+        (size.1 / grid).round() * grid,
+    )
+}
+
+/// Snaps each axis of `size` to `base` plus a whole number of `increment`s,
+/// never going below `base` or above `max`, like X11 window size hints do
+/// for terminals snapping to whole character cells.
+pub fn snap_to_increment(
+    size: (f64, f64),
+    base: (f64, f64),
+    increment: (f64, f64),
+    max: Option<(f64, f64)>,
+) -> (f64, f64) {
+    let (max_w, max_h) = max.unwrap_or((f64::INFINITY, f64::INFINITY));
+    let axis = |size: f64, base: f64, increment: f64, max: f64| {
+        if increment <= 0.0 {
+            return size;
+        }
+        let mut steps = ((size - base) / increment).round().max(0.0);
+        if base + steps * increment > max {
+            steps = ((max - base) / increment).floor().max(0.0);
+        }
+        base + steps * increment
+    };
+    (
+        axis(size.0, base.0, increment.0, max_w),
+        axis(size.1, base.1, increment.1, max_h),
+    )
+}
+
+/// What a resize has to respect.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SizeConstraints {
+    pub min: (f64, f64),
+    pub max: Option<(f64, f64)>,
+    /// Snaps dimensions to multiples of this many pixels.
+    pub grid: Option<f64>,
+    /// Steps the size above `min` moves in (see [`snap_to_increment`]).
+    pub increment: Option<(f64, f64)>,
+    /// Keeps width divided by height at this ratio. Increments don't apply
+    /// then.
+    pub aspect_ratio: Option<f64>,
+}
+
+/// Fits `wanted` to `ratio` (width / height) within `min` and `max`.
+///
+/// Edge handles follow the dragged axis; corner handles follow whichever
+/// axis grew the box more.
+fn keep_aspect(
+    handle: ResizeHandle,
+    wanted: (f64, f64),
+    ratio: f64,
+    min: (f64, f64),
+    max: Option<(f64, f64)>,
+) -> (f64, f64) {
+    let (max_w, max_h) = max.unwrap_or((f64::INFINITY, f64::INFINITY));
+    let width = match (handle.horizontal() != 0.0, handle.vertical() != 0.0) {
+        (true, false) => wanted.0,
+        (false, _) => wanted.1 * ratio,
+        (true, true) => wanted.0.max(wanted.1 * ratio),
+    };
+    // As with `clamp_size`, the minimum wins over the maximum.
+    let width = width
+        .min(max_w.min(max_h * ratio))
+        .max(min.0.max(min.1 * ratio));
+    (width, width / ratio)
+}
+
+/// Applies a resize drag of `delta` on `handle` to the rectangle at
+/// `position` with `size`, returning the new `(position, size)`.
+///
+/// The edge opposite to the dragged one stays anchored: dragging the top edge
+/// shrinks the height and moves `top` down by the same amount, while dragging
+/// the bottom edge only changes the height. This still holds once the size is
+/// snapped and clamped to `constraints`.
+pub fn resize_rect(
+    handle: ResizeHandle,
+    position: (f64, f64),
+    size: (f64, f64),
+    delta: (f64, f64),
+    constraints: SizeConstraints,
+) -> ((f64, f64), (f64, f64)) {
+    let SizeConstraints {
+        min,
+        max,
+        grid,
+        increment,
+        aspect_ratio,
+    } = constraints;
+    let (sx, sy) = (handle.horizontal(), handle.vertical());
+    let mut wanted = (size.0 + sx * delta.0, size.1 + sy * delta.1);
+    if let Some(grid) = grid {
+        wanted = snap_size(wanted, grid);
+    }
+    let (width, height) = match (aspect_ratio.filter(|ratio| *ratio > 0.0), increment) {
+        (Some(ratio), _) => keep_aspect(handle, wanted, ratio, min, max),
+        (None, Some(increment)) => {
+            snap_to_increment(clamp_size(wanted, min, max), min, increment, max)
+        }
+        (None, None) => clamp_size(wanted, min, max),
+    };
+
+    let left = if sx < 0.0 {
+        position.0 + size.0 - width
+    } else {
+        position.0
+    };
+    let top = if sy < 0.0 {
+        position.1 + size.1 - height
+    } else {
+        position.1
+    };
+
+    ((left, top), (width, height))
+}
+
+/// How much of a content-sized window, whose size isn't known yet, must stay
+/// inside the viewport.
+const MIN_VISIBLE: (f64, f64) = (120.0, 40.0);
+
+/// Pulls a window back into a `viewport` it (partly) falls outside of, the
+/// way desktops recover windows left on a disconnected display.
+///
+/// Windows larger than the viewport are shrunk to fit it. Content-sized
+/// windows (`size` of `None`) are moved so at least their top-left corner
+/// is reachable.
+pub fn clamp_into_view(
+    position: (f64, f64),
+    size: Option<(f64, f64)>,
+    viewport: (f64, f64),
+) -> ((f64, f64), Option<(f64, f64)>) {
+    let size = size.map(|(width, height)| (width.min(viewport.0), height.min(viewport.1)));
+    let extent = size.unwrap_or(MIN_VISIBLE);
+    let position = (
+        position.0.min(viewport.0 - extent.0).max(0.0),
+        position.1.min(viewport.1 - extent.1).max(0.0),
+    );
+    (position, size)
+}
+
+/// Where a window opens when it doesn't come with a position of its own.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Placement {
+    /// Down and to the right of the previously opened window.
+    #[default]
+    Cascade,
+    /// Centered in the desktop.
+    Center,
+    /// At a fixed position.
+    Fixed((f64, f64)),
+    /// Where it overlaps the other windows least, cascading when it can't
+    /// avoid them (see [`smart_place`]).
+    Smart,
+}
+
+/// Offset between consecutive cascaded windows.
+const CASCADE_STEP: f64 = 28.0;
+/// Cascaded windows wrap back to the top-left after this many.
+const CASCADE_LENGTH: usize = 8;
+
+/// Position of a window of `size` opened with `placement` in a container of
+/// `container` size. `index` is the number of windows opened before it, used
+/// to cascade; `others` are the `(position, size)` of the windows already
+/// on the desktop.
+pub fn place(
+    placement: Placement,
+    index: usize,
+    size: (f64, f64),
+    container: (f64, f64),
+    others: &[Rect],
+) -> (f64, f64) {
+    let cascade = || {
+        let offset = 40.0 + (index % CASCADE_LENGTH) as f64 * CASCADE_STEP;
+        (offset, offset)
+    };
+    match placement {
+        Placement::Cascade => cascade(),
+        Placement::Smart => smart_place(size, container, others).unwrap_or_else(cascade),
+        Placement::Center => (
+            ((container.0 - size.0) / 2.0).max(0.0),
+            ((container.1 - size.1) / 2.0).max(0.0),
+        ),
+        Placement::Fixed(position) => position,
+    }
+}
+
+/// A preset dividing the desktop into slots windows can be snapped into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapLayout {
+    Halves,
+    Thirds,
+    Quarters,
+    /// Two by two.
+    Grid,
+}
+
+const THIRD: f64 = 1.0 / 3.0;
+
+impl SnapLayout {
+    pub const ALL: [SnapLayout; 4] = [
+        SnapLayout::Halves,
+        SnapLayout::Thirds,
+        SnapLayout::Quarters,
+        SnapLayout::Grid,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SnapLayout::Halves => "Halves",
+            SnapLayout::Thirds => "Thirds",
+            SnapLayout::Quarters => "Quarters",
+            SnapLayout::Grid => "2×2",
+        }
+    }
+
+    /// Slots as `(left, top, width, height)` fractions of the container,
+    /// left to right, then top to bottom.
+    pub fn slots(self) -> &'static [(f64, f64, f64, f64)] {
+        match self {
+            SnapLayout::Halves => &[(0.0, 0.0, 0.5, 1.0), (0.5, 0.0, 0.5, 1.0)],
+            SnapLayout::Thirds => &[
+                (0.0, 0.0, THIRD, 1.0),
+                (THIRD, 0.0, THIRD, 1.0),
+                (2.0 * THIRD, 0.0, THIRD, 1.0),
+            ],
+            SnapLayout::Quarters => &[
+                (0.0, 0.0, 0.25, 1.0),
+                (0.25, 0.0, 0.25, 1.0),
+                (0.5, 0.0, 0.25, 1.0),
+                (0.75, 0.0, 0.25, 1.0),
+            ],
+            SnapLayout::Grid => &[
+                (0.0, 0.0, 0.5, 0.5),
+                (0.5, 0.0, 0.5, 0.5),
+                (0.0, 0.5, 0.5, 0.5),
+                (0.5, 0.5, 0.5, 0.5),
+            ],
+        }
+    }
+}
+
+/// Position and size of `slot` of `layout` in a container of `container`
+/// size, rounded to whole pixels. `None` if the layout has no such slot.
+pub fn snap_slot(
+    layout: SnapLayout,
+    slot: usize,
+    container: (f64, f64),
+) -> Option<((f64, f64), (f64, f64))> {
+    let &(left, top, width, height) = layout.slots().get(slot)?;
+    // Round the edges rather than the sizes, so neighbors share an edge.
+    let x0 = (left * container.0).round();
+    let y0 = (top * container.1).round();
+    let x1 = ((left + width) * container.0).round();
+    let y1 = ((top + height) * container.1).round();
+    Some(((x0, y0), (x1 - x0, y1 - y0)))
+}
+
+/// Column layouts keyboard tiling cycles through, widest columns first.
+const TILE_CYCLE: [SnapLayout; 3] = [SnapLayout::Halves, SnapLayout::Thirds, SnapLayout::Quarters];
+
+/// Way a window is tiled from the keyboard (Super+Left / Super+Right).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TileDirection {
+    Left,
+    Right,
+}
+
+/// Column slot of [`TILE_CYCLE`] the rectangle at `position` with `size`
+/// exactly fills in a container of `container` size, if any.
+pub fn column_tile(
+    position: (f64, f64),
+    size: (f64, f64),
+    container: (f64, f64),
+) -> Option<(SnapLayout, usize)> {
+    TILE_CYCLE.into_iter().find_map(|layout| {
+        (0..layout.slots().len())
+            .find(|&slot| snap_slot(layout, slot, container) == Some((position, size)))
+            .map(|slot| (layout, slot))
+    })
+}
+
+/// Column slot a window tiled into `current` moves to when tiled towards
+/// `direction`.
+///
+/// A window that isn't tiled goes to the half on that side. Within a layout
+/// it steps one column over; from the outermost column it narrows to the
+/// next layout's outermost column (half, third, quarter), wrapping back to
+/// the half after the quarter.
+pub fn next_tile(
+    current: Option<(SnapLayout, usize)>,
+    direction: TileDirection,
+) -> (SnapLayout, usize) {
+    let edge = |layout: SnapLayout| match direction {
+        TileDirection::Left => 0,
+        TileDirection::Right => layout.slots().len() - 1,
+    };
+    let Some((layout, slot)) = current else {
+        return (SnapLayout::Halves, edge(SnapLayout::Halves));
+    };
+    if slot != edge(layout) {
+        return match direction {
+            TileDirection::Left => (layout, slot - 1),
+            TileDirection::Right => (layout, slot + 1),
+        };
+    }
+    let index = TILE_CYCLE.iter().position(|&other| other == layout);
+    let next = index.map_or(TILE_CYCLE[0], |index| {
+        TILE_CYCLE[(index + 1) % TILE_CYCLE.len()]
+    });
+    (next, edge(next))
+}
+
+/// Area where the rectangles `a` and `b`, given as `(position, size)`,
+/// overlap.
+fn overlap_area(a: Rect, b: Rect) -> f64 {
+    let width = (a.0 .0 + a.1 .0).min(b.0 .0 + b.1 .0) - a.0 .0.max(b.0 .0);
+    let height = (a.0 .1 + a.1 .1).min(b.0 .1 + b.1 .1) - a.0 .1.max(b.0 .1);
+    width.max(0.0) * height.max(0.0)
+}
+
+/// Top-most, then left-most position where a window of `size` fits in
+/// `container` without overlapping any of `others`, or `None` if there is
+/// no such place.
+///
+/// A free spot, if there is one, can always be slid up and left until it
+/// touches the container or another window, so only positions on the
+/// container's edges and the right and bottom edges of `others` are tried.
+pub fn smart_place(size: (f64, f64), container: (f64, f64), others: &[Rect]) -> Option<(f64, f64)> {
+    let candidates = |origin: fn((f64, f64)) -> f64, extent: fn((f64, f64)) -> f64| {
+        let mut edges: Vec<f64> = std::iter::once(0.0)
+            .chain(
+                others
+                    .iter()
+                    .map(|&(position, other)| origin(position) + extent(other)),
+            )
+            .filter(|&edge| edge + extent(size) <= extent(container))
+            .collect();
+        edges.sort_by(f64::total_cmp);
+        edges.dedup();
+        edges
+    };
+    let xs = candidates(|point| point.0, |size| size.0);
+    let ys = candidates(|point| point.1, |size| size.1);
+
+    ys.iter()
+        .flat_map(|&y| xs.iter().map(move |&x| (x, y)))
+        .find(|&position| {
+            others
+                .iter()
+                .all(|&other| overlap_area((position, size), other) <= 0.0)
+        })
+}
+
+/// How close, in pixels, two window centers must be for [`center_snap`] to
+/// line them up.
+pub const CENTER_SNAP_THRESHOLD: f64 = 8.0;
+
+/// Alignment guides to draw while a window's center is snapped to another's.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CenterGuides {
+    /// X coordinate of a vertical guide through both centers.
+    pub vertical: Option<f64>,
+    /// Y coordinate of a horizontal guide through both centers.
+    pub horizontal: Option<f64>,
+}
+
+/// Soft-snaps the rectangle at `position` with `size` so its center lines up
+/// with the center of one of `others` (given as `(position, size)`), on each
+/// axis where the nearest center is within `threshold`.
+///
+/// Returns the snapped position and the guides to show for it.
+pub fn center_snap(
+    position: (f64, f64),
+    size: (f64, f64),
+    others: &[Rect],
+    threshold: f64,
+) -> ((f64, f64), CenterGuides) {
+    let center = (position.0 + size.0 / 2.0, position.1 + size.1 / 2.0);
+    let nearest = |axis: fn((f64, f64)) -> f64| {
+        others
+            .iter()
+            .map(|&(other_position, other_size)| axis(other_position) + axis(other_size) / 2.0)
+            .filter(|other| (other - axis(center)).abs() <= threshold)
+            .min_by(|a, b| {
+                (a - axis(center))
+                    .abs()
+                    .total_cmp(&(b - axis(center)).abs())
+            })
+    };
+
+    let guides = CenterGuides {
+        vertical: nearest(|point| point.0),
+        horizontal: nearest(|point| point.1),
+    };
+    let position = (
+        guides.vertical.map_or(position.0, |x| x - size.0 / 2.0),
+        guides.horizontal.map_or(position.1, |y| y - size.1 / 2.0),
+    );
+    (position, guides)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_minimums_override_the_fallback_per_axis() {
+        assert_eq!(
+            effective_min_size((Some(320.0), None), (200.0, 120.0)),
+            (320.0, 120.0)
+        );
+        assert_eq!(parse_css_px("240px"), Some(240.0));
+        assert_eq!(parse_css_px("0px"), None);
+        assert_eq!(parse_css_px("auto"), None);
+    }
+
+    #[test]
+    fn sizes_clamp_between_min_and_max() {
+        let (min, max) = ((200.0, 100.0), Some((400.0, 300.0)));
+        assert_eq!(clamp_size((100.0, 500.0), min, max), (200.0, 300.0));
+        assert_eq!(clamp_size((100.0, 500.0), min, None), (200.0, 500.0));
+        // A maximum below the minimum loses.
+        assert_eq!(
+            clamp_size((50.0, 50.0), min, Some((150.0, 80.0))),
+            (200.0, 100.0)
+        );
+    }
+
+    #[test]
+    fn resizing_stops_at_the_minimum_size() {
+        let constraints = SizeConstraints {
+            min: (200.0, 100.0),
+            max: Some((600.0, 400.0)),
+            ..Default::default()
+        };
+        let shrunk = resize_rect(
+            ResizeHandle::SouthEast,
+            (10.0, 10.0),
+            (300.0, 200.0),
+            (-500.0, -500.0),
+            constraints,
+        );
+        assert_eq!(shrunk, ((10.0, 10.0), (200.0, 100.0)));
+        let grown = resize_rect(
+            ResizeHandle::SouthEast,
+            (10.0, 10.0),
+            (300.0, 200.0),
+            (500.0, 500.0),
+            constraints,
+        );
+        assert_eq!(grown, ((10.0, 10.0), (600.0, 400.0)));
+    }
+
+    #[test]
+    fn every_handle_keeps_the_opposite_edges_in_place() {
+        let constraints = SizeConstraints {
+            min: (100.0, 80.0),
+            ..Default::default()
+        };
+        let (position, size) = ((50.0, 40.0), (300.0, 200.0));
+        let edges = |((left, top), (width, height)): Rect| (left, top, left + width, top + height);
+        let (left, top, right, bottom) = edges((position, size));
+        for handle in ResizeHandle::ALL {
+            // Far enough to hit the minimum size when shrinking.
+            for delta in [
+                (40.0, 30.0),
+                (-40.0, -30.0),
+                (-500.0, -500.0),
+                (500.0, 500.0),
+            ] {
+                let resized = resize_rect(handle, position, size, delta, constraints);
+                let (new_left, new_top, new_right, new_bottom) = edges(resized);
+                match handle.horizontal() {
+                    h if h < 0.0 => assert_eq!(new_right, right, "{handle:?} {delta:?}"),
+                    h if h > 0.0 => assert_eq!(new_left, left, "{handle:?} {delta:?}"),
+                    _ => assert_eq!((new_left, new_right), (left, right), "{handle:?}"),
+                }
+                match handle.vertical() {
+                    v if v < 0.0 => assert_eq!(new_bottom, bottom, "{handle:?} {delta:?}"),
+                    v if v > 0.0 => assert_eq!(new_top, top, "{handle:?} {delta:?}"),
+                    _ => assert_eq!((new_top, new_bottom), (top, bottom), "{handle:?}"),
+                }
+                assert!(
+                    resized.1 .0 >= 100.0 && resized.1 .1 >= 80.0,
+                    "{handle:?} {delta:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn dragging_the_top_left_corner_moves_the_origin() {
+        let resized = resize_rect(
+            ResizeHandle::NorthWest,
+            (50.0, 40.0),
+            (300.0, 200.0),
+            (20.0, -10.0),
+            SizeConstraints::default(),
+        );
+        assert_eq!(resized, ((70.0, 30.0), (280.0, 210.0)));
+    }
+
+    #[test]
+    fn smart_placement_takes_the_first_free_spot() {
+        let container = (1000.0, 800.0);
+        assert_eq!(
+            smart_place((300.0, 200.0), container, &[]),
+            Some((0.0, 0.0))
+        );
+
+        let others = [((0.0, 0.0), (400.0, 300.0)), ((400.0, 0.0), (400.0, 100.0))];
+        // Right of both windows on the top row.
+        assert_eq!(
+            smart_place((200.0, 200.0), container, &others),
+            Some((800.0, 0.0))
+        );
+        // Too wide for the top row, so below the shorter window.
+        assert_eq!(
+            smart_place((500.0, 200.0), container, &others),
+            Some((400.0, 100.0))
+        );
+        // Too wide for anything but the row below both.
+        assert_eq!(
+            smart_place((700.0, 200.0), container, &others),
+            Some((0.0, 300.0))
+        );
+        assert_eq!(smart_place((700.0, 600.0), container, &others), None);
+    }
+
+    #[test]
+    fn placements_fall_back_to_cascading() {
+        let container = (1000.0, 800.0);
+        let full = [((0.0, 0.0), container)];
+        assert_eq!(
+            place(Placement::Smart, 2, (300.0, 200.0), container, &full),
+            (96.0, 96.0)
+        );
+        assert_eq!(
+            place(Placement::Smart, 2, (300.0, 200.0), container, &[]),
+            (0.0, 0.0)
+        );
+        assert_eq!(
+            place(Placement::Cascade, 9, (300.0, 200.0), container, &[]),
+            (68.0, 68.0)
+        );
+        assert_eq!(
+            place(Placement::Center, 0, (300.0, 200.0), container, &[]),
+            (350.0, 300.0)
+        );
+        // Windows wider than the container keep to its left edge.
+        assert_eq!(
+            place(Placement::Center, 0, (1200.0, 200.0), container, &[]),
+            (0.0, 300.0)
+        );
+        assert_eq!(
+            place(
+                Placement::Fixed((24.0, 24.0)),
+                0,
+                (300.0, 200.0),
+                container,
+                &full
+            ),
+            (24.0, 24.0)
+        );
+    }
+}
@@ -0,0 +1,233 @@
+//! Reactive hooks over browser APIs shared by the desktop components.
+
+use crate::browser::{self, next_frame, MediaListener};
+use dioxus::core::Task;
+use dioxus::prelude::*;
+use std::future::Future;
+use std::rc::Rc;
+
+/// Tracks whether the media `query` currently matches, updating as it flips.
+pub fn use_media_query(query: &str) -> Signal<bool> {
+    let query = query.to_string();
+    let mut matches = use_signal(|| browser::media_matches(&query));
+    use_hook(move || MediaListener::attach(&query, move |now| matches.set(now)).map(Rc::new));
+
+    matches
+}
+
+/// Whether the user asked the system to minimize non-essential motion.
+///
+/// Animated components check this and fall back to instant state changes.
+pub fn use_reduced_motion() -> Signal<bool> {
+    use_media_query("(prefers-reduced-motion: reduce)")
+}
+
+/// Whether the system color scheme is dark.
+pub fn use_dark_mode() -> Signal<bool> {
+    use_media_query("(prefers-color-scheme: dark)")
+}
+
+/// A signal following `source`, updated only once `source` has stopped
+/// changing for `ms` milliseconds.
+pub fn use_debounce<T: Clone + 'static>(source: Signal<T>, ms: u32) -> Signal<T> {
+    use_debounce_with(source, move || browser::sleep(ms))
+}
+
+/// [`use_debounce`] waiting out the quiet period with `wait`, so tests can
+/// stand in for the browser timer.
+fn use_debounce_with<T: Clone + 'static, F: Future<Output = ()> + 'static>(
+    source: Signal<T>,
+    wait: impl Fn() -> F + 'static,
+) -> Signal<T> {
+    let mut output = use_signal(|| source.peek().clone());
+    let mut pending = use_signal(|| Option::<Task>::None);
+    let wait = use_hook(|| Rc::new(wait));
+
+    use_effect(move || {
+        let value = source();
+        if let Some(task) = pending.write().take() {
+            task.cancel();
+        }
+        let quiet = (*wait)();
+        pending.set(Some(spawn(async move {
+            quiet.await;
+            output.set(value);
+        })));
+    });
+
+    use_drop(move || {
+        if let Some(task) = pending.write().take() {
+            task.cancel();
+        }
+    });
+
+    output
+}
+
+/// A signal following `source`, updated at most once every `ms`
+/// milliseconds. The latest value is always delivered once the interval
+/// has passed.
+pub fn use_throttle<T: Clone + 'static>(source: Signal<T>, ms: u32) -> Signal<T> {
+    let mut output = use_signal(|| source.peek().clone());
+    let mut last_emit = use_signal(|| Option::<f64>::None);
+    let mut trailing = use_signal(|| Option::<Task>::None);
+
+    use_effect(move || {
+        let value = source();
+        if let Some(task) = trailing.write().take() {
+            task.cancel();
+        }
+
+        let now = browser::now();
+        let elapsed = last_emit.peek().map_or(f64::INFINITY, |last| now - last);
+        if elapsed >= ms as f64 {
+            last_emit.set(Some(now));
+            output.set(value);
+            return;
+        }
+
+        let wait = (ms as f64 - elapsed).ceil() as u32;
+        trailing.set(Some(spawn(async move {
+            browser::sleep(wait).await;
+            last_emit.set(Some(browser::now()));
+            output.set(value);
+        })));
+    });
+
+    use_drop(move || {
+        if let Some(task) = trailing.write().take() {
+            task.cancel();
+        }
+    });
+
+    output
+}
+
+/// Coalesces values pushed between two animation frames, applying only the
+/// latest of them once per frame. Made by [`use_frame_throttle`].
+pub struct FrameThrottle<T: 'static> {
+    pending: Signal<Option<T>>,
+    task: Signal<Option<Task>>,
+    apply: Callback<T>,
+}
+
+impl<T: 'static> Clone for FrameThrottle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: 'static> Copy for FrameThrottle<T> {}
+
+impl<T: 'static> FrameThrottle<T> {
+    /// Queues `value` for the next frame, replacing whatever was queued
+    /// since the last one.
+    pub fn push(&mut self, value: T) {
+        self.pending.set(Some(value));
+        if self.task.peek().is_some() {
+            return;
+        }
+        let (mut pending, mut task, apply) = (self.pending, self.task, self.apply);
+        self.task.set(Some(spawn(async move {
+            next_frame().await;
+            task.set(None);
+            if let Some(value) = pending.write().take() {
+                apply.call(value);
+            }
+        })));
+    }
+
+    /// Applies the queued value right away instead of on the next frame,
+    /// e.g. to commit the exact final value when a gesture ends.
+    pub fn flush(&mut self) {
+        if let Some(task) = self.task.write().take() {
+            task.cancel();
+        }
+        if let Some(value) = self.pending.write().take() {
+            self.apply.call(value);
+        }
+    }
+
+    /// Drops the queued value without applying it.
+    pub fn cancel(&mut self) {
+        if let Some(task) = self.task.write().take() {
+            task.cancel();
+        }
+        self.pending.set(None);
+    }
+}
+
+/// Throttles `apply` to at most once per animation frame, for input that
+/// arrives faster than the screen refreshes (pointer moves while dragging
+/// or resizing) and is expensive to lay out.
+pub fn use_frame_throttle<T: 'static>(apply: impl FnMut(T) + 'static) -> FrameThrottle<T> {
+    let apply = use_callback(apply);
+    let pending = use_signal(|| None);
+    let mut task = use_signal(|| Option::<Task>::None);
+
+    use_drop(move || {
+        if let Some(task) = task.write().take() {
+            task.cancel();
+        }
+    });
+
+    FrameThrottle {
+        pending,
+        task,
+        apply,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_channel::oneshot;
+    use std::cell::RefCell;
+
+    thread_local! {
+        /// Timers started by the debounce, fired by the test.
+        static TIMERS: RefCell<Vec<oneshot::Sender<()>>> = const { RefCell::new(Vec::new()) };
+        /// Every value the debounced signal took.
+        static EMITTED: RefCell<Vec<u32>> = const { RefCell::new(Vec::new()) };
+    }
+
+    #[component]
+    fn Debounced() -> Element {
+        let source = use_context_provider(|| Signal::new(0u32));
+        let debounced = use_debounce_with(source, || {
+            let (tx, rx) = oneshot::channel();
+            TIMERS.with(|timers| timers.borrow_mut().push(tx));
+            async move {
+                let _ = rx.await;
+            }
+        });
+        use_effect(move || {
+            let value = debounced();
+            EMITTED.with(|emitted| emitted.borrow_mut().push(value));
+        });
+        rsx! {}
+    }
+
+    #[test]
+    fn rapid_changes_emit_once() {
+        let mut dom = VirtualDom::new(Debounced);
+        dom.rebuild_in_place();
+        dom.render_immediate_to_vec();
+        let mut source: Signal<u32> = dom.in_scope(ScopeId::APP, consume_context);
+
+        for value in 1..=3 {
+            dom.in_scope(ScopeId::APP, || source.set(value));
+            dom.render_immediate_to_vec();
+        }
+        // Nothing is emitted while the source keeps changing.
+        assert_eq!(EMITTED.with(|emitted| emitted.borrow().clone()), [0]);
+
+        // Each change restarted the timer; only the last one is still
+        // waited on.
+        for timer in TIMERS.with(|timers| timers.take()) {
+            let _ = timer.send(());
+        }
+        dom.render_immediate_to_vec();
+        assert_eq!(EMITTED.with(|emitted| emitted.borrow().clone()), [0, 3]);
+    }
+}
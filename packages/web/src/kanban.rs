@@ -0,0 +1,300 @@
+//! A kanban board whose cards can be moved between columns by dragging them
+//! or, for keyboard users, by picking them up with Space and moving them
+//! with the arrow keys.
+//!
+//! Both ways end up in [`Board::move_card`], so they reorder cards exactly
+//! the same way.
+
+use crate::dnd::{Draggable, DropZone};
+use dioxus::prelude::*;
+
+pub type CardId = usize;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct KanbanCard {
+    pub id: CardId,
+    pub text: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct KanbanColumn {
+    pub title: String,
+    pub cards: Vec<KanbanCard>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Board {
+    pub columns: Vec<KanbanColumn>,
+}
+
+impl Board {
+    /// Column and index of card `id`.
+    pub fn position(&self, id: CardId) -> Option<(usize, usize)> {
+        self.columns.iter().enumerate().find_map(|(column, cards)| {
+            let index = cards.cards.iter().position(|card| card.id == id)?;
+            Some((column, index))
+        })
+    }
+
+    /// Moves card `id` to `index` in `column`, clamping `index` to the
+    /// column's length once the card has been taken out of its old place.
+    pub fn move_card(&mut self, id: CardId, column: usize, index: usize) {
+        let Some((from_column, from_index)) = self.position(id) else {
+            return;
+        };
+        if column >= self.columns.len() {
+            return;
+        }
+        let card = self.columns[from_column].cards.remove(from_index);
+        let cards = &mut self.columns[column].cards;
+        cards.insert(index.min(cards.len()), card);
+    }
+
+    /// Where card `id` ends up when moved one step in `direction`: up or
+    /// down within its column, or to the same row of the neighboring column.
+    pub fn step(&self, id: CardId, direction: Direction) -> Option<(usize, usize)> {
+        let (column, index) = self.position(id)?;
+        let target = match direction {
+            Direction::Up => (column, index.checked_sub(1)?),
+            Direction::Down if index + 1 < self.columns[column].cards.len() => (column, index + 1),
+            Direction::Down => return None,
+            Direction::Left => (column.checked_sub(1)?, index),
+            Direction::Right if column + 1 < self.columns.len() => (column + 1, index),
+            Direction::Right => return None,
+        };
+        let len = self.columns[target.0].cards.len();
+        // In another column the card is an extra entry, so it may go last.
+        let max = if target.0 == column { len - 1 } else { len };
+        Some((target.0, target.1.min(max)))
+    }
+
+    /// Description of where card `id` is, for screen reader announcements.
+    fn describe(&self, id: CardId) -> String {
+        match self.position(id) {
+            Some((column, index)) => format!(
+                "{}, position {} of {}",
+                self.columns[column].title,
+                index + 1,
+                self.columns[column].cards.len()
+            ),
+            None => String::new(),
+        }
+    }
+}
+
+/// The card picked up with the keyboard, and where it was picked up from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Grab {
+    id: CardId,
+    origin: (usize, usize),
+}
+
+#[component]
+pub fn Kanban(board: Signal<Board>) -> Element {
+    let mut grabbed = use_signal(|| None::<Grab>);
+    let mut announcement = use_signal(String::new);
+
+    let mut onkeydown = move |id: CardId, text: String, evt: Event<KeyboardData>| {
+        let direction = match evt.key() {
+            Key::ArrowUp => Some(Direction::Up),
+            Key::ArrowDown => Some(Direction::Down),
+            Key::ArrowLeft => Some(Direction::Left),
+            Key::ArrowRight => Some(Direction::Right),
+            _ => None,
+        };
+        let grab = grabbed().filter(|grab| grab.id == id);
+
+        match (evt.key(), grab, direction) {
+            (Key::Character(key), None, _) if key == " " => {
+                let Some(origin) = board.peek().position(id) else {
+                    return;
+                };
+                grabbed.set(Some(Grab { id, origin }));
+                announcement.set(format!(
+                    "Picked up {text}. Use the arrow keys to move it, Space to drop it, Escape to cancel."
+                ));
+            }
+            (Key::Character(key), Some(_), _) if key == " " => {
+                grabbed.set(None);
+                announcement.set(format!("Dropped {text} in {}.", board.peek().describe(id)));
+            }
+            (Key::Escape, Some(grab), _) => {
+                board.write().move_card(id, grab.origin.0, grab.origin.1);
+                grabbed.set(None);
+                announcement.set(format!(
+                    "Cancelled. {text} is back in {}.",
+                    board.peek().describe(id)
+                ));
+            }
+            (_, Some(_), Some(direction)) => {
+                let target = board.peek().step(id, direction);
+                if let Some((column, index)) = target {
+                    board.write().move_card(id, column, index);
+                }
+                announcement.set(format!("{text}: {}.", board.peek().describe(id)));
+            }
+            _ => return,
+        }
+        evt.prevent_default();
+        evt.stop_propagation();
+    };
+
+    let columns = board.read().columns.clone();
+
+    rsx! {
+        div { class: "flex h-full gap-3 overflow-x-auto bg-slate-50 p-3",
+            for (column_index, column) in columns.into_iter().enumerate() {
+                DropZone::<CardId> {
+                    key: "{column_index}",
+                    class: "flex w-56 shrink-0 flex-col gap-2 rounded-lg bg-slate-200 p-2",
+                    onaccept: move |id| {
+                        let len = board.peek().columns[column_index].cards.len();
+                        board.write().move_card(id, column_index, len);
+                    },
+                    h3 { class: "px-1 text-sm font-semibold text-slate-700", "{column.title}" }
+                    ul { class: "flex flex-col gap-2", role: "list", aria_label: "{column.title}",
+                        for (index, card) in column.cards.into_iter().enumerate() {
+                            li { key: "{card.id}",
+                                DropZone::<CardId> {
+                                    onaccept: move |id| board.write().move_card(id, column_index, index),
+                                    Draggable::<CardId> { payload: card.id,
+                                        div {
+                                            class: if grabbed().is_some_and(|grab| grab.id == card.id) {
+                                                "rounded-md bg-white p-2 text-sm shadow-lg ring-2 ring-sky-500 outline-none"
+                                            } else {
+                                                "rounded-md bg-white p-2 text-sm shadow-sm outline-none focus:ring-2 focus:ring-sky-300"
+                                            },
+                                            tabindex: 0,
+                                            aria_roledescription: "draggable card",
+                                            aria_pressed: grabbed().is_some_and(|grab| grab.id == card.id),
+                                            // Moving to another column re-creates the card, so
+                                            // focus is handed back to it.
+                                            onmounted: move |evt| async move {
+                                                if grabbed.peek().is_some_and(|grab| grab.id == card.id) {
+                                                    let _ = evt.data().set_focus(true).await;
+                                                }
+                                            },
+                                            onkeydown: {
+                                                let text = card.text.clone();
+                                                move |evt| onkeydown(card.id, text.clone(), evt)
+                                            },
+                                            "{card.text}"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            div { class: "sr-only", role: "status", aria_live: "assertive", "{announcement}" }
+        }
+    }
+}
+
+/// Board shown by the `kanban` app until it has its own storage.
+pub fn sample_board() -> Board {
+    let column = |title: &str, cards: &[(CardId, &str)]| KanbanColumn {
+        title: title.to_string(),
+        cards: cards
+            .iter()
+            .map(|&(id, text)| KanbanCard {
+                id,
+                text: text.to_string(),
+            })
+            .collect(),
+    };
+    Board {
+        columns: vec![
+            column("To do", &[(0, "Write docs"), (1, "Fix window snapping")]),
+            column("Doing", &[(2, "Keyboard drag and drop")]),
+            column("Done", &[(3, "Workspaces")]),
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The card ids of each column of `board`.
+    fn ids(board: &Board) -> Vec<Vec<CardId>> {
+        board
+            .columns
+            .iter()
+            .map(|column| column.cards.iter().map(|card| card.id).collect())
+            .collect()
+    }
+
+    #[test]
+    fn cards_move_between_columns() {
+        let mut board = sample_board();
+        board.move_card(0, 1, 0);
+        assert_eq!(ids(&board), [vec![1], vec![0, 2], vec![3]]);
+
+        // Past the end of the column means last.
+        board.move_card(1, 2, 10);
+        assert_eq!(ids(&board), [vec![], vec![0, 2], vec![3, 1]]);
+    }
+
+    #[test]
+    fn cards_reorder_within_their_column() {
+        let mut board = sample_board();
+        board.move_card(0, 0, 1);
+        assert_eq!(ids(&board), [vec![1, 0], vec![2], vec![3]]);
+        board.move_card(0, 0, 0);
+        assert_eq!(ids(&board), [vec![0, 1], vec![2], vec![3]]);
+    }
+
+    #[test]
+    fn moves_of_unknown_cards_or_to_unknown_columns_are_ignored() {
+        let mut board = sample_board();
+        board.move_card(42, 0, 0);
+        board.move_card(0, 3, 0);
+        assert_eq!(board, sample_board());
+    }
+
+    #[test]
+    fn arrow_keys_step_within_the_board() {
+        let board = sample_board();
+        assert_eq!(board.step(1, Direction::Up), Some((0, 0)));
+        assert_eq!(board.step(0, Direction::Down), Some((0, 1)));
+        assert_eq!(board.step(0, Direction::Up), None);
+        assert_eq!(board.step(1, Direction::Down), None);
+        assert_eq!(board.step(0, Direction::Left), None);
+        assert_eq!(board.step(3, Direction::Right), None);
+        // The same row of the next column, or its end when that's shorter.
+        assert_eq!(board.step(0, Direction::Right), Some((1, 0)));
+        assert_eq!(board.step(1, Direction::Right), Some((1, 1)));
+        assert_eq!(board.step(42, Direction::Up), None);
+    }
+
+    #[test]
+    fn cancelling_a_keyboard_move_puts_the_card_back() {
+        let mut board = sample_board();
+        let origin = board.position(1).unwrap();
+        for direction in [Direction::Right, Direction::Right, Direction::Up] {
+            let (column, index) = board.step(1, direction).unwrap();
+            board.move_card(1, column, index);
+        }
+        assert_eq!(board.position(1), Some((2, 0)));
+
+        board.move_card(1, origin.0, origin.1);
+        assert_eq!(board, sample_board());
+    }
+
+    #[test]
+    fn positions_are_described_for_announcements() {
+        let board = sample_board();
+        assert_eq!(board.describe(1), "To do, position 2 of 2");
+        assert_eq!(board.describe(42), "");
+    }
+}
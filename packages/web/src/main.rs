@@ -1,16 +1,105 @@
-//! This example shows how to implement a simple drag-and-drop kanban board using Dioxus.
-//! You can drag items between different categories and edit their contents.
+//! The JadeOS desktop shell: a windowed desktop running in the browser,
+//! with its own window manager, taskbar and workspaces, and apps (a file
+//! manager, notes, a kanban board, appearance settings) talking to the
+//! server through the `api` crate.
 //!
-//! This example uses the `.data_transfer()` API to handle drag-and-drop events. When an item is dragged,
-//! its ID is stored in the data transfer object. When the item is dropped into a new category, its ID is retrieved
-//! from the data transfer object and used to update the item's category.
-//!
-//! Note that in a real-world application, you'll want more sophisticated drop handling, such as visual
-//! feedback during dragging, and better drop-zone detection to allow dropping *between* items.
+//! The open windows are saved with the session and restored on the next
+//! load. Building with the `storybook` feature adds a `components` app
+//! previewing the component library; keep it off in production builds.
 
+use dioxus::core::Task;
 use dioxus::prelude::*;
-use dioxus_web::WebEventExt;
 use std::rc::Rc;
+use ui::{Card, CardBody, Spinner, ToastProvider};
+
+mod browser;
+
+mod geometry;
+use geometry::{
+    center_snap, clamp_into_view, effective_min_size, nearest_corner, parse_css_px, place,
+    resize_rect, CenterGuides, Placement, ResizeHandle, SizeConstraints, CENTER_SNAP_THRESHOLD,
+};
+
+mod animated_number;
+
+mod code_block;
+
+mod collapsible;
+
+mod console;
+use console::{ConsolePanel, LogViewer};
+
+mod deep_link;
+use deep_link::use_deep_link;
+
+mod decoration;
+use decoration::{use_window_decoration, DecorationPicker, TitleBar, WindowDecoration};
+
+mod details;
+
+mod dnd;
+use dnd::DragContext;
+
+mod event_bus;
+use event_bus::EventBus;
+
+mod file_manager;
+use file_manager::FileManager;
+
+mod file_ops;
+use file_ops::{ConflictDialog, FileTransfer};
+
+mod hooks;
+use hooks::{use_frame_throttle, use_reduced_motion};
+
+mod kanban;
+use kanban::{sample_board, Kanban};
+
+mod notes;
+use notes::{NoteBuffers, Notes};
+
+mod paste;
+
+mod pointer;
+use pointer::{DocumentEscapeListener, DocumentPointerListeners, PointerGesture};
+
+mod resizable;
+use resizable::ResizeGrips;
+
+mod server_fn;
+
+mod shortcuts;
+use shortcuts::{use_window_shortcut, CurrentWindow, ShortcutProvider};
+
+mod session;
+use session::use_session;
+
+mod snap;
+use snap::{SnapAssistOverlay, TilingShortcuts};
+
+#[cfg(feature = "storybook")]
+mod storybook;
+
+mod taskbar;
+use taskbar::Taskbar;
+
+mod theme;
+use theme::{use_theme_provider, ThemeEditor};
+
+mod typography;
+use typography::Typography;
+
+mod window_manager;
+use window_manager::{
+    use_window_manager, workspace_at, HoverFocus, ShowDesktopButton, WindowHandle, WindowId,
+    WindowManager, WindowState, WindowSwitcher, WorkspaceSwitcher,
+};
+
+/// Apps the desktop knows how to render, by app id.
+#[cfg(not(feature = "storybook"))]
+const APPS: &[&str] = &["card", "files", "kanban", "notes", "theme"];
+#[cfg(feature = "storybook")]
+const APPS: &[&str] = &["card", "files", "kanban", "notes", "theme", "components"];
 
 const TAILWIND_CSS: Asset = asset!("/assets/tailwind.css");
 
@@ -19,243 +108,842 @@ fn main() {
 }
 
 fn app() -> Element {
+    let mut manager = use_context_provider(|| Signal::new(WindowManager::default()));
+    use_context_provider(DragContext::new);
+    use_context_provider(EventBus::new);
+    use_context_provider(FileTransfer::new);
+    use_context_provider(NoteBuffers::new);
+    use_context_provider(|| Signal::new(WindowDecoration::default()));
+    let tokens = use_theme_provider();
+
+    let restored = use_session(APPS);
+    use_hook(move || {
+        if restored {
+            return;
+        }
+        let mut manager = manager.write();
+        for i in 0..5 {
+            manager.launch("card", format!("Window {}", i + 1));
+        }
+        manager.launch("files", "Files");
+        manager.launch("kanban", "Board");
+        manager.launch("notes", "Notes");
+        manager.launch("theme", "Appearance");
+        #[cfg(feature = "storybook")]
+        manager.launch("components", "Components");
+    });
+    use_deep_link(APPS);
+
+    let onkeydown = move |evt: Event<KeyboardData>| {
+        match evt.key() {
+            Key::Tab if evt.modifiers().alt() => {
+                evt.prevent_default();
+                if evt.modifiers().shift() {
+                    manager.write().prev();
+                } else {
+                    manager.write().next();
+                }
+            }
+            Key::Escape if manager.read().switching() => manager.write().cancel_switch(),
+            _ => {}
+        }
+    };
+
+    let onkeyup = move |evt: Event<KeyboardData>| {
+        if evt.key() == Key::Alt && manager.read().switching() {
+            manager.write().commit_switch();
+        }
+    };
+
     rsx! {
         document::Link { rel: "stylesheet", href: TAILWIND_CSS }
-        for _ in 0..5 {
-            Movable { 
-                Card {
-                    color: "white",
-                    shadow: "shadow-sm",
-                    rounded: "rounded-lg",
-                    CardBody {
-                        size: "h-100 w-200",
-                        Typography {
-                            text: "card.title",
-                            size: "text-xl",
-                            color: "text-slate-800",
-                            position: "text-left",
-                            class: "my-2 font-semibold",
-                        }
-                        Typography {
-                            text: "card.text",
-                            size: "text-base",
-                            color: "text-slate-600",
-                            position: "text-left",
-                            class: "leading-normal",
+        ToastProvider {
+            ShortcutProvider {
+                div {
+                    class: "fixed inset-0 outline-none",
+                    "data-desktop": true,
+                    style: tokens.read().css_vars(),
+                    tabindex: 0,
+                    onkeydown: onkeydown,
+                    onkeyup: onkeyup,
+                    for handle in manager.read().windows().to_vec() {
+                        Movable {
+                            key: "{handle.id}",
+                            handle: handle.clone(),
+                            initial_placement: app_placement(handle.app_id.as_deref()),
+                            {app_content(handle.app_id.as_deref())}
                         }
                     }
+                    ConsolePanel { LogViewer {} }
+                    Taskbar {}
+                    WorkspaceSwitcher {}
+                    ShowDesktopButton {}
+                    DecorationPicker {}
+                    WindowSwitcher {}
+                    SnapAssistOverlay {}
+                    TilingShortcuts {}
+                    ConflictDialog {}
                 }
             }
         }
     }
 }
+
+/// Where a new window running `app_id` opens. The board is large, so it
+/// takes the top-left corner; the appearance settings open in front of the
+/// user like a dialog.
+fn app_placement(app_id: Option<&str>) -> Placement {
+    match app_id {
+        Some("files" | "notes") => Placement::Smart,
+        Some("kanban") => Placement::Fixed((24.0, 24.0)),
+        Some("theme") => Placement::Center,
+        _ => Placement::Cascade,
+    }
+}
+
+/// The content of a window running `app_id`; windows without a known app
+/// show a sample card.
+fn app_content(app_id: Option<&str>) -> Element {
+    match app_id {
+        Some("files") => rsx! { FileManager {} },
+        Some("kanban") => rsx! { KanbanApp {} },
+        Some("notes") => rsx! { Notes {} },
+        Some("theme") => rsx! { ThemeEditor {} },
+        #[cfg(feature = "storybook")]
+        Some("components") => rsx! { storybook::Storybook {} },
+        _ => rsx! {
+            Card {
+                color: "white",
+                shadow: "shadow-sm",
+                rounded: "rounded-lg",
+                CardBody {
+                    size: "h-100 w-200",
+                    Typography {
+                        text: "card.title",
+                        size: "text-xl",
+                        color: "text-slate-800",
+                        position: "text-left",
+                        class: "my-2 font-semibold",
+                    }
+                    Typography {
+                        text: "card.text",
+                        size: "text-base",
+                        color: "text-slate-600",
+                        position: "text-left",
+                        class: "leading-normal",
+                    }
+                }
+            }
+        },
+    }
+}
+
 use dioxus::html::input_data::MouseButton;
 
 #[derive(Props, PartialEq, Clone)]
 struct MovableProps {
+    /// Window opened through the `WindowManager`. Without one the window
+    /// opens itself on mount and closes when dropped.
+    #[props(default)]
+    handle: Option<WindowHandle>,
+    #[props(default = "Window".to_string())]
+    title: String,
+    /// Overrides the active `WindowDecoration`'s corner rounding.
+    #[props(default)]
+    rounded: Option<String>,
+    /// Overrides the active `WindowDecoration`'s border.
+    #[props(default)]
+    border: Option<String>,
+    /// Overrides the active `WindowDecoration`'s shadow.
+    #[props(default)]
+    shadow: Option<String>,
+    /// Lower bound for resizing, used on each axis the content doesn't
+    /// constrain with its own `min-width`/`min-height`.
+    #[props(default = (160.0, 120.0))]
+    min_size: (f64, f64),
+    #[props(default)]
+    max_size: Option<(f64, f64)>,
+    /// Snaps resized dimensions to multiples of this many pixels, showing
+    /// the snapped size next to the cursor while resizing.
+    #[props(default)]
+    grid: Option<f64>,
+    /// Resizes in steps of this size above the minimum size, for content
+    /// with a natural unit such as a terminal's character cells.
+    #[props(default)]
+    resize_increment: Option<(f64, f64)>,
+    /// Keeps width divided by height at this ratio while resizing.
+    #[props(default)]
+    aspect_ratio: Option<f64>,
+    /// Lets clicks on the window reach whatever is beneath it, for overlays
+    /// and HUDs. The window can then only be moved by a small grip on its
+    /// top edge, and isn't resizable.
+    #[props(default)]
+    click_through: bool,
+    /// Padding classes for the content, e.g. `"p-4"`. None by default, for
+    /// content such as canvases that should reach the frame.
+    #[props(default)]
+    content_padding: Option<String>,
+    /// Scrolls content that doesn't fit instead of letting it overflow the
+    /// frame.
+    #[props(default)]
+    content_scroll: bool,
+    /// Covers the content with a spinner and blocks interaction with it
+    /// while a long operation runs. The title bar stays usable.
+    #[props(default)]
+    busy: bool,
+    /// Where the window opens when it wasn't given a position, e.g. by a
+    /// restored session.
+    #[props(default)]
+    initial_placement: Placement,
+    /// Lets a secondary-button drag anywhere on the window resize it from
+    /// the nearest corner while `gesture_modifier` is held, so the thin
+    /// edges don't have to be hit. Primary-button drags on the body already
+    /// move the window.
+    #[props(default)]
+    modifier_gestures: bool,
+    #[props(default = Modifiers::META)]
+    gesture_modifier: Modifiers,
+    /// Opens the window as a child of `parent`, for dialogs belonging to an
+    /// app window. It's meant to be rendered inside the parent's content,
+    /// stays above the parent, moves with it, closes with it and blocks
+    /// interaction with it while open.
+    #[props(default)]
+    parent: Option<WindowId>,
     children: Element,
 }
 
+/// Reads the computed `min-width`/`min-height` of the window's content,
+/// the first child of its `wrapper`.
+fn content_min_size(wrapper: &MountedData) -> (Option<f64>, Option<f64>) {
+    let min = |property| {
+        browser::child_computed_style(wrapper, property)
+            .as_deref()
+            .and_then(parse_css_px)
+    };
+    (min("min-width"), min("min-height"))
+}
+
+/// Number of times a flashing window pulses before the flash stops.
+const FLASH_PULSES: u32 = 3;
+/// How long a flash highlight stays on, then off, in each pulse.
+const FLASH_PHASE_MS: u32 = 350;
+
+/// DOM id of the frame of window `id`.
+pub fn window_element_id(id: WindowId) -> String {
+    format!("jadeos-window-{id}")
+}
+
+/// `(position, size)` of the placed, visible windows other than `except`.
+/// Windows still sized by their content are measured in the DOM.
+fn window_rects(manager: &WindowManager, except: WindowId) -> Vec<((f64, f64), (f64, f64))> {
+    manager
+        .windows()
+        .iter()
+        .filter(|window| {
+            window.id != except
+                && manager.is_visible(window.id)
+                && !manager.needs_placement(window.id)
+        })
+        .filter_map(|window| {
+            let state = manager.state(window.id);
+            let size = state
+                .size
+                .or_else(|| browser::element_size(&window_element_id(window.id)))?;
+            Some((state.position, size))
+        })
+        .collect()
+}
+
+/// Size of the desktop window `id` is on: the closest element marked
+/// `data-desktop` around its frame, or the browser viewport outside of one.
+///
+/// Maximizing, tiling, placing windows and pulling them back into view all
+/// work within this area, so a desktop can also be embedded in part of a
+/// page.
+fn desktop_size(id: WindowId) -> Option<(f64, f64)> {
+    browser::closest_size(&window_element_id(id), "[data-desktop]")
+        .or_else(browser::viewport_size)
+}
 
 #[component]
 pub fn Movable(props: MovableProps) -> Element {
-    let mut position = use_signal(|| (100.0, 100.0));
+    let mut manager = use_window_manager();
+    let handle = props.handle.clone();
+    let title = props.title.clone();
+    let owned = handle.is_none();
+    let parent = props.parent;
+    let id = use_hook(move || match (handle, parent) {
+        (Some(handle), _) => handle.id,
+        (None, Some(parent)) => manager.write().open_child(parent, title).id,
+        (None, None) => manager.write().open(title).id,
+    });
+    use_drop(move || {
+        if owned {
+            manager.write().close(id);
+        }
+    });
+    use_context_provider(|| CurrentWindow(id));
+
+    let initial = use_hook(move || manager.peek().state(id));
+    let mut position = use_signal(|| initial.position);
+    let mut size = use_signal(|| initial.size);
     let mut dragging = use_signal(|| false);
+    let mut resizing = use_signal(|| Option::<ResizeHandle>::None);
     let mut mounted = use_signal(|| Option::<Rc<MountedData>>::None);
-    let mut active_pointer_id = use_signal(|| Option::<i32>::None);
+    let mut content = use_signal(|| Option::<Rc<MountedData>>::None);
+    let mut gesture = use_signal(PointerGesture::default);
+    let mut fallback = use_signal(|| Option::<Rc<DocumentPointerListeners>>::None);
+    let mut escape = use_signal(|| Option::<Rc<DocumentEscapeListener>>::None);
+    // Size before the current gesture, restored if it's cancelled.
+    let mut origin_size = use_signal(|| Option::<(f64, f64)>::None);
 
     let mut click_origin = use_signal(|| (0.0, 0.0));
     let mut modal_origin = use_signal(|| (0.0, 0.0));
+    let mut size_origin = use_signal(|| (0.0, 0.0));
+    // Size of the window being moved, measured when the drag starts if it's
+    // sized by its content.
+    let mut drag_size = use_signal(|| Option::<(f64, f64)>::None);
+    let mut guides = use_signal(CenterGuides::default);
+    let default_min_size = props.min_size;
+    let max_size = props.max_size;
+    let grid = props.grid;
+    let resize_increment = props.resize_increment;
+    let aspect_ratio = props.aspect_ratio;
+    let mut cursor = use_signal(|| (0.0, 0.0));
+    let mut min_size = use_signal(|| default_min_size);
+    let reduced_motion = use_reduced_motion();
+
+    // Follow geometry committed through the manager by someone else
+    // (maximizing, restoring, ...) unless a gesture owns it right now.
+    use_effect(move || {
+        let state = manager.read().state(id);
+        if *dragging.peek() || resizing.peek().is_some() {
+            return;
+        }
+        if *position.peek() != state.position {
+            position.set(state.position);
+        }
+        if *size.peek() != state.size {
+            size.set(state.size);
+        }
+    });
+
+    use_window_shortcut("Alt+F10", move || {
+        if let Some(desktop) = desktop_size(id) {
+            manager.write().toggle_maximize(id, desktop);
+        }
+    });
+
+    // Pulse the flash highlight while the manager says the window flashes,
+    // or just show it for as long with reduced motion. Focusing the window
+    // ends the flash in the manager, which cancels the pulsing.
+    let flashing = use_memo(move || manager.read().is_flashing(id));
+    let mut flash_task = use_signal(|| None::<Task>);
+    use_effect(move || {
+        if let Some(task) = flash_task.write().take() {
+            task.cancel();
+        }
+        if !flashing() {
+            return;
+        }
+        let steady = reduced_motion();
+        flash_task.set(Some(spawn(async move {
+            for phase in 0..FLASH_PULSES * 2 {
+                if !steady {
+                    manager.write().set_flash_lit(id, phase % 2 == 0);
+                }
+                browser::sleep(FLASH_PHASE_MS).await;
+            }
+            manager.write().stop_flash(id);
+        })));
+    });
+
+    // Hovering focuses the window when the focus policy asks for it. The
+    // hover isn't rendered, so following the pointer doesn't re-render.
+    let mut hover = use_signal(HoverFocus::default);
+    let mut hover_task = use_signal(|| None::<Task>);
+    let onpointerenter = move |_| {
+        hover.write().enter(browser::now());
+        if let Some(task) = hover_task.write().take() {
+            task.cancel();
+        }
+        hover_task.set(Some(spawn(async move {
+            // Sloppy focus pushes the deadline back while the pointer moves.
+            loop {
+                let policy = manager.peek().focus_policy();
+                let Some(due) = hover.peek().due(policy) else {
+                    break;
+                };
+                let now = browser::now();
+                if now >= due {
+                    break;
+                }
+                browser::sleep((due - now).ceil() as u32).await;
+            }
+            if manager.peek().wants_hover_focus(id, &hover.peek(), browser::now()) {
+                manager.write().focus(id);
+            }
+        })));
+    };
+    let onpointerleave = move |_| {
+        hover.write().leave();
+        if let Some(task) = hover_task.write().take() {
+            task.cancel();
+        }
+    };
+
+    // Windows waiting to be placed stay hidden until they are, and don't
+    // animate there.
+    let mut placing = use_signal(|| manager.peek().needs_placement(id));
+    let initial_placement = props.initial_placement;
 
     let onmounted = move |evt: Event<MountedData>| {
-        mounted.set(Some(evt.data()));
+        let data = evt.data();
+        if !placing() {
+            // Persisted geometry may come from a larger screen or desktop;
+            // pull it into view.
+            if let Some(desktop) = desktop_size(id) {
+                let state = (position(), size());
+                let clamped = clamp_into_view(state.0, state.1, desktop);
+                if clamped != state {
+                    position.set(clamped.0);
+                    size.set(clamped.1);
+                    manager.write().set_state(id, WindowState { position: clamped.0, size: clamped.1 });
+                }
+            }
+        } else {
+            if let Some(measured) = browser::mounted_size(&data) {
+                let container = desktop_size(id).unwrap_or_default();
+                let (index, others) = {
+                    let manager = manager.peek();
+                    let index = manager
+                        .windows()
+                        .iter()
+                        .position(|window| window.id == id)
+                        .unwrap_or_default();
+                    (index, window_rects(&manager, id))
+                };
+                let placed = place(
+                    initial_placement,
+                    index,
+                    measured,
+                    container,
+                    &others,
+                );
+                position.set(placed);
+                manager.write().place(id, placed);
+            }
+            spawn(async move {
+                browser::sleep(50).await;
+                placing.set(false);
+            });
+        }
+        mounted.set(Some(data));
     };
 
-    let onpointerdown = move |evt: Event<PointerData>| {
-        if evt.data.trigger_button() != Some(MouseButton::Primary) {
+    let oncontentmounted = move |evt: Event<MountedData>| {
+        content.set(Some(evt.data()));
+    };
+
+    let track = move |mouse: (f64, f64)| {
+        if !dragging() && resizing().is_none() { return; }
+        cursor.set(mouse);
+
+        let origin = click_origin();
+        let modal = modal_origin();
+
+        let delta = (
+            mouse.0 - origin.0,
+            mouse.1 - origin.1
+        );
+
+        if let Some(handle) = resizing() {
+            let constraints = SizeConstraints {
+                min: min_size(),
+                max: max_size,
+                grid,
+                increment: resize_increment,
+                aspect_ratio,
+            };
+            let (new_position, new_size) =
+                resize_rect(handle, modal, size_origin(), delta, constraints);
+            position.set(new_position);
+            size.set(Some(new_size));
+            return;
+        }
+
+        let mut moved = (modal.0 + delta.0, modal.1 + delta.1);
+        if let Some(own_size) = drag_size() {
+            // Windows still sized by their content don't know their size, so
+            // they can't be aligned with.
+            let others: Vec<_> = {
+                let manager = manager.peek();
+                manager
+                    .windows()
+                    .iter()
+                    .filter(|window| window.id != id && manager.is_visible(window.id))
+                    .filter_map(|window| {
+                        let state = manager.state(window.id);
+                        Some((state.position, state.size?))
+                    })
+                    .collect()
+            };
+            let (snapped, new_guides) =
+                center_snap(moved, own_size, &others, CENTER_SNAP_THRESHOLD);
+            moved = snapped;
+            if guides() != new_guides {
+                guides.set(new_guides);
+            }
+        }
+        position.set(moved);
+        // Child windows follow the drag as it happens.
+        if manager.peek().has_children(id) {
+            manager.write().set_state(id, WindowState { position: moved, size: size() });
+        }
+
+        let target = workspace_at(mouse);
+        if manager.peek().drag_target() != target {
+            manager.write().set_drag_target(target);
+        }
+    };
+
+    // Pointer moves are applied at most once per animation frame, so heavy
+    // content isn't laid out more often than it's painted.
+    let mut frames = use_frame_throttle(track);
+
+    let mut end_gesture = move |pointer_id: i32| {
+        if let Some(mounted) = mounted.read().as_ref() {
+            browser::release_pointer(mounted, pointer_id);
+        }
+
+        // Dropped on a workspace indicator: send the window there, back where
+        // it was picked up rather than on top of the switcher.
+        let dropped = dragging() && manager.peek().drag_target().is_some();
+        if dropped {
+            position.set(modal_origin());
+        }
+
+        gesture.set(PointerGesture::Idle);
+        dragging.set(false);
+        resizing.set(None);
+        guides.set(CenterGuides::default());
+        {
+            let mut manager = manager.write();
+            manager.set_state(id, WindowState { position: position(), size: size() });
+            if dropped {
+                manager.drop_on_drag_target(id);
+            } else {
+                manager.set_drag_target(None);
+            }
+        }
+
+        // The fallback listeners may be the ones calling us, so they're
+        // detached once the current event has been handled.
+        if fallback.peek().is_some() {
+            spawn(async move { fallback.set(None) });
+        }
+        if escape.peek().is_some() {
+            spawn(async move { escape.set(None) });
+        }
+    };
+
+    // Escape cancels the gesture, putting the window back where it started.
+    let mut arm_escape = move |pointer_id: i32| {
+        let listener = DocumentEscapeListener::attach(move || {
+            if !gesture().follows(pointer_id) {
+                return;
+            }
+            frames.cancel();
+            position.set(modal_origin());
+            size.set(origin_size());
+            manager.write().set_drag_target(None);
+            end_gesture(pointer_id);
+        });
+        escape.set(listener.map(Rc::new));
+    };
+
+    // Follows the pointer from document-level listeners when it can't be
+    // captured, until it's released.
+    let mut capture_fallback = move |pointer_id: i32| {
+        if fallback.peek().is_some() {
+            return;
+        }
+        let listeners = DocumentPointerListeners::attach(
+            pointer_id,
+            move |mouse| frames.push(mouse),
+            move || {
+                if gesture().follows(pointer_id) {
+                    frames.flush();
+                    end_gesture(pointer_id);
+                }
+            },
+        );
+        fallback.set(listeners.map(Rc::new));
+    };
+
+    let modifier_gestures = props.modifier_gestures;
+    let gesture_modifier = props.gesture_modifier;
+    let modifier_resize = move |evt: &Event<PointerData>| {
+        modifier_gestures
+            && evt.data.modifiers().contains(gesture_modifier)
+            && evt.data.trigger_button() == Some(MouseButton::Secondary)
+    };
+
+    let mut start_resize = move |handle: ResizeHandle, evt: Event<PointerData>| {
+        if evt.data.trigger_button() != Some(MouseButton::Primary) && !modifier_resize(&evt) {
             return;
         }
+        evt.stop_propagation();
+        {
+            // Resizing a maximized window un-maximizes it: the gesture starts
+            // from the maximized geometry and the window keeps the dragged
+            // size rather than snapping back.
+            let mut manager = manager.write();
+            manager.focus(id);
+            manager.clear_maximized(id);
+        }
 
         let pointer_id = evt.data.pointer_id();
 
-        if let Some(element) = mounted
+        let Some(element) = mounted.read().clone() else {
+            return;
+        };
+        let captured = browser::capture_pointer(&element, pointer_id);
+        if !captured {
+            capture_fallback(pointer_id);
+        }
+
+        // Content constraints are re-read on every gesture so windows pick up
+        // layout changes made since the last resize.
+        let content_min = content
             .read()
             .as_ref()
-            .and_then(|m| m.as_ref().try_as_web_event())
-        {
-            let _ = element.set_pointer_capture(pointer_id);
+            .map_or((None, None), |wrapper| content_min_size(wrapper));
+        min_size.set(effective_min_size(content_min, default_min_size));
+
+        let current = size()
+            .or_else(|| browser::mounted_size(&element))
+            .unwrap_or_default();
+
+        let coords = evt.data.coordinates();
+        click_origin.set((coords.client().x, coords.client().y));
+        modal_origin.set(position());
+        size_origin.set(current);
+        origin_size.set(size());
+        gesture.set(PointerGesture::start(pointer_id, captured));
+        resizing.set(Some(handle));
+        arm_escape(pointer_id);
+    };
+
+    let onpointerdown = move |evt: Event<PointerData>| {
+        // A child window sits inside its parent's DOM; keep the parent from
+        // handling the press as well.
+        if parent.is_some() {
+            evt.stop_propagation();
+        }
+        if modifier_resize(&evt) {
+            let local = (
+                evt.data.client_coordinates().x - position().0,
+                evt.data.client_coordinates().y - position().1,
+            );
+            let current = size().or_else(|| browser::mounted_size(mounted.read().as_ref()?));
+            if let Some(current) = current {
+                start_resize(nearest_corner(local, current), evt);
+            }
+            return;
+        }
+        if evt.data.trigger_button() != Some(MouseButton::Primary) {
+            return;
+        }
+        manager.write().focus(id);
+
+        let pointer_id = evt.data.pointer_id();
+
+        let element = mounted.read().clone();
+        let mut captured = false;
+        if let Some(element) = element {
+            captured = browser::capture_pointer(&element, pointer_id);
+            if !captured {
+                capture_fallback(pointer_id);
+            }
+            drag_size.set(size().or_else(|| browser::mounted_size(&element)));
         }
 
         let coords = evt.data.coordinates();
-        let mouse = (
-            coords.client().x as f64,
-            coords.client().y as f64
-        );
+        let mouse = (coords.client().x, coords.client().y);
 
         click_origin.set(mouse);
         modal_origin.set(position());
-        active_pointer_id.set(Some(pointer_id));
+        origin_size.set(size());
+        gesture.set(PointerGesture::start(pointer_id, captured));
         dragging.set(true);
+        arm_escape(pointer_id);
     };
 
     let onpointermove = move |evt: Event<PointerData>| {
-        if !dragging() { return; }
-        if active_pointer_id() != Some(evt.data.pointer_id()) { return; }
+        hover.write().moved(browser::now());
+        if !gesture().follows(evt.data.pointer_id()) { return; }
 
         let coords = evt.data.coordinates();
-        let mouse = (
-            coords.client().x as f64,
-            coords.client().y as f64
-        );
+        frames.push((coords.client().x, coords.client().y));
+    };
 
-        let origin = click_origin();
-        let modal = modal_origin();
+    // The release commits exactly where the pointer was let go.
+    let onpointerup = move |evt: Event<PointerData>| {
+        if !gesture().follows(evt.data.pointer_id()) { return; }
+        let coords = evt.data.coordinates();
+        frames.push((coords.client().x, coords.client().y));
+        frames.flush();
+        end_gesture(evt.data.pointer_id());
+    };
 
-        let delta = (
-            mouse.0 - origin.0,
-            mouse.1 - origin.1
-        );
+    let onpointercancel = move |evt: Event<PointerData>| {
+        if !gesture().follows(evt.data.pointer_id()) { return; }
+        frames.flush();
+        end_gesture(evt.data.pointer_id());
+    };
 
-        position.set((
-            modal.0 + delta.0,
-            modal.1 + delta.1
-        ));
+    let onlostpointercapture = move |_| {
+        let lost = gesture().capture_lost();
+        gesture.set(lost);
+        if let PointerGesture::Fallback(pointer_id) = lost {
+            capture_fallback(pointer_id);
+        }
     };
 
-    let onpointerup = move |evt: Event<PointerData>| {
-        if active_pointer_id() != Some(evt.data.pointer_id()) { return; }
+    let mut size_style = match size() {
+        Some((width, height)) => format!("width:{width}px; height:{height}px;"),
+        None => String::new(),
+    };
+    // Minimized windows and windows on other workspaces stay mounted so they
+    // keep their state.
+    if !manager.read().is_visible(id) {
+        size_style.push_str(" display:none;");
+    }
 
-        if let Some(element) = mounted
-            .read()
-            .as_ref()
-            .and_then(|m| m.as_ref().try_as_web_event())
-        {
-            let _ = element.release_pointer_capture(evt.data.pointer_id());
-        }
+    // Geometry changes made outside of a gesture (snapping, tiling, ...) are
+    // animated, unless the user prefers reduced motion.
+    if placing() {
+        size_style.push_str(" visibility:hidden;");
+    }
 
-        active_pointer_id.set(None);
-        dragging.set(false);
+    let transition = if reduced_motion() || placing() || dragging() || resizing().is_some() {
+        ""
+    } else {
+        "transition: left 150ms ease-out, top 150ms ease-out, width 150ms ease-out, height 150ms ease-out;"
     };
 
-    let onpointercancel = move |evt: Event<PointerData>| {
-        if active_pointer_id() != Some(evt.data.pointer_id()) { return; }
+    if props.click_through {
+        size_style.push_str(" pointer-events:none;");
+    }
 
-        if let Some(element) = mounted
-            .read()
-            .as_ref()
-            .and_then(|m| m.as_ref().try_as_web_event())
-        {
-            let _ = element.release_pointer_capture(evt.data.pointer_id());
+    let content_class = format!(
+        "relative w-full min-h-0 flex-1 {} {}",
+        props.content_padding.as_deref().unwrap_or_default(),
+        if props.content_scroll { "overflow-auto" } else { "overflow-visible" },
+    );
+
+    let decoration = use_window_decoration()();
+    let rounded = props.rounded.as_deref().unwrap_or(decoration.rounded());
+    let border = props.border.as_deref().unwrap_or(decoration.border());
+    let shadow = props.shadow.as_deref().unwrap_or(decoration.shadow());
+
+    let focused = manager.read().focused() == Some(id);
+    let state_class = match (focused, dragging()) {
+        (_, true) => "ring-1 ring-[var(--jade-accent)] select-none cursor-grabbing",
+        (true, false) => "ring-1 ring-[var(--jade-accent)]",
+        (false, false) if manager.read().flash_lit(id) => {
+            "ring-4 ring-amber-400 shadow-[0_0_24px_rgb(251_191_36/0.6)]"
         }
-
-        active_pointer_id.set(None);
-        dragging.set(false);
+        (false, false) => "",
     };
 
-    let onlostpointercapture = move |_| {
-        active_pointer_id.set(None);
-        dragging.set(false);
-    };
     rsx! {
         div {
+            id: window_element_id(id),
+            class: "flex flex-col {rounded} {border} {shadow} {state_class}",
             style: format!(
-                "position:absolute; left:{}px; top:{}px;",
+                "position:{}; left:{}px; top:{}px; z-index:{}; background:var(--jade-surface); color:var(--jade-text); {} {}",
+                // Child windows are nested in their parent's frame but use
+                // desktop coordinates.
+                if parent.is_some() { "fixed" } else { "absolute" },
                 position().0,
                 position().1,
+                manager.read().z_index(id),
+                size_style,
+                transition,
             ),
             onmounted: onmounted,
             onpointerdown: onpointerdown,
             onpointermove: onpointermove,
+            onpointerenter: onpointerenter,
+            onpointerleave: onpointerleave,
             onpointerup: onpointerup,
             onpointercancel: onpointercancel,
             onlostpointercapture: onlostpointercapture,
-            {props.children}
-        }
-    }
-}
-#[derive(PartialEq, Clone, Props)]
-pub struct CardProps {
-    color: String,
-    shadow: String,
-    rounded: String,
-    #[props(default = "".to_string())]
-    size: String,
-    #[props(default = "".to_string())]
-    class: String,
-    children: Element,
-}
-
-#[component]
-pub fn Card(props: CardProps) -> Element {
-    rsx! {
-        div { class: "relative flex flex-col border border-slate-200 {props.color} {props.shadow} {props.rounded} {props.size} {props.class:?}",
-            {props.children}
-        }
-    }
-}
-
-
-#[derive(PartialEq, Clone, Props)]
-pub struct CardHeaderProps {
-    color: String,
-    size: String,
-    class: Option<String>,
-    children: Element,
-}
-
-#[component]
-pub fn CardHeader(props: CardHeaderProps) -> Element {
-    rsx! {
-        div { class: "relative overflow-hidden flex-auto {props.color} {props.size} {props.class:?}",
-            {props.children}
+            // The secondary button resizes while the modifier is held.
+            oncontextmenu: move |evt: Event<MouseData>| {
+                if modifier_gestures && evt.data.modifiers().contains(gesture_modifier) {
+                    evt.prevent_default();
+                }
+            },
+            TitleBar { id, closable: !owned }
+            div {
+                class: content_class,
+                aria_busy: props.busy,
+                onmounted: oncontentmounted,
+                {props.children}
+                if props.busy {
+                    div {
+                        class: "absolute inset-0 flex items-center justify-center bg-white/60 text-slate-600 cursor-wait",
+                        Spinner { size: "h-8 w-8" }
+                    }
+                }
+                // Child windows are app-modal.
+                if manager.read().has_children(id) {
+                    div { class: "absolute inset-0" }
+                }
+            }
+            if let Some(x) = guides().vertical {
+                div {
+                    class: "pointer-events-none bg-sky-400",
+                    style: "position:fixed; left:{x}px; top:0; bottom:0; width:1px; z-index:10001;",
+                }
+            }
+            if let Some(y) = guides().horizontal {
+                div {
+                    class: "pointer-events-none bg-sky-400",
+                    style: "position:fixed; top:{y}px; left:0; right:0; height:1px; z-index:10001;",
+                }
+            }
+            if let (Some(_), Some(_), Some((width, height))) = (grid, resizing(), size()) {
+                div {
+                    class: "pointer-events-none rounded bg-slate-900/90 px-2 py-1 text-xs text-white",
+                    style: "position:fixed; left:{cursor().0 + 14.0}px; top:{cursor().1 + 14.0}px; z-index:10001;",
+                    "{width} × {height}"
+                }
+            }
+            if props.click_through {
+                // Events on the grip still bubble to the frame's handlers,
+                // which only ignores them for hit testing.
+                div {
+                    class: "rounded-full bg-slate-500/70 hover:bg-slate-600",
+                    style: "position:absolute; top:-10px; left:50%; width:40px; height:8px; margin-left:-20px; pointer-events:auto; cursor:grab;",
+                    title: "Move",
+                }
+            } else {
+                ResizeGrips {
+                    handles: ResizeHandle::ALL.to_vec(),
+                    onstart: move |(handle, evt)| start_resize(handle, evt),
+                }
+            }
         }
     }
 }
-
-
-#[derive(PartialEq, Clone, Props)]
-pub struct CardBodyProps {
-    #[props(default = "".to_string())]
-    size: String,
-    children: Element,
-}
-
-#[component]
-pub fn CardBody(props: CardBodyProps) -> Element {
-    rsx! {
-        div { class: "p-4 {props.size}", {props.children} }
-
-    }
-}
-
-#[derive(PartialEq, Clone, Props)]
-pub struct CardFooterProps {
-    #[props(default = "".to_string())]
-    size: String,
-    children: Element,
-}
-
 #[component]
-pub fn CardFooter(props: CardFooterProps) -> Element {
+fn KanbanApp() -> Element {
+    let board = use_signal(sample_board);
     rsx! {
-        div { class: "px-4 pb-4 pt-0 mt-2 {props.size}", {props.children} }
+        div { class: "h-96 w-[44rem]", Kanban { board } }
     }
 }
-
-#[derive(Props, Clone, PartialEq)]
-pub struct TypographyProps {
-    text: String,
-    size: String,
-    color: String,
-    #[props(default="".to_string())]
-    position: String,
-    #[props(default="".to_string())]
-    class : String,
-}
-
-#[component]
-pub fn Typography(props: TypographyProps) -> Element {
-    rsx! {
-        div { class: "{props.size} {props.color} {props.position} {props.class}", {props.text} }
-    }
-}
\ No newline at end of file
@@ -0,0 +1,382 @@
+//! The notes app: a plain text editor for a file in the sandbox. Images
+//! pasted into a note are uploaded next to it and referenced as Markdown.
+//!
+//! A note can be split into a second window beside it. Both windows then
+//! edit the same [`NoteBuffer`], so typing in one shows up in the other.
+
+use crate::browser;
+use crate::desktop_size;
+use crate::event_bus::{use_subscribe, OPEN_FILE};
+use crate::file_manager::parent_path;
+use crate::geometry::SnapLayout;
+use crate::paste::use_image_paste;
+use crate::shortcuts::{use_window_shortcut, CurrentWindow};
+use crate::window_manager::{use_window_manager, WindowId};
+use dioxus::prelude::*;
+use std::collections::HashMap;
+use std::rc::Rc;
+use ui::use_toasts;
+
+pub type BufferId = usize;
+
+/// A note being edited, shared by every Notes window showing it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NoteBuffer {
+    /// File the note was opened from or saved to; empty until then.
+    pub path: String,
+    pub text: String,
+    /// Whether `text` has changes not saved to `path`.
+    pub dirty: bool,
+    /// Notes windows showing the buffer. It's dropped with the last one.
+    views: usize,
+}
+
+/// The note buffers of all Notes windows, provided by the desktop.
+#[derive(Clone, Copy)]
+pub struct NoteBuffers {
+    buffers: Signal<HashMap<BufferId, NoteBuffer>>,
+    /// Buffers windows about to open should show instead of a new one,
+    /// waiting for their Notes app to mount and claim them.
+    pending: Signal<HashMap<WindowId, BufferId>>,
+    next_id: Signal<BufferId>,
+}
+
+impl NoteBuffers {
+    pub fn new() -> Self {
+        Self {
+            buffers: Signal::new(HashMap::new()),
+            pending: Signal::new(HashMap::new()),
+            next_id: Signal::new(0),
+        }
+    }
+
+    /// Adds a view of the buffer linked to `window` with [`link`], or of a
+    /// new empty buffer.
+    ///
+    /// [`link`]: NoteBuffers::link
+    fn attach(&mut self, window: Option<WindowId>) -> BufferId {
+        let linked = window.and_then(|window| self.pending.write().remove(&window));
+        let mut buffers = self.buffers.write();
+        let id = match linked.filter(|id| buffers.contains_key(id)) {
+            Some(id) => id,
+            None => {
+                let id = *self.next_id.peek();
+                *self.next_id.write() += 1;
+                buffers.insert(id, NoteBuffer::default());
+                id
+            }
+        };
+        if let Some(buffer) = buffers.get_mut(&id) {
+            buffer.views += 1;
+        }
+        id
+    }
+
+    /// Removes a view of buffer `id`, dropping the buffer if it was the last.
+    fn detach(&mut self, id: BufferId) {
+        let mut buffers = self.buffers.write();
+        if let Some(buffer) = buffers.get_mut(&id) {
+            buffer.views -= 1;
+            if buffer.views == 0 {
+                buffers.remove(&id);
+            }
+        }
+    }
+
+    /// Has the Notes app opening in `window` show buffer `id`.
+    pub fn link(&mut self, window: WindowId, id: BufferId) {
+        self.pending.write().insert(window, id);
+    }
+
+    /// Whether buffer `id` is shown in more than one window.
+    fn is_shared(&self, id: BufferId) -> bool {
+        self.buffers
+            .peek()
+            .get(&id)
+            .is_some_and(|buffer| buffer.views > 1)
+    }
+
+    /// A snapshot of buffer `id`, subscribing the caller to its changes.
+    pub fn get(&self, id: BufferId) -> NoteBuffer {
+        self.buffers.read().get(&id).cloned().unwrap_or_default()
+    }
+
+    pub fn update(&mut self, id: BufferId, f: impl FnOnce(&mut NoteBuffer)) {
+        if let Some(buffer) = self.buffers.write().get_mut(&id) {
+            f(buffer);
+        }
+    }
+}
+
+/// Byte index in `text` of the character `offset` UTF-16 code units in, as
+/// the DOM counts text positions. Offsets past the end give `text.len()`.
+pub fn utf16_to_byte(text: &str, offset: usize) -> usize {
+    let mut units = 0;
+    for (index, c) in text.char_indices() {
+        if units >= offset {
+            return index;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+/// Markdown image reference to the file at `path`.
+pub fn image_reference(path: &str) -> String {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    format!("![{name}]({path})")
+}
+
+#[component]
+pub fn Notes() -> Element {
+    let mut buffers = use_context::<NoteBuffers>();
+    let window = try_use_context::<CurrentWindow>();
+    let mut buffer = use_signal(move || buffers.attach(window.map(|CurrentWindow(id)| id)));
+    use_drop(move || buffers.detach(*buffer.peek()));
+
+    let note = buffers.get(buffer());
+    let mut draft_path = use_signal(|| note.path.clone());
+    let mut editor = use_signal(|| None::<Rc<MountedData>>);
+    let mut toasts = use_toasts();
+
+    let mut manager = use_window_manager();
+    use_effect(move || {
+        let note = buffers.get(buffer());
+        let name = note.path.rsplit('/').next().filter(|name| !name.is_empty());
+        let marker = if note.dirty { " •" } else { "" };
+        if let Some(CurrentWindow(id)) = window {
+            manager.write().set_title(
+                id,
+                format!("Notes — {}{marker}", name.unwrap_or("Untitled")),
+            );
+        }
+    });
+
+    let open = move |to: String| {
+        spawn(async move {
+            match api::read_file(to.clone()).await {
+                Ok(contents) => {
+                    // Opening another file in a split window leaves the
+                    // note shown in the other window alone.
+                    let current = buffer();
+                    if buffers.is_shared(current) {
+                        buffers.detach(current);
+                        buffer.set(buffers.attach(None));
+                    }
+                    buffers.update(buffer(), |note| {
+                        note.text = contents;
+                        note.path = to;
+                        note.dirty = false;
+                    });
+                }
+                Err(err) => toasts.error(&err),
+            }
+        });
+    };
+
+    // Files opened from the file manager go to the most recently used
+    // Notes window.
+    use_subscribe(OPEN_FILE, move |path: String| {
+        let latest = {
+            let manager = manager.peek();
+            manager.mru().iter().copied().find(|&other| {
+                manager
+                    .get(other)
+                    .and_then(|handle| handle.app_id.as_deref())
+                    == Some("notes")
+            })
+        };
+        match (window, latest) {
+            (Some(CurrentWindow(id)), Some(latest)) if id != latest => {}
+            (Some(CurrentWindow(id)), _) => {
+                // A copy, as subscribers can't mutate what they capture.
+                let mut manager = manager;
+                manager.write().focus(id);
+                open(path);
+            }
+            (None, _) => open(path),
+        }
+    });
+
+    let save = move || {
+        let to = draft_path();
+        if to.is_empty() {
+            return;
+        }
+        spawn(async move {
+            let id = buffer();
+            match api::write_file(to.clone(), buffers.get(id).text).await {
+                Ok(()) => buffers.update(id, |note| {
+                    note.path = to;
+                    note.dirty = false;
+                }),
+                Err(err) => toasts.error(&err),
+            }
+        });
+    };
+    use_window_shortcut("Ctrl+S", save);
+
+    // Opens a Notes window beside this one, showing the same note when
+    // `linked` and an empty one otherwise.
+    let mut split = move |linked: bool| {
+        let Some(CurrentWindow(id)) = window else {
+            return;
+        };
+        let mut manager = manager.write();
+        let other = manager.launch("notes", "Notes").id;
+        if linked {
+            buffers.link(other, buffer());
+        }
+        if let Some(desktop) = desktop_size(id) {
+            manager.snap(id, SnapLayout::Halves, 0, desktop);
+            manager.snap(other, SnapLayout::Halves, 1, desktop);
+        }
+    };
+
+    // Pasted images go next to the note and are referenced at the caret,
+    // or at the end if the editor can't tell where that is.
+    let insert = use_callback(move |uploaded: String| {
+        let caret = editor.peek().as_deref().and_then(browser::selection_start);
+        buffers.update(*buffer.peek(), |note| {
+            let at = caret.map_or(note.text.len(), |caret| {
+                utf16_to_byte(&note.text, caret as usize)
+            });
+            note.text.insert_str(at, &image_reference(&uploaded));
+            note.dirty = true;
+        });
+    });
+    let onpaste = use_image_paste(
+        move || match buffers.get(*buffer.peek()).path.as_str() {
+            "" => "/".to_string(),
+            path => parent_path(path),
+        },
+        insert,
+    );
+
+    rsx! {
+        div { class: "flex h-80 w-[32rem] flex-col bg-white",
+            div { class: "flex items-center gap-2 border-b border-slate-200 px-2 py-1 text-sm",
+                input {
+                    class: "min-w-0 flex-1 rounded border border-slate-300 px-1",
+                    placeholder: "/notes/todo.md",
+                    value: "{draft_path}",
+                    oninput: move |evt| draft_path.set(evt.value()),
+                    onkeydown: move |evt| {
+                        if evt.key() == Key::Enter {
+                            open(draft_path());
+                        }
+                    },
+                }
+                button {
+                    class: "rounded px-2 hover:bg-slate-200",
+                    onclick: move |_| open(draft_path()),
+                    "Open"
+                }
+                button {
+                    class: "rounded px-2 hover:bg-slate-200 disabled:opacity-40",
+                    disabled: draft_path().is_empty(),
+                    onclick: move |_| save(),
+                    "Save"
+                }
+                if window.is_some() {
+                    button {
+                        class: "rounded px-2 hover:bg-slate-200",
+                        title: "Edit this note in a second window beside this one",
+                        onclick: move |_| split(true),
+                        "Split"
+                    }
+                    button {
+                        class: "rounded px-2 hover:bg-slate-200",
+                        title: "Open another note beside this one",
+                        onclick: move |_| split(false),
+                        "New beside"
+                    }
+                }
+            }
+            textarea {
+                class: "flex-1 resize-none p-2 font-mono text-sm outline-none",
+                value: "{note.text}",
+                onmounted: move |evt| editor.set(Some(evt.data())),
+                oninput: move |evt| {
+                    buffers.update(buffer(), |note| {
+                        note.text = evt.value();
+                        note.dirty = true;
+                    });
+                },
+                onpaste,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utf16_offsets_map_to_char_boundaries() {
+        let text = "aé😀b";
+        assert_eq!(utf16_to_byte(text, 0), 0);
+        assert_eq!(utf16_to_byte(text, 1), 1);
+        assert_eq!(utf16_to_byte(text, 2), 3);
+        // The emoji is a surrogate pair: an offset inside it rounds up.
+        assert_eq!(utf16_to_byte(text, 3), 7);
+        assert_eq!(utf16_to_byte(text, 4), 7);
+        assert_eq!(utf16_to_byte(text, 5), 8);
+        assert_eq!(utf16_to_byte(text, 99), text.len());
+        assert_eq!(utf16_to_byte("", 3), 0);
+    }
+
+    /// Runs `test` where signals can be created, as inside a component.
+    fn with_runtime(test: impl FnOnce()) {
+        let mut dom = VirtualDom::new(|| rsx! {});
+        dom.rebuild_in_place();
+        dom.in_scope(ScopeId::ROOT, test);
+    }
+
+    #[test]
+    fn split_windows_share_one_buffer() {
+        with_runtime(|| {
+            let mut buffers = NoteBuffers::new();
+            let first = buffers.attach(Some(1));
+            assert!(!buffers.is_shared(first));
+
+            // The split window claims the buffer linked to it.
+            buffers.link(2, first);
+            assert_eq!(buffers.attach(Some(2)), first);
+            assert!(buffers.is_shared(first));
+            buffers.update(first, |buffer| {
+                buffer.text = "shared".to_string();
+                buffer.dirty = true;
+            });
+            assert_eq!(buffers.get(first).text, "shared");
+
+            // Links are claimed once; other windows get buffers of their own.
+            let other = buffers.attach(Some(2));
+            assert_ne!(other, first);
+            assert_eq!(buffers.get(other).text, "");
+            assert_ne!(buffers.attach(None), first);
+
+            buffers.detach(first);
+            assert!(!buffers.is_shared(first));
+            assert_eq!(buffers.get(first).text, "shared");
+            buffers.detach(first);
+            assert_eq!(buffers.get(first), NoteBuffer::default());
+        });
+    }
+
+    #[test]
+    fn links_to_dropped_buffers_open_a_new_one() {
+        with_runtime(|| {
+            let mut buffers = NoteBuffers::new();
+            let first = buffers.attach(Some(1));
+            buffers.update(first, |buffer| buffer.text = "gone".to_string());
+            buffers.link(2, first);
+            buffers.detach(first);
+
+            let second = buffers.attach(Some(2));
+            assert_ne!(second, first);
+            assert_eq!(buffers.get(second).text, "");
+        });
+    }
+}
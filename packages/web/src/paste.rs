@@ -0,0 +1,65 @@
+//! Pasting images into apps: an image on the clipboard is uploaded to the
+//! sandbox with `api::upload_file` and the app gets its path back to insert
+//! a reference to it. Anything else on the clipboard pastes as usual.
+
+use crate::browser;
+use dioxus::prelude::*;
+use ui::{use_toasts, Toast, ToastLevel};
+
+/// Extension an image of MIME type `mime` is saved with, or `None` if
+/// `mime` isn't an image type.
+pub fn image_extension(mime: &str) -> Option<&str> {
+    match mime.strip_prefix("image/")? {
+        "" => None,
+        "jpeg" => Some("jpg"),
+        "svg+xml" => Some("svg"),
+        subtype => Some(subtype),
+    }
+}
+
+/// Name a pasted image of MIME type `mime` is uploaded as, e.g.
+/// `pasted-1760550000000.png`.
+pub fn pasted_image_name(mime: &str, timestamp_ms: u64) -> Option<String> {
+    Some(format!("pasted-{timestamp_ms}.{}", image_extension(mime)?))
+}
+
+/// An `onpaste` handler uploading a pasted image into the directory `dir`
+/// returns and calling `oninsert` with the uploaded file's path.
+///
+/// Text and other pastes aren't intercepted. Failed uploads are reported
+/// as toasts.
+pub fn use_image_paste(
+    dir: impl Fn() -> String + 'static,
+    oninsert: Callback<String>,
+) -> Callback<Event<ClipboardData>> {
+    let mut toasts = use_toasts();
+
+    use_callback(move |evt: Event<ClipboardData>| {
+        // Browsers that don't expose the clipboard to the page (an insecure
+        // context, or clipboard access denied) have no file here, and the
+        // paste is left to them.
+        let Some(file) = browser::pasted_file(&evt.data(), |mime| image_extension(mime).is_some())
+        else {
+            return;
+        };
+        let Some(name) = pasted_image_name(&file.mime(), browser::now() as u64) else {
+            return;
+        };
+        evt.prevent_default();
+
+        let dir = dir();
+        spawn(async move {
+            let Some(bytes) = file.read().await else {
+                toasts.push(Toast::new(
+                    ToastLevel::Warning,
+                    "The pasted image couldn't be read",
+                ));
+                return;
+            };
+            match api::upload_file(dir, name, bytes).await {
+                Ok(path) => oninsert.call(path),
+                Err(err) => toasts.error(&err),
+            }
+        });
+    })
+}
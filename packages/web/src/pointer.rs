@@ -0,0 +1,215 @@
+//! Document-level pointer tracking, used to keep a gesture alive when
+//! pointer capture isn't available, and cancelling gestures with Escape.
+
+#[cfg(feature = "web")]
+pub use web::*;
+
+#[cfg(not(feature = "web"))]
+pub use inert::*;
+
+/// Which pointer a gesture follows, and how its moves reach it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PointerGesture {
+    #[default]
+    Idle,
+    /// The element captured the pointer, so its own events follow it.
+    Captured(i32),
+    /// Capture was refused or taken away; the pointer is followed by
+    /// [`DocumentPointerListeners`] instead.
+    Fallback(i32),
+}
+
+impl PointerGesture {
+    /// A gesture following `pointer_id`, through capture if the element got it.
+    pub fn start(pointer_id: i32, captured: bool) -> Self {
+        if captured {
+            Self::Captured(pointer_id)
+        } else {
+            Self::Fallback(pointer_id)
+        }
+    }
+
+    /// Whether events from `pointer_id` belong to this gesture.
+    pub fn follows(self, pointer_id: i32) -> bool {
+        self.pointer() == Some(pointer_id)
+    }
+
+    pub fn pointer(self) -> Option<i32> {
+        match self {
+            Self::Idle => None,
+            Self::Captured(pointer_id) | Self::Fallback(pointer_id) => Some(pointer_id),
+        }
+    }
+
+    /// The gesture after the element lost capture. Capture is also lost on
+    /// release, once the gesture has ended; losing it mid-gesture means the
+    /// browser or an iframe took it, so the gesture falls back to the
+    /// document.
+    pub fn capture_lost(self) -> Self {
+        match self {
+            Self::Captured(pointer_id) => Self::Fallback(pointer_id),
+            other => other,
+        }
+    }
+}
+
+#[cfg(feature = "web")]
+mod web {
+    use wasm_bindgen::{closure::Closure, JsCast};
+    use web_sys::{Document, KeyboardEvent, PointerEvent};
+
+    type Listener = Closure<dyn FnMut(PointerEvent)>;
+
+    /// `pointermove`/`pointerup`/`pointercancel` listeners on the document,
+    /// filtered to a single pointer. They're removed when this is dropped.
+    pub struct DocumentPointerListeners {
+        document: Document,
+        listeners: Vec<(&'static str, Listener)>,
+    }
+
+    impl DocumentPointerListeners {
+        /// Calls `onmove` with the client coordinates of every move of
+        /// `pointer_id`, and `onend` once it's released or cancelled.
+        pub fn attach(
+            pointer_id: i32,
+            mut onmove: impl FnMut((f64, f64)) + 'static,
+            onend: impl FnMut() + Clone + 'static,
+        ) -> Option<Self> {
+            let document = web_sys::window()?.document()?;
+
+            let pointermove = Listener::new(move |evt: PointerEvent| {
+                if evt.pointer_id() == pointer_id {
+                    onmove((evt.client_x() as f64, evt.client_y() as f64));
+                }
+            });
+            let end = |mut onend: Box<dyn FnMut()>| {
+                Listener::new(move |evt: PointerEvent| {
+                    if evt.pointer_id() == pointer_id {
+                        onend();
+                    }
+                })
+            };
+            let pointerup = end(Box::new(onend.clone()));
+            let pointercancel = end(Box::new(onend));
+
+            let attached = Self {
+                document,
+                listeners: vec![
+                    ("pointermove", pointermove),
+                    ("pointerup", pointerup),
+                    ("pointercancel", pointercancel),
+                ],
+            };
+            // On failure `attached` is dropped, detaching whatever got attached.
+            for (event, listener) in &attached.listeners {
+                attached
+                    .document
+                    .add_event_listener_with_callback(event, listener.as_ref().unchecked_ref())
+                    .ok()?;
+            }
+
+            Some(attached)
+        }
+    }
+
+    impl Drop for DocumentPointerListeners {
+        fn drop(&mut self) {
+            for (event, listener) in &self.listeners {
+                let _ = self
+                    .document
+                    .remove_event_listener_with_callback(event, listener.as_ref().unchecked_ref());
+            }
+        }
+    }
+
+    /// `keydown` listener on the document calling `oncancel` when Escape is
+    /// pressed, wherever focus is. It's removed when this is dropped.
+    pub struct DocumentEscapeListener {
+        document: Document,
+        listener: Closure<dyn FnMut(KeyboardEvent)>,
+    }
+
+    impl DocumentEscapeListener {
+        pub fn attach(mut oncancel: impl FnMut() + 'static) -> Option<Self> {
+            let document = web_sys::window()?.document()?;
+            let listener = Closure::<dyn FnMut(KeyboardEvent)>::new(move |evt: KeyboardEvent| {
+                if evt.key() == "Escape" {
+                    evt.prevent_default();
+                    evt.stop_propagation();
+                    oncancel();
+                }
+            });
+            // Capture phase, so it runs before handlers inside the document
+            // (such as the Alt+Tab switcher's) see the key.
+            document
+                .add_event_listener_with_callback_and_bool(
+                    "keydown",
+                    listener.as_ref().unchecked_ref(),
+                    true,
+                )
+                .ok()?;
+            Some(Self { document, listener })
+        }
+    }
+
+    impl Drop for DocumentEscapeListener {
+        fn drop(&mut self) {
+            let _ = self.document.remove_event_listener_with_callback_and_bool(
+                "keydown",
+                self.listener.as_ref().unchecked_ref(),
+                true,
+            );
+        }
+    }
+}
+
+/// Builds without the `web` feature have no document to listen on.
+#[cfg(not(feature = "web"))]
+mod inert {
+    pub struct DocumentPointerListeners;
+
+    impl DocumentPointerListeners {
+        pub fn attach(
+            _pointer_id: i32,
+            _onmove: impl FnMut((f64, f64)) + 'static,
+            _onend: impl FnMut() + Clone + 'static,
+        ) -> Option<Self> {
+            None
+        }
+    }
+
+    pub struct DocumentEscapeListener;
+
+    impl DocumentEscapeListener {
+        pub fn attach(_oncancel: impl FnMut() + 'static) -> Option<Self> {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gestures_follow_only_their_own_pointer() {
+        assert!(!PointerGesture::Idle.follows(1));
+        assert!(PointerGesture::start(1, true).follows(1));
+        assert!(!PointerGesture::start(1, true).follows(2));
+        assert!(PointerGesture::start(1, false).follows(1));
+    }
+
+    #[test]
+    fn gestures_without_capture_fall_back_to_the_document() {
+        assert_eq!(PointerGesture::start(1, true), PointerGesture::Captured(1));
+        assert_eq!(PointerGesture::start(1, false), PointerGesture::Fallback(1));
+    }
+
+    #[test]
+    fn losing_capture_mid_gesture_falls_back_and_after_it_does_nothing() {
+        let gesture = PointerGesture::start(3, true).capture_lost();
+        assert_eq!(gesture, PointerGesture::Fallback(3));
+        assert_eq!(gesture.capture_lost(), PointerGesture::Fallback(3));
+        assert_eq!(PointerGesture::Idle.capture_lost(), PointerGesture::Idle);
+    }
+}
@@ -0,0 +1,276 @@
+//! Resizing without moving: grips around a box that change its size while
+//! layout keeps deciding where it sits, for sidebars, preview panes and the
+//! like. `Movable` uses the same grips and geometry for its frame.
+
+use crate::browser;
+use crate::geometry::{resize_rect, ResizeHandle, SizeConstraints};
+use crate::hooks::use_frame_throttle;
+use crate::pointer::{DocumentEscapeListener, DocumentPointerListeners};
+use dioxus::html::input_data::MouseButton;
+use dioxus::prelude::*;
+use std::rc::Rc;
+
+/// Invisible grips on the edges and corners listed in `handles`, calling
+/// `onstart` when a resize starts from one. The parent must be positioned.
+#[component]
+pub fn ResizeGrips(
+    handles: Vec<ResizeHandle>,
+    onstart: EventHandler<(ResizeHandle, Event<PointerData>)>,
+) -> Element {
+    rsx! {
+        for handle in handles {
+            div {
+                key: "{handle:?}",
+                style: "position:absolute; {handle.style()}",
+                onpointerdown: move |evt| onstart.call((handle, evt)),
+            }
+        }
+    }
+}
+
+/// Size of a box that was `size_origin` when a resize from `handle` started
+/// at `click_origin`, with the pointer now at `mouse`.
+fn resized(
+    handle: ResizeHandle,
+    size_origin: (f64, f64),
+    click_origin: (f64, f64),
+    mouse: (f64, f64),
+    constraints: SizeConstraints,
+) -> (f64, f64) {
+    let delta = (mouse.0 - click_origin.0, mouse.1 - click_origin.1);
+    // Position is left to layout, so only the size is used.
+    let (_, size) = resize_rect(handle, (0.0, 0.0), size_origin, delta, constraints);
+    size
+}
+
+/// Inline style fixing the axes of `size` that one of `handles` changes,
+/// leaving the others to layout.
+fn size_style(size: Option<(f64, f64)>, handles: &[ResizeHandle]) -> String {
+    let mut style = String::new();
+    if let Some((width, height)) = size {
+        if handles.iter().any(|handle| handle.horizontal() != 0.0) {
+            style.push_str(&format!("width:{width}px;"));
+        }
+        if handles.iter().any(|handle| handle.vertical() != 0.0) {
+            style.push_str(&format!(" height:{height}px;"));
+        }
+    }
+    style
+}
+
+/// A box whose size the user changes by dragging the grips in `handles`.
+/// Only the axes those grips change get a fixed size.
+///
+/// Only the size is managed: dragging the left or top edge grows the box
+/// the same way as the opposite edge would, and the surrounding layout
+/// places it. Escape cancels a resize in progress.
+#[component]
+pub fn Resizable(
+    /// Starting size; `None` follows the content until the first resize.
+    #[props(default)]
+    size: Option<(f64, f64)>,
+    #[props(default = (40.0, 40.0))] min_size: (f64, f64),
+    #[props(default)] max_size: Option<(f64, f64)>,
+    /// Width divided by height, kept while resizing.
+    #[props(default)]
+    aspect_ratio: Option<f64>,
+    /// Snaps dimensions to multiples of this many pixels.
+    #[props(default)]
+    grid: Option<f64>,
+    #[props(default = ResizeHandle::ALL.to_vec())] handles: Vec<ResizeHandle>,
+    /// Called with the new size on every change during a resize.
+    #[props(default)]
+    onresize: Option<EventHandler<(f64, f64)>>,
+    #[props(default)] class: String,
+    children: Element,
+) -> Element {
+    let mut current = use_signal(|| size);
+    let mut mounted = use_signal(|| None::<Rc<MountedData>>);
+    let mut resizing = use_signal(|| None::<ResizeHandle>);
+    let mut click_origin = use_signal(|| (0.0, 0.0));
+    let mut size_origin = use_signal(|| (0.0, 0.0));
+    let mut origin_size = use_signal(|| None::<(f64, f64)>);
+    let mut listeners = use_signal(|| None::<Rc<DocumentPointerListeners>>);
+    let mut escape = use_signal(|| None::<Rc<DocumentEscapeListener>>);
+
+    let constraints = SizeConstraints {
+        min: min_size,
+        max: max_size,
+        grid,
+        increment: None,
+        aspect_ratio,
+    };
+
+    let track = move |mouse: (f64, f64)| {
+        let Some(handle) = resizing() else {
+            return;
+        };
+        let new_size = resized(handle, size_origin(), click_origin(), mouse, constraints);
+        if current() != Some(new_size) {
+            current.set(Some(new_size));
+            if let Some(onresize) = onresize {
+                onresize.call(new_size);
+            }
+        }
+    };
+
+    // At most one resize per animation frame; the last one is applied right
+    // away on release.
+    let mut frames = use_frame_throttle(track);
+
+    // The listeners may be the ones calling us, so they're detached once the
+    // current event has been handled.
+    let mut end_resize = move || {
+        frames.flush();
+        resizing.set(None);
+        spawn(async move {
+            listeners.set(None);
+            escape.set(None);
+        });
+    };
+
+    let start_resize = move |(handle, evt): (ResizeHandle, Event<PointerData>)| {
+        if evt.data.trigger_button() != Some(MouseButton::Primary) {
+            return;
+        }
+        evt.prevent_default();
+        evt.stop_propagation();
+        let Some(measured) = mounted.read().as_deref().and_then(browser::mounted_size) else {
+            return;
+        };
+
+        let coords = evt.data.client_coordinates();
+        click_origin.set((coords.x, coords.y));
+        size_origin.set(current().unwrap_or(measured));
+        origin_size.set(current());
+        resizing.set(Some(handle));
+
+        let pointer_id = evt.data.pointer_id();
+        let attached = DocumentPointerListeners::attach(
+            pointer_id,
+            move |mouse| frames.push(mouse),
+            end_resize,
+        );
+        listeners.set(attached.map(Rc::new));
+        let attached = DocumentEscapeListener::attach(move || {
+            frames.cancel();
+            current.set(origin_size());
+            if let (Some(onresize), Some(size)) = (onresize, origin_size()) {
+                onresize.call(size);
+            }
+            end_resize();
+        });
+        escape.set(attached.map(Rc::new));
+    };
+
+    let size_style = size_style(current(), &handles);
+    let select = if resizing().is_some() {
+        "select-none"
+    } else {
+        ""
+    };
+
+    rsx! {
+        div {
+            class: "relative {select} {class}",
+            style: size_style,
+            onmounted: move |evt| mounted.set(Some(evt.data())),
+            {children}
+            ResizeGrips { handles, onstart: start_resize }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn constraints() -> SizeConstraints {
+        SizeConstraints {
+            min: (100.0, 50.0),
+            max: Some((400.0, 300.0)),
+            grid: None,
+            increment: None,
+            aspect_ratio: None,
+        }
+    }
+
+    #[test]
+    fn dragging_either_edge_outwards_grows_the_box() {
+        let size = (200.0, 100.0);
+        let start = (500.0, 500.0);
+        assert_eq!(
+            resized(
+                ResizeHandle::East,
+                size,
+                start,
+                (550.0, 520.0),
+                constraints()
+            ),
+            (250.0, 100.0)
+        );
+        assert_eq!(
+            resized(
+                ResizeHandle::West,
+                size,
+                start,
+                (450.0, 520.0),
+                constraints()
+            ),
+            (250.0, 100.0)
+        );
+        assert_eq!(
+            resized(
+                ResizeHandle::SouthEast,
+                size,
+                start,
+                (520.0, 530.0),
+                constraints()
+            ),
+            (220.0, 130.0)
+        );
+    }
+
+    #[test]
+    fn resizes_stay_within_the_size_limits() {
+        let size = (200.0, 100.0);
+        let start = (500.0, 500.0);
+        assert_eq!(
+            resized(
+                ResizeHandle::East,
+                size,
+                start,
+                (900.0, 500.0),
+                constraints()
+            ),
+            (400.0, 100.0)
+        );
+        assert_eq!(
+            resized(
+                ResizeHandle::North,
+                size,
+                start,
+                (500.0, 600.0),
+                constraints()
+            ),
+            (200.0, 50.0)
+        );
+    }
+
+    #[test]
+    fn only_axes_with_a_grip_get_a_fixed_size() {
+        assert_eq!(size_style(None, &ResizeHandle::ALL), "");
+        assert_eq!(
+            size_style(Some((260.0, 0.0)), &[ResizeHandle::West]),
+            "width:260px;"
+        );
+        assert_eq!(
+            size_style(Some((260.0, 120.0)), &[ResizeHandle::South]),
+            " height:120px;"
+        );
+        assert_eq!(
+            size_style(Some((260.0, 120.0)), &ResizeHandle::ALL),
+            "width:260px; height:120px;"
+        );
+    }
+}
@@ -0,0 +1,298 @@
+//! Calling server functions from components, with retries on transient
+//! failures and optional caching by input.
+//!
+//! ```rust,ignore
+//! let mut path = use_signal(|| "photos/cat.png".to_string());
+//! let thumbnail = use_server_fn(
+//!     move || (path(), 128),
+//!     |(path, max_dim)| api::thumbnail(path, max_dim),
+//!     ServerFnOptions::default(),
+//! );
+//!
+//! match (thumbnail.data(), thumbnail.error()) {
+//!     (Some(bytes), _) => rsx! { Image { bytes } },
+//!     (None, Some(err)) => rsx! { "{err}" },
+//!     (None, None) => rsx! { Spinner {} },
+//! }
+//! ```
+
+use crate::browser;
+use dioxus::prelude::*;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ServerFnOptions {
+    /// Extra attempts after a transient failure.
+    pub retries: u32,
+    /// Delay before the first retry, doubled for each following one.
+    pub backoff_ms: u32,
+    /// Reuse the result of a previous successful call with the same input.
+    pub cache: bool,
+}
+
+impl Default for ServerFnOptions {
+    fn default() -> Self {
+        Self {
+            retries: 2,
+            backoff_ms: 250,
+            cache: false,
+        }
+    }
+}
+
+/// Whether `err` is worth retrying: the request didn't get through, or a
+/// gateway in front of the server failed.
+pub fn is_transient(err: &ServerFnError) -> bool {
+    match err {
+        ServerFnError::Request(_) => true,
+        ServerFnError::ServerError { code, .. } => matches!(code, 502..=504),
+        _ => false,
+    }
+}
+
+/// Runs `call(input)`, retrying transient failures with exponential backoff.
+pub async fn call_with_retry<I, O, Fut>(
+    call: impl Fn(I) -> Fut,
+    input: I,
+    options: ServerFnOptions,
+) -> Result<O, ServerFnError>
+where
+    I: Clone,
+    Fut: Future<Output = Result<O, ServerFnError>>,
+{
+    retry_with(call, input, options, browser::sleep).await
+}
+
+/// [`call_with_retry`] waiting out each backoff with `sleep`, so tests can
+/// stand in for the browser timer.
+async fn retry_with<I, O, Fut, Sleep>(
+    call: impl Fn(I) -> Fut,
+    input: I,
+    options: ServerFnOptions,
+    sleep: impl Fn(u32) -> Sleep,
+) -> Result<O, ServerFnError>
+where
+    I: Clone,
+    Fut: Future<Output = Result<O, ServerFnError>>,
+    Sleep: Future<Output = ()>,
+{
+    let mut attempt = 0;
+    loop {
+        match call(input.clone()).await {
+            Err(err) if attempt < options.retries && is_transient(&err) => {
+                sleep(options.backoff_ms.saturating_mul(1 << attempt.min(16))).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// State of a server function call made by [`use_server_fn`].
+pub struct ServerFn<O: 'static> {
+    resource: Resource<Result<O, ServerFnError>>,
+    bypass_cache: Signal<bool>,
+}
+
+impl<O: 'static> Clone for ServerFn<O> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<O: 'static> Copy for ServerFn<O> {}
+
+impl<O: Clone + 'static> ServerFn<O> {
+    /// Result of the last successful call.
+    pub fn data(&self) -> Option<O> {
+        self.resource
+            .read()
+            .as_ref()
+            .and_then(|result| result.as_ref().ok().cloned())
+    }
+
+    /// Error of the last call, once retries were exhausted.
+    pub fn error(&self) -> Option<ServerFnError> {
+        self.resource
+            .read()
+            .as_ref()
+            .and_then(|result| result.as_ref().err().cloned())
+    }
+
+    pub fn loading(&self) -> bool {
+        self.resource.pending()
+    }
+
+    /// Calls the server function again, ignoring any cached result.
+    pub fn refetch(&mut self) {
+        self.bypass_cache.set(true);
+        self.resource.restart();
+    }
+}
+
+/// Calls `call` with the value of `input`, again whenever the signals `input`
+/// reads change.
+pub fn use_server_fn<I, O, Fut>(
+    input: impl Fn() -> I + 'static,
+    call: impl Fn(I) -> Fut + Copy + 'static,
+    options: ServerFnOptions,
+) -> ServerFn<O>
+where
+    I: Clone + Eq + Hash + 'static,
+    O: Clone + 'static,
+    Fut: Future<Output = Result<O, ServerFnError>> + 'static,
+{
+    let mut cache = use_signal(HashMap::<I, O>::new);
+    let mut bypass_cache = use_signal(|| false);
+
+    let resource = use_resource(move || {
+        let input = input();
+        async move {
+            let bypass = std::mem::take(&mut *bypass_cache.write());
+            if options.cache && !bypass {
+                if let Some(hit) = cache.peek().get(&input) {
+                    return Ok(hit.clone());
+                }
+            }
+
+            let result = call_with_retry(call, input.clone(), options).await;
+            if let (true, Ok(output)) = (options.cache, &result) {
+                cache.write().insert(input, output.clone());
+            }
+            result
+        }
+    });
+
+    ServerFn {
+        resource,
+        bypass_cache,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+    use std::future::{ready, Ready};
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+
+    /// Polls `future` once; the fakes below never make it wait.
+    fn now<T>(future: impl Future<Output = T>) -> T {
+        match pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("future is still pending"),
+        }
+    }
+
+    fn unreachable_gateway() -> ServerFnError {
+        ServerFnError::ServerError {
+            message: "bad gateway".to_string(),
+            code: 502,
+            details: None,
+        }
+    }
+
+    #[test]
+    fn transient_errors_are_retried_with_backoff() {
+        let calls = Cell::new(0);
+        let call = |input: u32| {
+            calls.set(calls.get() + 1);
+            ready(match calls.get() {
+                1 | 2 => Err(unreachable_gateway()),
+                _ => Ok(input * 2),
+            })
+        };
+        let delays = RefCell::new(Vec::new());
+        let sleep = |ms| {
+            delays.borrow_mut().push(ms);
+            ready(())
+        };
+        let options = ServerFnOptions::default();
+
+        assert_eq!(now(retry_with(call, 21, options, sleep)), Ok(42));
+        assert_eq!(calls.get(), 3);
+        assert_eq!(*delays.borrow(), [250, 500]);
+
+        // Out of retries, the last error is returned.
+        calls.set(0);
+        let once = ServerFnOptions {
+            retries: 1,
+            ..options
+        };
+        assert_eq!(
+            now(retry_with(call, 21, once, sleep)),
+            Err(unreachable_gateway())
+        );
+        assert_eq!(calls.get(), 2);
+
+        // Other errors aren't retried.
+        let rejected = || ServerFnError::ServerError {
+            message: "no such file".to_string(),
+            code: 404,
+            details: None,
+        };
+        calls.set(0);
+        let call = |_: u32| {
+            calls.set(calls.get() + 1);
+            ready(Err::<u32, _>(rejected()))
+        };
+        assert_eq!(now(retry_with(call, 21, options, sleep)), Err(rejected()));
+        assert_eq!(calls.get(), 1);
+    }
+
+    thread_local! {
+        /// Inputs the server function was called with.
+        static CALLS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn shout(input: String) -> Ready<Result<String, ServerFnError>> {
+        CALLS.with(|calls| calls.borrow_mut().push(input.clone()));
+        ready(Ok(input.to_uppercase()))
+    }
+
+    #[component]
+    fn Cached() -> Element {
+        let input = use_context_provider(|| Signal::new("a".to_string()));
+        let options = ServerFnOptions {
+            cache: true,
+            ..ServerFnOptions::default()
+        };
+        let server_fn = use_server_fn(move || input.cloned(), shout, options);
+        use_context_provider(|| server_fn);
+        rsx! {}
+    }
+
+    /// Sets the input to `value` and returns the data it leads to.
+    fn show(dom: &mut VirtualDom, value: &str) -> Option<String> {
+        let mut input: Signal<String> = dom.in_scope(ScopeId::APP, consume_context);
+        dom.in_scope(ScopeId::APP, || input.set(value.to_string()));
+        dom.render_immediate_to_vec();
+        let server_fn: ServerFn<String> = dom.in_scope(ScopeId::APP, consume_context);
+        dom.in_scope(ScopeId::APP, || server_fn.data())
+    }
+
+    #[test]
+    fn cached_results_are_reused_until_refetched() {
+        let mut dom = VirtualDom::new(Cached);
+        dom.rebuild_in_place();
+        dom.render_immediate_to_vec();
+        let calls = || CALLS.with(|calls| calls.borrow().clone());
+
+        assert_eq!(show(&mut dom, "b"), Some("B".to_string()));
+        // Back to an input seen before: answered from the cache.
+        assert_eq!(show(&mut dom, "a"), Some("A".to_string()));
+        assert_eq!(calls(), ["a", "b"]);
+
+        // Refetching calls the server again despite the cached result...
+        let mut server_fn: ServerFn<String> = dom.in_scope(ScopeId::APP, consume_context);
+        dom.in_scope(ScopeId::APP, || server_fn.refetch());
+        dom.render_immediate_to_vec();
+        assert_eq!(calls(), ["a", "b", "a"]);
+        // ...and only that once.
+        assert_eq!(show(&mut dom, "b"), Some("B".to_string()));
+        assert_eq!(calls(), ["a", "b", "a"]);
+    }
+}
@@ -0,0 +1,283 @@
+//! Saving the open windows to `localStorage` and restoring them on the next
+//! load.
+//!
+//! A session records each window's app id, title and [`WindowState`], plus
+//! whatever internal state the app chose to persist with
+//! [`use_session_state`], and the order of the taskbar. Windows of apps that
+//! no longer exist are skipped on restore.
+
+use crate::browser::{self, WindowListener};
+use crate::hooks::use_debounce;
+use crate::window_manager::{use_window_manager, WindowId, WindowManager, WindowState};
+use dioxus::logger::tracing;
+use dioxus::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+const SESSION_KEY: &str = "jadeos.session";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedWindow {
+    pub app_id: String,
+    pub title: String,
+    pub state: WindowState,
+    pub app_state: Option<Value>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    /// Least recently focused first, so reopening them in order leaves the
+    /// previously focused window on top.
+    pub windows: Vec<SavedWindow>,
+    /// Indices into `windows` in taskbar order.
+    #[serde(default)]
+    pub taskbar: Vec<usize>,
+}
+
+type Saver = Rc<dyn Fn() -> Option<Value>>;
+
+/// Per-window hooks into the session, provided by [`use_session`].
+#[derive(Clone, Copy)]
+pub struct SessionRegistry {
+    savers: Signal<HashMap<WindowId, Saver>>,
+    /// App state read from the stored session, waiting for its window's app
+    /// to mount and claim it.
+    pending: Signal<HashMap<WindowId, Value>>,
+}
+
+impl SessionRegistry {
+    fn new() -> Self {
+        Self {
+            savers: Signal::new(HashMap::new()),
+            pending: Signal::new(HashMap::new()),
+        }
+    }
+}
+
+/// Captures every app window currently open in `manager`.
+pub fn capture_session(manager: &WindowManager, registry: &SessionRegistry) -> Session {
+    let savers = registry.savers.peek();
+    let (ids, windows): (Vec<WindowId>, Vec<SavedWindow>) = manager
+        .mru()
+        .iter()
+        .rev()
+        .filter_map(|&id| {
+            let handle = manager.get(id)?;
+            let saved = SavedWindow {
+                app_id: handle.app_id.clone()?,
+                title: handle.title.clone(),
+                state: manager.state(id),
+                app_state: savers.get(&id).and_then(|save| save()),
+            };
+            Some((id, saved))
+        })
+        .unzip();
+    let taskbar = manager
+        .taskbar()
+        .iter()
+        .filter_map(|id| ids.iter().position(|other| other == id))
+        .collect();
+
+    Session { windows, taskbar }
+}
+
+/// Reopens the windows of `session` whose app is in `known_apps`, returning
+/// the app state each new window should start from.
+pub fn apply_session(
+    manager: &mut WindowManager,
+    session: Session,
+    known_apps: &[&str],
+) -> HashMap<WindowId, Value> {
+    let mut pending = HashMap::new();
+    let mut opened = Vec::new();
+    for saved in session.windows {
+        if !known_apps.contains(&saved.app_id.as_str()) {
+            tracing::warn!("skipping saved window of unknown app `{}`", saved.app_id);
+            opened.push(None);
+            continue;
+        }
+        let handle = manager.open_app(saved.app_id, saved.title, saved.state);
+        if let Some(app_state) = saved.app_state {
+            pending.insert(handle.id, app_state);
+        }
+        opened.push(Some(handle.id));
+    }
+
+    let taskbar: Vec<WindowId> = session
+        .taskbar
+        .iter()
+        .filter_map(|&index| opened.get(index).copied().flatten())
+        .collect();
+    manager.set_taskbar_order(&taskbar);
+    pending
+}
+
+pub fn save_session(manager: &WindowManager, registry: &SessionRegistry) {
+    let session = capture_session(manager, registry);
+    match serde_json::to_string(&session) {
+        Ok(json) => browser::store(SESSION_KEY, &json),
+        Err(err) => tracing::warn!("failed to serialize session: {err}"),
+    }
+}
+
+/// Restores the stored session into `manager`. Returns `false` when there was
+/// nothing to restore.
+pub fn restore_session(
+    manager: &mut WindowManager,
+    registry: &mut SessionRegistry,
+    known_apps: &[&str],
+) -> bool {
+    let Some(json) = browser::load(SESSION_KEY) else {
+        return false;
+    };
+    let session = match serde_json::from_str::<Session>(&json) {
+        Ok(session) => session,
+        Err(err) => {
+            tracing::warn!("ignoring unreadable session: {err}");
+            return false;
+        }
+    };
+    if session.windows.is_empty() {
+        return false;
+    }
+
+    let pending = apply_session(manager, session, known_apps);
+    registry.pending.write().extend(pending);
+    true
+}
+
+/// Restores the previous session once, then saves the current one shortly
+/// after the windows settle and whenever the page is hidden or unloaded.
+/// Returns whether a session was restored.
+pub fn use_session(known_apps: &'static [&'static str]) -> bool {
+    let mut manager = use_window_manager();
+    let mut registry = use_context_provider(SessionRegistry::new);
+    let restored =
+        use_hook(move || restore_session(&mut manager.write(), &mut registry, known_apps));
+
+    let settled = use_debounce(manager, 500);
+    use_effect(move || {
+        settled.read();
+        save_session(&manager.peek(), &registry);
+    });
+
+    use_hook(move || {
+        WindowListener::attach("pagehide", move || {
+            save_session(&manager.peek(), &registry);
+        })
+        .map(Rc::new)
+    });
+
+    restored
+}
+
+/// Persists an app's internal `state` with the session of window `id`,
+/// restoring it first if the session saved some for this window.
+pub fn use_session_state<T>(id: WindowId, mut state: Signal<T>)
+where
+    T: Serialize + DeserializeOwned + 'static,
+{
+    let mut registry: SessionRegistry = use_context();
+
+    use_hook(move || {
+        if let Some(saved) = registry.pending.write().remove(&id) {
+            match serde_json::from_value::<T>(saved) {
+                Ok(saved) => state.set(saved),
+                Err(err) => tracing::warn!("discarding saved state of window {id}: {err}"),
+            }
+        }
+
+        let save: Saver = Rc::new(move || serde_json::to_value(&*state.peek()).ok());
+        registry.savers.write().insert(id, save);
+    });
+
+    use_drop(move || {
+        registry.savers.write().remove(&id);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs `test` where signals can be created, as inside a component.
+    fn with_runtime(test: impl FnOnce()) {
+        let mut dom = VirtualDom::new(|| rsx! {});
+        dom.rebuild_in_place();
+        dom.in_scope(ScopeId::ROOT, test);
+    }
+
+    fn at(x: f64, y: f64) -> WindowState {
+        WindowState {
+            position: (x, y),
+            size: Some((300.0, 200.0)),
+        }
+    }
+
+    #[test]
+    fn sessions_round_trip_windows_and_app_state() {
+        with_runtime(|| {
+            let mut manager = WindowManager::default();
+            let mut registry = SessionRegistry::new();
+            let notes = manager.open_app("notes", "Notes", at(10.0, 20.0)).id;
+            let files = manager.open_app("files", "Files", at(30.0, 40.0)).id;
+            // Not an app, so not saved.
+            manager.open("Scratch");
+            manager.focus(notes);
+            manager.set_taskbar_order(&[files, notes]);
+            for (id, value) in [(notes, "draft"), (files, "/docs")] {
+                let save: Saver = Rc::new(move || Some(Value::from(value)));
+                registry.savers.write().insert(id, save);
+            }
+
+            let session = capture_session(&manager, &registry);
+            let json = serde_json::to_string(&session).unwrap();
+            let session: Session = serde_json::from_str(&json).unwrap();
+
+            let mut restored = WindowManager::default();
+            let pending = apply_session(&mut restored, session, &["notes", "files"]);
+            let windows: Vec<(&str, WindowState)> = restored
+                .mru()
+                .iter()
+                .map(|&id| {
+                    let handle = restored.get(id).unwrap();
+                    (handle.app_id.as_deref().unwrap(), restored.state(id))
+                })
+                .collect();
+            // Still focused last.
+            assert_eq!(
+                windows,
+                [("notes", at(10.0, 20.0)), ("files", at(30.0, 40.0))]
+            );
+            let [notes, files] = [restored.mru()[0], restored.mru()[1]];
+            assert_eq!(restored.taskbar(), [files, notes]);
+            assert_eq!(pending[&notes], Value::from("draft"));
+            assert_eq!(pending[&files], Value::from("/docs"));
+        });
+    }
+
+    #[test]
+    fn windows_of_unknown_apps_are_skipped() {
+        let saved = |app_id: &str| SavedWindow {
+            app_id: app_id.to_string(),
+            title: app_id.to_string(),
+            state: WindowState::default(),
+            app_state: Some(Value::from(app_id)),
+        };
+        let session = Session {
+            windows: vec![saved("removed"), saved("notes")],
+            taskbar: vec![1, 0],
+        };
+
+        let mut manager = WindowManager::default();
+        let pending = apply_session(&mut manager, session, &["notes"]);
+        assert_eq!(manager.windows().len(), 1);
+        let notes = manager.windows()[0].id;
+        assert_eq!(manager.get(notes).unwrap().app_id.as_deref(), Some("notes"));
+        assert_eq!(manager.taskbar(), [notes]);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[&notes], Value::from("notes"));
+    }
+}
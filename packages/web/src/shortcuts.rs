@@ -0,0 +1,250 @@
+//! Keyboard shortcuts, either global or scoped to a window.
+//!
+//! A shortcut scoped to a window only fires while that window is focused,
+//! and takes precedence over a global shortcut with the same keys, so e.g.
+//! Ctrl+S saves in the focused app rather than triggering a desktop action.
+
+use crate::window_manager::{use_window_manager, WindowId};
+use dioxus::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A key combination such as `Ctrl+Shift+S`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Shortcut {
+    /// The key, lowercased (`"s"`, `"escape"`, `"arrowleft"`).
+    key: String,
+    ctrl: bool,
+    alt: bool,
+    shift: bool,
+    meta: bool,
+}
+
+impl Shortcut {
+    /// Parses `+` separated modifiers followed by a key, e.g. `"Ctrl+S"`.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let mut shortcut = Shortcut {
+            key: String::new(),
+            ctrl: false,
+            alt: false,
+            shift: false,
+            meta: false,
+        };
+        let mut parts = spec.split('+').map(str::trim).peekable();
+        while let Some(part) = parts.next() {
+            if parts.peek().is_none() {
+                shortcut.key = part.to_lowercase();
+                break;
+            }
+            match part.to_lowercase().as_str() {
+                "ctrl" | "control" => shortcut.ctrl = true,
+                "alt" => shortcut.alt = true,
+                "shift" => shortcut.shift = true,
+                "meta" | "super" | "cmd" => shortcut.meta = true,
+                _ => return None,
+            }
+        }
+        (!shortcut.key.is_empty()).then_some(shortcut)
+    }
+
+    /// Whether pressing `key` (as in [`Key`]'s display) with `modifiers`
+    /// triggers the shortcut.
+    fn matches(&self, key: &str, modifiers: Modifiers) -> bool {
+        key.to_lowercase() == self.key
+            && modifiers.ctrl() == self.ctrl
+            && modifiers.alt() == self.alt
+            && modifiers.shift() == self.shift
+            && modifiers.meta() == self.meta
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShortcutScope {
+    Global,
+    /// Only while the window is focused.
+    Window(WindowId),
+}
+
+#[derive(Clone)]
+struct Binding {
+    id: usize,
+    shortcut: Shortcut,
+    scope: ShortcutScope,
+    handler: Rc<RefCell<dyn FnMut()>>,
+}
+
+#[derive(Clone, Copy)]
+pub struct Shortcuts {
+    bindings: Signal<Vec<Binding>>,
+    next_id: Signal<usize>,
+}
+
+impl Shortcuts {
+    fn new() -> Self {
+        Self {
+            bindings: Signal::new(Vec::new()),
+            next_id: Signal::new(0),
+        }
+    }
+
+    pub fn register(
+        &mut self,
+        shortcut: Shortcut,
+        scope: ShortcutScope,
+        handler: impl FnMut() + 'static,
+    ) -> usize {
+        let id = *self.next_id.peek();
+        self.next_id += 1;
+        self.bindings.write().push(Binding {
+            id,
+            shortcut,
+            scope,
+            handler: Rc::new(RefCell::new(handler)),
+        });
+        id
+    }
+
+    pub fn unregister(&mut self, id: usize) {
+        self.bindings.write().retain(|binding| binding.id != id);
+    }
+
+    /// Runs the shortcut matching `evt`, preferring one scoped to the
+    /// `focused` window over a global one. Returns whether one ran.
+    pub fn dispatch(&self, evt: &KeyboardData, focused: Option<WindowId>) -> bool {
+        let key = evt.key().to_string();
+        let handler = resolve(&self.bindings.peek(), &key, evt.modifiers(), focused)
+            .map(|binding| binding.handler.clone());
+
+        match handler {
+            Some(handler) => {
+                (handler.borrow_mut())();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// The binding pressing `key` with `modifiers` runs: the one scoped to the
+/// `focused` window if there is one, otherwise a global one. Bindings of
+/// other windows never run.
+fn resolve<'a>(
+    bindings: &'a [Binding],
+    key: &str,
+    modifiers: Modifiers,
+    focused: Option<WindowId>,
+) -> Option<&'a Binding> {
+    let matching = || {
+        bindings
+            .iter()
+            .filter(|binding| binding.shortcut.matches(key, modifiers))
+    };
+    matching()
+        .find(|binding| matches!(binding.scope, ShortcutScope::Window(id) if Some(id) == focused))
+        .or_else(|| matching().find(|binding| binding.scope == ShortcutScope::Global))
+}
+
+/// The window a component is rendered in, provided by `Movable`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CurrentWindow(pub WindowId);
+
+/// Registers `handler` for `shortcut` in `scope` while the calling component
+/// is mounted.
+///
+/// # Panics
+///
+/// If `shortcut` can't be parsed.
+pub fn use_shortcut(shortcut: &str, scope: ShortcutScope, handler: impl FnMut() + 'static) {
+    let mut shortcuts: Shortcuts = use_context();
+    let shortcut =
+        Shortcut::parse(shortcut).unwrap_or_else(|| panic!("invalid shortcut `{shortcut}`"));
+    let id = use_hook(move || shortcuts.register(shortcut, scope, handler));
+    use_drop(move || shortcuts.unregister(id));
+}
+
+/// Registers a shortcut scoped to the window the calling component is in.
+pub fn use_window_shortcut(shortcut: &str, handler: impl FnMut() + 'static) {
+    let CurrentWindow(id) = use_context();
+    use_shortcut(shortcut, ShortcutScope::Window(id), handler);
+}
+
+/// Provides [`Shortcuts`] and dispatches key presses inside it.
+#[component]
+pub fn ShortcutProvider(children: Element) -> Element {
+    let shortcuts = use_context_provider(Shortcuts::new);
+    let manager = use_window_manager();
+
+    rsx! {
+        div {
+            class: "contents",
+            onkeydown: move |evt: Event<KeyboardData>| {
+                if shortcuts.dispatch(&evt.data, manager.peek().focused()) {
+                    evt.prevent_default();
+                    evt.stop_propagation();
+                }
+            },
+            {children}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Binds `spec` in `scope` to a handler recording `name` in `ran`.
+    fn bind(
+        ran: &Rc<Cell<&'static str>>,
+        id: usize,
+        spec: &str,
+        scope: ShortcutScope,
+        name: &'static str,
+    ) -> Binding {
+        let ran = ran.clone();
+        Binding {
+            id,
+            shortcut: Shortcut::parse(spec).unwrap(),
+            scope,
+            handler: Rc::new(RefCell::new(move || ran.set(name))),
+        }
+    }
+
+    #[test]
+    fn window_shortcuts_run_only_in_the_focused_window() {
+        let ran = Rc::new(Cell::new(""));
+        let bindings = [
+            bind(&ran, 0, "Ctrl+S", ShortcutScope::Global, "desktop"),
+            bind(&ran, 1, "Ctrl+S", ShortcutScope::Window(1), "notes"),
+            bind(&ran, 2, "Ctrl+S", ShortcutScope::Window(2), "editor"),
+        ];
+        let press = |key: &str, modifiers, focused| {
+            ran.set("");
+            let binding = resolve(&bindings, key, modifiers, focused)?;
+            (binding.handler.borrow_mut())();
+            Some(ran.get())
+        };
+
+        assert_eq!(press("s", Modifiers::CONTROL, Some(2)), Some("editor"));
+        assert_eq!(press("S", Modifiers::CONTROL, Some(1)), Some("notes"));
+        // Windows without a binding of their own fall back to the global one.
+        assert_eq!(press("s", Modifiers::CONTROL, Some(3)), Some("desktop"));
+        assert_eq!(press("s", Modifiers::CONTROL, None), Some("desktop"));
+        // Modifiers must match exactly.
+        assert_eq!(
+            press("s", Modifiers::CONTROL | Modifiers::SHIFT, Some(2)),
+            None
+        );
+        assert_eq!(press("s", Modifiers::empty(), Some(2)), None);
+    }
+
+    #[test]
+    fn shortcuts_parse_modifiers_and_key() {
+        let shortcut = Shortcut::parse("Ctrl+Shift+ArrowLeft").unwrap();
+        assert!(shortcut.matches("ArrowLeft", Modifiers::CONTROL | Modifiers::SHIFT));
+        assert!(!shortcut.matches("ArrowRight", Modifiers::CONTROL | Modifiers::SHIFT));
+        assert_eq!(Shortcut::parse("Super+Escape").unwrap().key, "escape");
+        assert_eq!(Shortcut::parse("Hyper+S"), None);
+        assert_eq!(Shortcut::parse("Ctrl+"), None);
+    }
+}
@@ -0,0 +1,199 @@
+//! Snap layouts: a menu of tiling presets shown when hovering a window's
+//! maximize button, and snap assist offering the other windows for the
+//! slots left free once one window was snapped, plus keyboard tiling.
+
+use crate::desktop_size;
+use crate::geometry::{snap_slot, SnapLayout, TileDirection};
+use crate::shortcuts::{use_shortcut, ShortcutScope};
+use crate::window_manager::{use_window_manager, SnapAssist, WindowId};
+use dioxus::prelude::*;
+
+/// Width of a layout's miniature in the menu, in pixels.
+const MINIATURE_WIDTH: f64 = 72.0;
+
+/// Miniatures of every [`SnapLayout`], each slot a button snapping window
+/// `id` into it.
+#[component]
+pub fn SnapLayoutMenu(id: WindowId, onselect: EventHandler) -> Element {
+    let mut manager = use_window_manager();
+    let Some(desktop) = desktop_size(id) else {
+        return rsx! {};
+    };
+    let miniature = (MINIATURE_WIDTH, MINIATURE_WIDTH * desktop.1 / desktop.0);
+
+    rsx! {
+        div {
+            class: "absolute top-full left-1/2 z-10 mt-2 grid -translate-x-1/2 grid-cols-2 gap-2 rounded-lg bg-white p-2 shadow-lg",
+            role: "menu",
+            aria_label: "Snap layouts",
+            for layout in SnapLayout::ALL {
+                div {
+                    key: "{layout:?}",
+                    class: "relative rounded border border-slate-300",
+                    style: "width:{miniature.0}px; height:{miniature.1}px;",
+                    title: layout.name(),
+                    for slot in 0..layout.slots().len() {
+                        if let Some(((left, top), (width, height))) = snap_slot(layout, slot, miniature) {
+                            button {
+                                key: "{slot}",
+                                class: "absolute rounded-sm border border-white bg-slate-300 hover:bg-[var(--jade-accent)]",
+                                style: "left:{left}px; top:{top}px; width:{width}px; height:{height}px;",
+                                role: "menuitem",
+                                aria_label: "{layout.name()}, slot {slot + 1}",
+                                onclick: move |evt| {
+                                    evt.stop_propagation();
+                                    manager.write().snap(id, layout, slot, desktop);
+                                    onselect.call(());
+                                },
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The free slot of `assist` containing `point`, in desktop coordinates.
+/// Slots share their edges; a point on one belongs to the slot right or
+/// below of it.
+fn free_slot_at(assist: &SnapAssist, point: (f64, f64)) -> Option<usize> {
+    assist.free.iter().copied().find(|&slot| {
+        snap_slot(assist.layout, slot, assist.desktop).is_some_and(
+            |((left, top), (width, height))| {
+                (left..left + width).contains(&point.0) && (top..top + height).contains(&point.1)
+            },
+        )
+    })
+}
+
+/// Outlines of the free slots of the active snap assist, each listing the
+/// windows that can be snapped into it. Clicking outside the free slots or
+/// pressing Escape dismisses it.
+#[component]
+pub fn SnapAssistOverlay() -> Element {
+    let mut manager = use_window_manager();
+    let Some(assist) = manager.read().snap_assist().cloned() else {
+        return rsx! {};
+    };
+    let candidates: Vec<(WindowId, String)> = {
+        let manager = manager.read();
+        manager
+            .snap_candidates()
+            .into_iter()
+            .filter_map(|id| Some((id, manager.get(id)?.title.clone())))
+            .collect()
+    };
+
+    rsx! {
+        div {
+            class: "absolute inset-0 bg-slate-900/20",
+            style: "z-index: 9500;",
+            tabindex: 0,
+            onmounted: move |evt| async move {
+                let _ = evt.data().set_focus(true).await;
+            },
+            // The overlay covers the desktop, so its coordinates are the
+            // desktop's.
+            onclick: move |evt| {
+                let point = evt.element_coordinates();
+                if free_slot_at(&assist, (point.x, point.y)).is_none() {
+                    manager.write().dismiss_snap_assist();
+                }
+            },
+            onkeydown: move |evt| {
+                if evt.key() == Key::Escape {
+                    manager.write().dismiss_snap_assist();
+                }
+            },
+            for slot in assist.free.iter().copied() {
+                if let Some(((left, top), (width, height))) = snap_slot(assist.layout, slot, assist.desktop) {
+                    div {
+                        key: "{slot}",
+                        class: "absolute flex flex-wrap content-start gap-2 overflow-auto rounded-lg border-2 border-[var(--jade-accent)] bg-white/70 p-3",
+                        style: "left:{left + 4.0}px; top:{top + 4.0}px; width:{width - 8.0}px; height:{height - 8.0}px;",
+                        for (id, title) in candidates.iter().cloned() {
+                            button {
+                                key: "{id}",
+                                class: "max-w-48 truncate rounded-md bg-white px-3 py-2 text-sm text-slate-700 shadow hover:ring-2 hover:ring-[var(--jade-accent)]",
+                                onclick: move |evt| {
+                                    evt.stop_propagation();
+                                    manager.write().snap(id, assist.layout, slot, assist.desktop);
+                                },
+                                "{title}"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Super+Left / Super+Right tile the focused window through the column
+/// slots (see [`WindowManager::tile`]); Super+Up maximizes it.
+///
+/// [`WindowManager::tile`]: crate::window_manager::WindowManager::tile
+#[component]
+pub fn TilingShortcuts() -> Element {
+    let mut manager = use_window_manager();
+    // The focused window with the size of the desktop it's on.
+    let focused = move || {
+        let id = {
+            let manager = manager.peek();
+            manager.focused().filter(|&id| manager.is_visible(id))?
+        };
+        Some((id, desktop_size(id)?))
+    };
+    use_shortcut("Super+ArrowLeft", ShortcutScope::Global, move || {
+        if let Some((id, desktop)) = focused() {
+            manager.write().tile(id, TileDirection::Left, desktop);
+        }
+    });
+    use_shortcut("Super+ArrowRight", ShortcutScope::Global, move || {
+        if let Some((id, desktop)) = focused() {
+            manager.write().tile(id, TileDirection::Right, desktop);
+        }
+    });
+    use_shortcut("Super+ArrowUp", ShortcutScope::Global, move || {
+        if let Some((id, desktop)) = focused() {
+            manager.write().maximize(id, desktop);
+        }
+    });
+
+    rsx! {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::SnapLayout;
+
+    fn assist(layout: SnapLayout, free: Vec<usize>) -> SnapAssist {
+        SnapAssist {
+            layout,
+            desktop: (1200.0, 800.0),
+            free,
+            snapped: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn points_hit_the_free_slot_they_fall_in() {
+        let grid = assist(SnapLayout::Grid, vec![1, 2, 3]);
+        assert_eq!(free_slot_at(&grid, (900.0, 100.0)), Some(1));
+        assert_eq!(free_slot_at(&grid, (100.0, 700.0)), Some(2));
+        assert_eq!(free_slot_at(&grid, (900.0, 700.0)), Some(3));
+        // Shared edges belong to the slot right or below.
+        assert_eq!(free_slot_at(&grid, (600.0, 400.0)), Some(3));
+    }
+
+    #[test]
+    fn taken_slots_and_points_off_the_desktop_hit_nothing() {
+        let thirds = assist(SnapLayout::Thirds, vec![1, 2]);
+        assert_eq!(free_slot_at(&thirds, (100.0, 400.0)), None);
+        assert_eq!(free_slot_at(&thirds, (500.0, 400.0)), Some(1));
+        assert_eq!(free_slot_at(&thirds, (1200.0, 400.0)), None);
+        assert_eq!(free_slot_at(&thirds, (500.0, -1.0)), None);
+    }
+}
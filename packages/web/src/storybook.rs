@@ -0,0 +1,510 @@
+//! Component harness for working on the component library: every shared
+//! component in its variants, with controls for its props.
+//!
+//! Only built with the `storybook` feature, which adds it to the desktop as
+//! the `components` app. Production builds leave the feature off.
+
+use crate::animated_number::AnimatedNumber;
+use crate::code_block::CodeBlock;
+use crate::collapsible::Collapsible;
+use crate::details::format_bytes;
+use crate::geometry::ResizeHandle;
+use crate::resizable::Resizable;
+use crate::typography::Typography;
+use dioxus::prelude::*;
+use ui::{
+    Button, Card, CardBody, CardFooter, CardHeader, ColorPicker, Modal, NumberInput, Popover,
+    Spinner,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Story {
+    Card,
+    Typography,
+    Button,
+    Spinner,
+    AnimatedNumber,
+    ColorPicker,
+    NumberInput,
+    Popover,
+    Modal,
+    CodeBlock,
+    Resizable,
+    Collapsible,
+}
+
+impl Story {
+    const ALL: [Story; 12] = [
+        Story::Card,
+        Story::Typography,
+        Story::Button,
+        Story::Spinner,
+        Story::AnimatedNumber,
+        Story::ColorPicker,
+        Story::NumberInput,
+        Story::Popover,
+        Story::Modal,
+        Story::CodeBlock,
+        Story::Resizable,
+        Story::Collapsible,
+    ];
+}
+
+/// A labelled text field editing `value`.
+#[component]
+fn TextControl(label: String, value: Signal<String>) -> Element {
+    rsx! {
+        label { class: "flex items-center justify-between gap-3",
+            span { "{label}" }
+            input {
+                class: "w-48 rounded border border-slate-300 px-1 font-mono text-xs",
+                value: "{value}",
+                oninput: move |evt| value.set(evt.value()),
+            }
+        }
+    }
+}
+
+#[component]
+fn BoolControl(label: String, value: Signal<bool>) -> Element {
+    rsx! {
+        label { class: "flex items-center justify-between gap-3",
+            span { "{label}" }
+            input {
+                r#type: "checkbox",
+                checked: value(),
+                onchange: move |evt| value.set(evt.checked()),
+            }
+        }
+    }
+}
+
+/// A select picking `value` among `options`.
+#[component]
+fn ChoiceControl(label: String, options: Vec<String>, value: Signal<String>) -> Element {
+    rsx! {
+        label { class: "flex items-center justify-between gap-3",
+            span { "{label}" }
+            select {
+                class: "w-48 rounded border border-slate-300 px-1 text-xs",
+                onchange: move |evt| value.set(evt.value()),
+                for option in options {
+                    option { key: "{option}", selected: *value.read() == option, "{option}" }
+                }
+            }
+        }
+    }
+}
+
+/// A story's preview above the controls for its props.
+#[component]
+fn StoryLayout(preview: Element, controls: Element) -> Element {
+    rsx! {
+        div { class: "flex flex-col gap-4",
+            div { class: "flex min-h-40 items-center justify-center rounded-lg border border-dashed border-slate-300 bg-slate-50 p-6",
+                {preview}
+            }
+            div { class: "flex flex-col gap-2 text-sm", {controls} }
+        }
+    }
+}
+
+fn choices(options: &[&str]) -> Vec<String> {
+    options.iter().map(|option| option.to_string()).collect()
+}
+
+#[component]
+fn CardStory() -> Element {
+    let color = use_signal(|| "bg-white".to_string());
+    let shadow = use_signal(|| "shadow-sm".to_string());
+    let rounded = use_signal(|| "rounded-lg".to_string());
+    let class = use_signal(|| "w-72".to_string());
+    let header = use_signal(|| true);
+    let footer = use_signal(|| true);
+
+    rsx! {
+        StoryLayout {
+            preview: rsx! {
+                Card { color: color(), shadow: shadow(), rounded: rounded(), class: class(),
+                    if header() {
+                        CardHeader { color: "bg-slate-100", size: "h-16", class: "p-4", "Header" }
+                    }
+                    CardBody { "Body content" }
+                    if footer() {
+                        CardFooter { Button { "Action" } }
+                    }
+                }
+            },
+            controls: rsx! {
+                ChoiceControl { label: "color", options: choices(&["bg-white", "bg-slate-100", "bg-sky-50"]), value: color }
+                ChoiceControl { label: "shadow", options: choices(&["shadow-none", "shadow-sm", "shadow-lg"]), value: shadow }
+                ChoiceControl { label: "rounded", options: choices(&["rounded-none", "rounded-lg", "rounded-2xl"]), value: rounded }
+                TextControl { label: "class", value: class }
+                BoolControl { label: "CardHeader", value: header }
+                BoolControl { label: "CardFooter", value: footer }
+            },
+        }
+    }
+}
+
+#[component]
+fn TypographyStory() -> Element {
+    let text = use_signal(|| "The quick brown fox jumps over the lazy dog".to_string());
+    let size = use_signal(|| "text-xl".to_string());
+    let color = use_signal(|| "text-slate-800".to_string());
+    let position = use_signal(|| "text-left".to_string());
+    let fit = use_signal(|| false);
+    let mut min_font_px = use_signal(|| 10.0);
+    let mut width = use_signal(|| 320.0);
+
+    rsx! {
+        StoryLayout {
+            preview: rsx! {
+                div { class: "border border-slate-200", style: "width: {width}px;",
+                    Typography {
+                        text: text(),
+                        size: size(),
+                        color: color(),
+                        position: position(),
+                        fit: fit(),
+                        min_font_px: min_font_px(),
+                    }
+                }
+            },
+            controls: rsx! {
+                TextControl { label: "text", value: text }
+                ChoiceControl { label: "size", options: choices(&["text-sm", "text-base", "text-xl", "text-3xl"]), value: size }
+                ChoiceControl { label: "color", options: choices(&["text-slate-800", "text-slate-500", "text-sky-600"]), value: color }
+                ChoiceControl { label: "position", options: choices(&["text-left", "text-center", "text-right"]), value: position }
+                BoolControl { label: "fit", value: fit }
+                NumberInput { label: "min_font_px", value: min_font_px(), min: 6.0, max: 24.0, onchange: move |value| min_font_px.set(value) }
+                NumberInput { label: "Container width", value: width(), min: 80.0, max: 600.0, step: 10.0, onchange: move |value| width.set(value) }
+            },
+        }
+    }
+}
+
+#[component]
+fn ButtonStory() -> Element {
+    let label = use_signal(|| "Save".to_string());
+    let color = use_signal(|| "bg-slate-800 text-white".to_string());
+    let disabled = use_signal(|| false);
+    let loading = use_signal(|| false);
+    let mut clicks = use_signal(|| 0);
+
+    rsx! {
+        StoryLayout {
+            preview: rsx! {
+                div { class: "flex flex-col items-center gap-2",
+                    Button {
+                        color: color(),
+                        disabled: disabled(),
+                        loading: loading(),
+                        onclick: move |_| clicks += 1,
+                        "{label}"
+                    }
+                    span { class: "text-xs text-slate-500", "Clicked {clicks} times" }
+                }
+            },
+            controls: rsx! {
+                TextControl { label: "children", value: label }
+                ChoiceControl { label: "color", options: choices(&["bg-slate-800 text-white", "bg-sky-600 text-white", "bg-white text-slate-800 border"]), value: color }
+                BoolControl { label: "disabled", value: disabled }
+                BoolControl { label: "loading", value: loading }
+            },
+        }
+    }
+}
+
+#[component]
+fn SpinnerStory() -> Element {
+    let size = use_signal(|| "h-8 w-8".to_string());
+    let class = use_signal(|| "text-sky-600".to_string());
+
+    rsx! {
+        StoryLayout {
+            preview: rsx! {
+                Spinner { size: size(), class: class() }
+            },
+            controls: rsx! {
+                ChoiceControl { label: "size", options: choices(&["h-4 w-4", "h-8 w-8", "h-16 w-16"]), value: size }
+                TextControl { label: "class", value: class }
+            },
+        }
+    }
+}
+
+#[component]
+fn AnimatedNumberStory() -> Element {
+    let mut value = use_signal(|| 1024.0);
+    let mut duration_ms = use_signal(|| 400.0);
+    let bytes = use_signal(|| false);
+
+    rsx! {
+        StoryLayout {
+            preview: rsx! {
+                if bytes() {
+                    AnimatedNumber {
+                        class: "text-3xl",
+                        value: value(),
+                        duration_ms: duration_ms() as u32,
+                        format: |value: f64| format_bytes(value as u64),
+                    }
+                } else {
+                    AnimatedNumber { class: "text-3xl", value: value(), duration_ms: duration_ms() as u32 }
+                }
+            },
+            controls: rsx! {
+                NumberInput { label: "value", value: value(), min: 0.0, max: 1e9, step: 1.0, onchange: move |new| value.set(new) }
+                NumberInput { label: "duration_ms", value: duration_ms(), min: 0.0, max: 3000.0, step: 50.0, onchange: move |new| duration_ms.set(new) }
+                BoolControl { label: "format as bytes", value: bytes }
+                Button { class: "self-end", onclick: move |_| value.set((value() * 7.3).round() % 1e9), "Jump" }
+            },
+        }
+    }
+}
+
+#[component]
+fn ColorPickerStory() -> Element {
+    let label = use_signal(|| "Accent".to_string());
+    let mut value = use_signal(|| "#38bdf8".to_string());
+
+    rsx! {
+        StoryLayout {
+            preview: rsx! {
+                div { class: "w-64",
+                    ColorPicker { label: label(), value: value(), onchange: move |new| value.set(new) }
+                }
+            },
+            controls: rsx! {
+                TextControl { label: "label", value: label }
+                TextControl { label: "value", value }
+            },
+        }
+    }
+}
+
+#[component]
+fn NumberInputStory() -> Element {
+    let mut value = use_signal(|| 8.0);
+    let mut min = use_signal(|| 0.0);
+    let mut max = use_signal(|| 24.0);
+    let mut step = use_signal(|| 1.0);
+
+    rsx! {
+        StoryLayout {
+            preview: rsx! {
+                div { class: "w-80",
+                    NumberInput {
+                        label: "Value",
+                        value: value(),
+                        min: min(),
+                        max: max(),
+                        step: step(),
+                        onchange: move |new| value.set(new),
+                    }
+                }
+            },
+            controls: rsx! {
+                NumberInput { label: "min", value: min(), min: -100.0, max: 100.0, onchange: move |new| min.set(new) }
+                NumberInput { label: "max", value: max(), min: -100.0, max: 100.0, onchange: move |new| max.set(new) }
+                NumberInput { label: "step", value: step(), min: 0.05, max: 10.0, step: 0.05, onchange: move |new| step.set(new) }
+            },
+        }
+    }
+}
+
+#[component]
+fn PopoverStory() -> Element {
+    let open = use_signal(|| true);
+    let text = use_signal(|| "Popover content".to_string());
+    let color = use_signal(|| "bg-white".to_string());
+
+    rsx! {
+        StoryLayout {
+            preview: rsx! {
+                div { class: "relative mt-16",
+                    Button { "Anchor" }
+                    Popover { open: open(), color: color(),
+                        p { class: "whitespace-nowrap text-sm", "{text}" }
+                    }
+                }
+            },
+            controls: rsx! {
+                BoolControl { label: "open", value: open }
+                TextControl { label: "children", value: text }
+                ChoiceControl { label: "color", options: choices(&["bg-white", "bg-slate-800 text-white", "bg-amber-100"]), value: color }
+            },
+        }
+    }
+}
+
+#[component]
+fn ModalStory() -> Element {
+    let mut open = use_signal(|| false);
+    let text = use_signal(|| "Are you sure?".to_string());
+    let color = use_signal(|| "bg-white".to_string());
+    let closable = use_signal(|| true);
+
+    rsx! {
+        StoryLayout {
+            preview: rsx! {
+                Button { onclick: move |_| open.set(true), "Open modal" }
+                if open() {
+                    Modal {
+                        color: color(),
+                        onclose: move |_| {
+                            if closable() {
+                                open.set(false);
+                            }
+                        },
+                        p { class: "mb-4", "{text}" }
+                        Button { onclick: move |_| open.set(false), "Close" }
+                    }
+                }
+            },
+            controls: rsx! {
+                TextControl { label: "children", value: text }
+                ChoiceControl { label: "color", options: choices(&["bg-white", "bg-slate-100", "bg-amber-50"]), value: color }
+                BoolControl { label: "close on backdrop click", value: closable }
+            },
+        }
+    }
+}
+
+#[component]
+fn CodeBlockStory() -> Element {
+    let mut code = use_signal(|| "fn main() {\n    println!(\"Hello, JadeOS!\");\n}".to_string());
+    let language = use_signal(|| "rs".to_string());
+
+    rsx! {
+        StoryLayout {
+            preview: rsx! {
+                CodeBlock { class: "w-full", code, language }
+            },
+            controls: rsx! {
+                label { class: "flex flex-col gap-1",
+                    span { "code" }
+                    textarea {
+                        class: "h-24 rounded border border-slate-300 p-1 font-mono text-xs",
+                        value: "{code}",
+                        oninput: move |evt| code.set(evt.value()),
+                    }
+                }
+                ChoiceControl { label: "language", options: choices(&["rs", "py", "js", "toml", "txt"]), value: language }
+            },
+        }
+    }
+}
+
+#[component]
+fn ResizableStory() -> Element {
+    let mut size = use_signal(|| (200.0, 120.0));
+    let keep_aspect = use_signal(|| false);
+    let edges_only = use_signal(|| false);
+    let mut min = use_signal(|| 80.0);
+    let mut max = use_signal(|| 400.0);
+
+    let handles = if edges_only() {
+        vec![ResizeHandle::East, ResizeHandle::South]
+    } else {
+        ResizeHandle::ALL.to_vec()
+    };
+    // Remount when the constraints change, as the box only reads its size
+    // once.
+    let key = format!("{}-{}-{}-{}", keep_aspect(), edges_only(), min(), max());
+
+    rsx! {
+        StoryLayout {
+            preview: rsx! {
+                Resizable {
+                    key: "{key}",
+                    class: "rounded-lg border border-slate-300 bg-white",
+                    size: size(),
+                    min_size: (min(), min()),
+                    max_size: (max(), max()),
+                    aspect_ratio: keep_aspect().then(|| size().0 / size().1),
+                    handles,
+                    onresize: move |new| size.set(new),
+                    p { class: "p-2 text-xs text-slate-500", "{size().0:.0} × {size().1:.0}" }
+                }
+            },
+            controls: rsx! {
+                BoolControl { label: "aspect_ratio", value: keep_aspect }
+                BoolControl { label: "East and South handles only", value: edges_only }
+                NumberInput { label: "min_size", value: min(), min: 20.0, max: 200.0, onchange: move |new| min.set(new) }
+                NumberInput { label: "max_size", value: max(), min: 200.0, max: 600.0, onchange: move |new| max.set(new) }
+            },
+        }
+    }
+}
+
+#[component]
+fn CollapsibleStory() -> Element {
+    let open = use_signal(|| true);
+    let mut lines = use_signal(|| 3.0);
+    let mut duration = use_signal(|| 200.0);
+
+    rsx! {
+        StoryLayout {
+            preview: rsx! {
+                div { class: "w-64 rounded-lg border border-slate-300 bg-white",
+                    p { class: "border-b border-slate-200 px-3 py-2 text-sm font-medium", "Header" }
+                    Collapsible { open: open(), duration_ms: duration() as u32,
+                        ul { class: "px-3 py-2 text-sm text-slate-600",
+                            for line in 0..lines() as usize {
+                                li { key: "{line}", "Item {line + 1}" }
+                            }
+                        }
+                    }
+                }
+            },
+            controls: rsx! {
+                BoolControl { label: "open", value: open }
+                NumberInput { label: "Content lines", value: lines(), min: 0.0, max: 20.0, onchange: move |new| lines.set(new) }
+                NumberInput { label: "duration_ms", value: duration(), min: 0.0, max: 1000.0, onchange: move |new| duration.set(new) }
+            },
+        }
+    }
+}
+
+/// The `components` app: a list of stories next to the selected one.
+#[component]
+pub fn Storybook() -> Element {
+    let mut selected = use_signal(|| Story::Card);
+
+    rsx! {
+        div { class: "flex h-[32rem] w-[48rem]",
+            nav { class: "flex w-40 shrink-0 flex-col gap-1 overflow-y-auto border-r border-slate-200 bg-slate-50 p-2 text-sm",
+                for story in Story::ALL {
+                    button {
+                        key: "{story:?}",
+                        class: if selected() == story {
+                            "rounded-md bg-slate-200 px-2 py-1 text-left font-medium"
+                        } else {
+                            "rounded-md px-2 py-1 text-left hover:bg-slate-100"
+                        },
+                        onclick: move |_| selected.set(story),
+                        "{story:?}"
+                    }
+                }
+            }
+            div { class: "min-w-0 flex-1 overflow-y-auto p-4",
+                match selected() {
+                    Story::Card => rsx! { CardStory {} },
+                    Story::Typography => rsx! { TypographyStory {} },
+                    Story::Button => rsx! { ButtonStory {} },
+                    Story::Spinner => rsx! { SpinnerStory {} },
+                    Story::AnimatedNumber => rsx! { AnimatedNumberStory {} },
+                    Story::ColorPicker => rsx! { ColorPickerStory {} },
+                    Story::NumberInput => rsx! { NumberInputStory {} },
+                    Story::Popover => rsx! { PopoverStory {} },
+                    Story::Modal => rsx! { ModalStory {} },
+                    Story::CodeBlock => rsx! { CodeBlockStory {} },
+                    Story::Resizable => rsx! { ResizableStory {} },
+                    Story::Collapsible => rsx! { CollapsibleStory {} },
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! Buttons for the windows of the active workspace. Hovering one for a
+//! moment peeks at its window; clicking it brings the window to the front.
+//! Buttons can be dragged onto each other to reorder them; the order is
+//! saved with the session.
+
+use crate::browser;
+use crate::dnd::{Draggable, DropZone};
+use crate::window_element_id;
+use crate::window_manager::{use_window_manager, WindowHandle, WindowId, WindowManager};
+use dioxus::core::Task;
+use dioxus::prelude::*;
+use ui::Popover;
+
+/// How long an entry has to be hovered before its preview shows.
+const PEEK_DELAY_MS: u32 = 400;
+
+const PREVIEW_SIZE: (f64, f64) = (200.0, 130.0);
+
+/// Snapshot of window `id` taken when the preview opens, or just its `title`
+/// when the window can't be captured, such as a minimized one.
+#[component]
+fn WindowPreview(id: WindowId, title: String) -> Element {
+    let mut captured = use_signal(|| true);
+
+    rsx! {
+        div {
+            class: "relative overflow-hidden rounded bg-slate-100",
+            style: "width:{PREVIEW_SIZE.0}px; height:{PREVIEW_SIZE.1}px;",
+            onmounted: move |evt: Event<MountedData>| {
+                let copied = browser::clone_scaled(&window_element_id(id), &evt.data(), PREVIEW_SIZE);
+                captured.set(copied.is_some());
+            },
+            if !captured() {
+                div { class: "flex h-full items-center justify-center p-2 text-center text-sm text-slate-700",
+                    "{title}"
+                }
+            }
+        }
+        if captured() {
+            p { class: "mt-1 truncate text-xs text-slate-700", "{title}" }
+        }
+    }
+}
+
+/// The windows with a taskbar entry on the active workspace, in taskbar
+/// order. Minimized windows keep theirs.
+fn entries(manager: &WindowManager) -> Vec<WindowHandle> {
+    let active = manager.active_workspace();
+    manager
+        .taskbar()
+        .iter()
+        .filter(|&&id| manager.is_on_workspace(id, active))
+        .filter_map(|&id| manager.get(id).cloned())
+        .collect()
+}
+
+/// Index in the whole taskbar, which also holds the other workspaces'
+/// windows, that an entry dropped on the entry of `onto` moves to.
+fn drop_index(manager: &WindowManager, onto: WindowId) -> Option<usize> {
+    manager.taskbar().iter().position(|&id| id == onto)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum EntryState {
+    Normal,
+    Focused,
+    Minimized,
+    /// The window is flashing and the highlight is lit, whatever its state.
+    Flashing,
+}
+
+impl EntryState {
+    fn of(manager: &WindowManager, id: WindowId) -> Self {
+        if manager.flash_lit(id) {
+            EntryState::Flashing
+        } else if manager.is_minimized(id) {
+            EntryState::Minimized
+        } else if manager.focused() == Some(id) {
+            EntryState::Focused
+        } else {
+            EntryState::Normal
+        }
+    }
+
+    fn class(self) -> &'static str {
+        match self {
+            EntryState::Flashing => {
+                "max-w-40 truncate rounded-md bg-amber-400 px-3 py-1 text-sm text-slate-900"
+            }
+            EntryState::Minimized => {
+                "max-w-40 truncate rounded-md px-3 py-1 text-sm text-slate-400 italic"
+            }
+            EntryState::Focused => {
+                "max-w-40 truncate rounded-md bg-slate-500 px-3 py-1 text-sm text-white"
+            }
+            EntryState::Normal => {
+                "max-w-40 truncate rounded-md px-3 py-1 text-sm text-slate-200 hover:bg-slate-700"
+            }
+        }
+    }
+}
+
+#[component]
+pub fn Taskbar() -> Element {
+    let mut manager = use_window_manager();
+    let mut peek = use_signal(|| None::<WindowId>);
+    let mut pending = use_signal(|| None::<Task>);
+
+    let entries = entries(&manager.read());
+
+    let mut stop_peeking = move || {
+        if let Some(task) = pending.write().take() {
+            task.cancel();
+        }
+        peek.set(None);
+    };
+
+    rsx! {
+        div {
+            class: "fixed bottom-3 left-3 flex max-w-[40vw] gap-1 rounded-lg bg-slate-800/80 p-1",
+            style: "z-index: 9000;",
+            for entry in entries {
+                div {
+                    key: "{entry.id}",
+                    class: "relative",
+                    onmouseenter: move |_| {
+                        stop_peeking();
+                        pending.set(Some(spawn(async move {
+                            browser::sleep(PEEK_DELAY_MS).await;
+                            peek.set(Some(entry.id));
+                        })));
+                    },
+                    onmouseleave: move |_| stop_peeking(),
+                    DropZone::<WindowId> {
+                        class: "rounded-md",
+                        onaccept: move |dragged| {
+                            let mut manager = manager.write();
+                            if let Some(index) = drop_index(&manager, entry.id) {
+                                manager.reorder_taskbar(dragged, index);
+                            }
+                        },
+                        Draggable::<WindowId> { payload: entry.id,
+                            button {
+                                class: EntryState::of(&manager.read(), entry.id).class(),
+                                onclick: move |_| {
+                                    stop_peeking();
+                                    let mut manager = manager.write();
+                                    manager.restore(entry.id);
+                                    manager.focus(entry.id);
+                                },
+                                "{entry.title}"
+                            }
+                        }
+                    }
+                    Popover { open: peek() == Some(entry.id),
+                        WindowPreview { id: entry.id, title: entry.title.clone() }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_are_the_active_workspaces_windows_in_taskbar_order() {
+        let mut manager = WindowManager::default();
+        let a = manager.open("a").id;
+        let b = manager.open("b").id;
+        let c = manager.open("c").id;
+        let pinned = manager.open("pinned").id;
+        manager.set_sticky(pinned, true);
+        manager.move_to_workspace(b, 1);
+        manager.minimize(c);
+        manager.reorder_taskbar(c, 0);
+
+        let ids = |manager: &WindowManager| {
+            entries(manager)
+                .iter()
+                .map(|entry| entry.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&manager), [c, a, pinned]);
+
+        manager.switch_workspace(1);
+        assert_eq!(ids(&manager), [b, pinned]);
+    }
+
+    #[test]
+    fn drops_land_at_the_target_entrys_place_in_the_whole_taskbar() {
+        let mut manager = WindowManager::default();
+        let a = manager.open("a").id;
+        let hidden = manager.open("hidden").id;
+        let c = manager.open("c").id;
+        manager.move_to_workspace(hidden, 1);
+
+        // Dropping `c` on `a`, with a window of another workspace between
+        // them on the taskbar.
+        let index = drop_index(&manager, a).unwrap();
+        manager.reorder_taskbar(c, index);
+        assert_eq!(manager.taskbar(), [c, a, hidden]);
+
+        assert_eq!(drop_index(&manager, 42), None);
+    }
+
+    #[test]
+    fn a_lit_flash_shows_over_any_other_state() {
+        let mut manager = WindowManager::default();
+        let a = manager.open("a").id;
+        let b = manager.open("b").id;
+        assert_eq!(EntryState::of(&manager, b), EntryState::Focused);
+        assert_eq!(EntryState::of(&manager, a), EntryState::Normal);
+
+        manager.minimize(a);
+        assert_eq!(EntryState::of(&manager, a), EntryState::Minimized);
+
+        manager.flash(a);
+        manager.set_flash_lit(a, true);
+        assert_eq!(EntryState::of(&manager, a), EntryState::Flashing);
+        manager.set_flash_lit(a, false);
+        assert_eq!(EntryState::of(&manager, a), EntryState::Minimized);
+    }
+}
@@ -0,0 +1,186 @@
+//! Design tokens the desktop is styled with, editable at runtime.
+//!
+//! The tokens are exposed to CSS as custom properties on the desktop root
+//! (`--jade-accent`, ...), so anything styled with them follows edits
+//! immediately. They're saved to `localStorage`.
+
+use crate::browser;
+use crate::hooks::use_debounce;
+use crate::window_manager::FocusSettings;
+use dioxus::logger::tracing;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use ui::{Button, ColorPicker, NumberInput};
+
+const THEME_KEY: &str = "jadeos.theme";
+/// How long edits must pause before the tokens are saved; dragging a color
+/// picker changes them on every move.
+const SAVE_DELAY_MS: u32 = 300;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DesignTokens {
+    /// Focus rings and highlights, as `#rrggbb`.
+    pub accent: String,
+    /// Window background.
+    pub surface: String,
+    /// Window text.
+    pub text: String,
+    /// Corner radius of windows, in pixels.
+    pub radius_px: f64,
+    /// Opacity of window shadows, from 0 to 1.
+    pub shadow: f64,
+}
+
+impl Default for DesignTokens {
+    fn default() -> Self {
+        Self {
+            accent: "#38bdf8".to_string(),
+            surface: "#ffffff".to_string(),
+            text: "#1e293b".to_string(),
+            radius_px: 8.0,
+            shadow: 0.15,
+        }
+    }
+}
+
+impl DesignTokens {
+    /// Inline style declaring the tokens as CSS custom properties.
+    pub fn css_vars(&self) -> String {
+        format!(
+            "--jade-accent:{}; --jade-surface:{}; --jade-text:{}; --jade-radius:{}px; --jade-shadow:{};",
+            self.accent, self.surface, self.text, self.radius_px, self.shadow
+        )
+    }
+}
+
+/// The tokens saved as `json`. Tokens missing from it, e.g. ones added
+/// since it was saved, keep their defaults; an unreadable theme is dropped.
+fn resolve_tokens(json: Option<&str>) -> DesignTokens {
+    let Some(json) = json else {
+        return DesignTokens::default();
+    };
+    serde_json::from_str(json).unwrap_or_else(|err| {
+        tracing::warn!("ignoring unreadable theme: {err}");
+        DesignTokens::default()
+    })
+}
+
+fn load_tokens() -> DesignTokens {
+    resolve_tokens(browser::load(THEME_KEY).as_deref())
+}
+
+/// Provides the stored tokens and saves them once edits settle.
+pub fn use_theme_provider() -> Signal<DesignTokens> {
+    let tokens = use_context_provider(|| Signal::new(load_tokens()));
+    let settled = use_debounce(tokens, SAVE_DELAY_MS);
+    use_effect(move || {
+        let tokens = settled.read();
+        if let Ok(json) = serde_json::to_string(&*tokens) {
+            browser::store(THEME_KEY, &json);
+        }
+    });
+    tokens
+}
+
+pub fn use_design_tokens() -> Signal<DesignTokens> {
+    use_context()
+}
+
+/// Settings app editing the [`DesignTokens`] live.
+#[component]
+pub fn ThemeEditor() -> Element {
+    let mut tokens = use_design_tokens();
+    let current = tokens();
+
+    rsx! {
+        div { class: "flex w-80 flex-col gap-3 p-4 text-sm",
+            ColorPicker {
+                label: "Accent",
+                value: current.accent,
+                onchange: move |value| tokens.write().accent = value,
+            }
+            ColorPicker {
+                label: "Window background",
+                value: current.surface,
+                onchange: move |value| tokens.write().surface = value,
+            }
+            ColorPicker {
+                label: "Window text",
+                value: current.text,
+                onchange: move |value| tokens.write().text = value,
+            }
+            NumberInput {
+                label: "Corner radius",
+                value: current.radius_px,
+                min: 0.0,
+                max: 24.0,
+                step: 1.0,
+                onchange: move |value| tokens.write().radius_px = value,
+            }
+            NumberInput {
+                label: "Shadow",
+                value: current.shadow,
+                min: 0.0,
+                max: 1.0,
+                step: 0.05,
+                onchange: move |value| tokens.write().shadow = value,
+            }
+            Button {
+                class: "self-end",
+                onclick: move |_| tokens.set(DesignTokens::default()),
+                "Reset to defaults"
+            }
+            div { class: "border-t border-slate-200 pt-3",
+                FocusSettings {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_tokens_override_the_defaults() {
+        let saved = DesignTokens {
+            accent: "#f43f5e".to_string(),
+            radius_px: 0.0,
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&saved).unwrap();
+        assert_eq!(resolve_tokens(Some(&json)), saved);
+    }
+
+    #[test]
+    fn tokens_missing_from_the_saved_theme_keep_their_defaults() {
+        let tokens = resolve_tokens(Some(r##"{"accent": "#f43f5e"}"##));
+        assert_eq!(
+            tokens,
+            DesignTokens {
+                accent: "#f43f5e".to_string(),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn a_missing_or_unreadable_theme_falls_back_to_the_defaults() {
+        assert_eq!(resolve_tokens(None), DesignTokens::default());
+        assert_eq!(resolve_tokens(Some("not json")), DesignTokens::default());
+        assert_eq!(
+            resolve_tokens(Some(r#"{"radius_px": "round"}"#)),
+            DesignTokens::default()
+        );
+    }
+
+    #[test]
+    fn tokens_become_css_custom_properties() {
+        assert_eq!(
+            DesignTokens::default().css_vars(),
+            "--jade-accent:#38bdf8; --jade-surface:#ffffff; --jade-text:#1e293b; \
+             --jade-radius:8px; --jade-shadow:0.15;"
+        );
+    }
+}
@@ -0,0 +1,133 @@
+//! Text in a given size and color that can shrink to stay on one line.
+
+use dioxus::prelude::*;
+use std::rc::Rc;
+
+use crate::browser::{self, SizeObserver};
+use crate::geometry::parse_css_px;
+
+#[derive(Props, Clone, PartialEq)]
+pub struct TypographyProps {
+    text: String,
+    size: String,
+    color: String,
+    #[props(default = "".to_string())]
+    position: String,
+    #[props(default = "".to_string())]
+    class: String,
+    /// Keeps the text on one line, shrinking the font below `size` as far as
+    /// `min_font_px` when the container gets too narrow for it.
+    #[props(default)]
+    fit: bool,
+    #[props(default = 10.0)]
+    min_font_px: f64,
+}
+
+/// Font size at which text measuring `natural_width` at `natural_size` fits
+/// in `available`, but no smaller than `min`.
+pub fn fit_font_size(natural_size: f64, natural_width: f64, available: f64, min: f64) -> f64 {
+    if natural_width <= available || natural_width <= 0.0 {
+        return natural_size;
+    }
+    (natural_size * available / natural_width)
+        .max(min)
+        .min(natural_size)
+}
+
+/// The font size to set on an element whose computed `font-size` is
+/// `natural` and whose text is `(width, available)` wide, or `None` to leave
+/// it at its natural size.
+fn fitted_size(natural: Option<&str>, widths: Option<(f64, f64)>, min_font_px: f64) -> Option<f64> {
+    let natural = natural.and_then(parse_css_px)?;
+    let (width, available) = widths?;
+    let size = fit_font_size(natural, width, available, min_font_px);
+    (size < natural).then_some(size)
+}
+
+/// Shrinks the font of a single-line `element` until its text fits.
+fn fit_text(element: &MountedData, min_font_px: f64) {
+    browser::set_font_size(element, None);
+    let natural = browser::computed_style(element, "font-size");
+    let size = fitted_size(
+        natural.as_deref(),
+        browser::content_widths(element),
+        min_font_px,
+    );
+    if size.is_some() {
+        browser::set_font_size(element, size);
+    }
+}
+
+#[component]
+pub fn Typography(props: TypographyProps) -> Element {
+    let mut element = use_signal(|| Option::<Rc<MountedData>>::None);
+    let mut observer = use_signal(|| Option::<SizeObserver>::None);
+    let fit = props.fit;
+    let min_font_px = props.min_font_px;
+
+    let onmounted = move |evt: Event<MountedData>| {
+        if !fit {
+            return;
+        }
+        let mounted = evt.data();
+        let target = mounted.clone();
+        observer.set(SizeObserver::observe(&mounted, move || {
+            fit_text(&target, min_font_px)
+        }));
+        element.set(Some(mounted));
+    };
+
+    // The box doesn't resize when only the text changes, so refit then too.
+    let text = props.text.clone();
+    use_effect(use_reactive!(|text| {
+        let _ = &text;
+        if let (true, Some(element)) = (fit, element.peek().as_ref()) {
+            fit_text(element, min_font_px);
+        }
+    }));
+
+    let fit_class = if fit {
+        "whitespace-nowrap overflow-hidden"
+    } else {
+        ""
+    };
+
+    rsx! {
+        div {
+            class: "{props.size} {props.color} {props.position} {props.class} {fit_class}",
+            onmounted: onmounted,
+            {props.text}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fitted_fonts_shrink_in_proportion_down_to_the_minimum() {
+        assert_eq!(fit_font_size(16.0, 150.0, 200.0, 10.0), 16.0);
+        assert_eq!(fit_font_size(20.0, 400.0, 300.0, 10.0), 15.0);
+        // Half the size would fit, but that's below the minimum.
+        assert_eq!(fit_font_size(16.0, 400.0, 200.0, 10.0), 10.0);
+        // Nothing measured yet.
+        assert_eq!(fit_font_size(16.0, 0.0, 0.0, 10.0), 16.0);
+    }
+
+    #[test]
+    fn only_overflowing_text_gets_a_font_size() {
+        assert_eq!(
+            fitted_size(Some("20px"), Some((400.0, 300.0)), 10.0),
+            Some(15.0)
+        );
+        assert_eq!(fitted_size(Some("16px"), Some((150.0, 200.0)), 10.0), None);
+        // Unmeasurable elements, e.g. before layout, are left alone.
+        assert_eq!(
+            fitted_size(Some("normal"), Some((400.0, 300.0)), 10.0),
+            None
+        );
+        assert_eq!(fitted_size(Some("20px"), None, 10.0), None);
+        assert_eq!(fitted_size(None, Some((400.0, 300.0)), 10.0), None);
+    }
+}
@@ -0,0 +1,1212 @@
+//! Shared bookkeeping for every `Movable` on the desktop: which windows are
+//! open, which one has focus and the order they were last focused in.
+
+use crate::browser;
+use crate::geometry::{column_tile, next_tile, snap_slot, SnapLayout, TileDirection};
+use crate::shortcuts::{use_shortcut, ShortcutScope};
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use ui::NumberInput;
+
+pub type WindowId = usize;
+
+/// A window known to the [`WindowManager`].
+///
+/// `id` is never reused, so it is safe to use as the `key` when rendering
+/// windows in a loop: Dioxus then keeps each window's DOM (and its drag,
+/// scroll and component state) attached to the right window as others are
+/// opened or closed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WindowHandle {
+    pub id: WindowId,
+    pub title: String,
+    /// App rendered in the window; windows without one can't be restored by
+    /// a session.
+    pub app_id: Option<String>,
+}
+
+/// Geometry of a window, as last committed by its `Movable`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WindowState {
+    pub position: (f64, f64),
+    /// `None` while the window is still sized by its content.
+    pub size: Option<(f64, f64)>,
+}
+
+impl Default for WindowState {
+    fn default() -> Self {
+        Self {
+            position: (100.0, 100.0),
+            size: None,
+        }
+    }
+}
+
+/// Slots of a snap layout still waiting for a window after one was snapped
+/// into it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnapAssist {
+    pub layout: SnapLayout,
+    /// Size of the desktop the layout was laid out over.
+    pub desktop: (f64, f64),
+    pub free: Vec<usize>,
+    /// Windows snapped into the layout so far, which aren't offered again.
+    pub snapped: Vec<WindowId>,
+}
+
+/// How hovering a window moves focus to it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FocusMode {
+    /// Only clicking a window focuses it.
+    #[default]
+    Click,
+    /// Focus follows the pointer into a window once it has been there for
+    /// the hover delay.
+    FollowsPointer,
+    /// Like [`FocusMode::FollowsPointer`], but the pointer has to come to
+    /// rest on the window, so passing over windows on the way to a menu or
+    /// another window doesn't take focus away from the last one.
+    Sloppy,
+}
+
+impl FocusMode {
+    pub const ALL: [FocusMode; 3] = [
+        FocusMode::Click,
+        FocusMode::FollowsPointer,
+        FocusMode::Sloppy,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FocusMode::Click => "Click to focus",
+            FocusMode::FollowsPointer => "Focus follows pointer",
+            FocusMode::Sloppy => "Sloppy focus",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FocusPolicy {
+    pub mode: FocusMode,
+    /// How long a window has to be hovered before it's focused.
+    pub focus_hover_delay_ms: u32,
+}
+
+impl Default for FocusPolicy {
+    fn default() -> Self {
+        Self {
+            mode: FocusMode::Click,
+            focus_hover_delay_ms: 150,
+        }
+    }
+}
+
+/// How long the pointer has to rest for sloppy focus, even with no hover
+/// delay, to tell it settled from it passing by.
+const SLOPPY_SETTLE_MS: f64 = 120.0;
+
+/// The pointer hovering a window, tracked by the window's `Movable` with
+/// timestamps in milliseconds.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HoverFocus {
+    /// When the pointer entered the window, while it's inside.
+    entered: Option<f64>,
+    /// When the pointer last moved over the window.
+    moved: f64,
+}
+
+impl HoverFocus {
+    pub fn enter(&mut self, now: f64) {
+        self.entered = Some(now);
+        self.moved = now;
+    }
+
+    pub fn moved(&mut self, now: f64) {
+        if self.entered.is_none() {
+            self.entered = Some(now);
+        }
+        self.moved = now;
+    }
+
+    pub fn leave(&mut self) {
+        self.entered = None;
+    }
+
+    /// When this hover focuses the window under `policy`, or `None` if it
+    /// doesn't: the pointer isn't over the window or focus needs a click.
+    pub fn due(&self, policy: FocusPolicy) -> Option<f64> {
+        let entered = self.entered?;
+        let delay = policy.focus_hover_delay_ms as f64;
+        match policy.mode {
+            FocusMode::Click => None,
+            FocusMode::FollowsPointer => Some(entered + delay),
+            FocusMode::Sloppy => Some(self.moved + delay.max(SLOPPY_SETTLE_MS)),
+        }
+    }
+}
+
+/// Offset of a child window from its parent's top-left corner when it opens.
+const CHILD_OFFSET: (f64, f64) = (32.0, 32.0);
+
+/// Number of virtual desktops windows can be spread over.
+pub const WORKSPACE_COUNT: usize = 4;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WindowManager {
+    /// Open windows in creation order. Focus changes only reorder `mru`, so
+    /// rendering from this list never moves DOM nodes around.
+    windows: Vec<WindowHandle>,
+    states: HashMap<WindowId, WindowState>,
+    /// Windows opened without a position, waiting for their `Movable` to
+    /// place them.
+    unplaced: HashSet<WindowId>,
+    /// Geometry of maximized windows from before they were maximized.
+    maximized: HashMap<WindowId, WindowState>,
+    /// Workspace of each window; windows missing here are on workspace 0.
+    workspaces: HashMap<WindowId, usize>,
+    active_workspace: usize,
+    /// Windows pinned to every workspace. They keep their entry in
+    /// `workspaces`, the one they stay on once unpinned.
+    sticky: HashSet<WindowId>,
+    minimized: HashSet<WindowId>,
+    /// Windows minimized by [`WindowManager::show_desktop`], restored when it
+    /// is toggled off again.
+    desktop_shown: Option<Vec<WindowId>>,
+    /// Workspace indicator under a window being dragged, if any.
+    drag_target: Option<usize>,
+    /// Whether moving a window to another workspace also switches to it.
+    follow_moved: bool,
+    /// Window ids, most recently focused first.
+    mru: Vec<WindowId>,
+    /// Window ids in the order of their taskbar entries.
+    taskbar: Vec<WindowId>,
+    /// Parent of each child window. Children stay above their parent, move
+    /// with it and close with it.
+    parents: HashMap<WindowId, WindowId>,
+    /// Windows flashing for attention, with whether their highlight is lit
+    /// at this point of the pulse.
+    flashing: HashMap<WindowId, bool>,
+    /// Position in `mru` highlighted by the Alt+Tab switcher while it's open.
+    switcher: Option<usize>,
+    snap_assist: Option<SnapAssist>,
+    focus_policy: FocusPolicy,
+    next_id: WindowId,
+}
+
+impl WindowManager {
+    /// Adds a window and focuses it, as newly opened windows are. Its
+    /// `Movable` decides where it opens.
+    pub fn open(&mut self, title: impl Into<String>) -> WindowHandle {
+        let handle = self.insert(title.into(), None, WindowState::default());
+        self.unplaced.insert(handle.id);
+        handle
+    }
+
+    /// Opens a window running the app `app_id`, placed by its `Movable`.
+    pub fn launch(&mut self, app_id: impl Into<String>, title: impl Into<String>) -> WindowHandle {
+        let handle = self.insert(title.into(), Some(app_id.into()), WindowState::default());
+        self.unplaced.insert(handle.id);
+        handle
+    }
+
+    /// Opens a window running the app `app_id`, at the given geometry.
+    pub fn open_app(
+        &mut self,
+        app_id: impl Into<String>,
+        title: impl Into<String>,
+        state: WindowState,
+    ) -> WindowHandle {
+        self.insert(title.into(), Some(app_id.into()), state)
+    }
+
+    /// Opens a child window of `parent`, slightly offset from it.
+    pub fn open_child(&mut self, parent: WindowId, title: impl Into<String>) -> WindowHandle {
+        let origin = self.state(parent).position;
+        let state = WindowState {
+            position: (origin.0 + CHILD_OFFSET.0, origin.1 + CHILD_OFFSET.1),
+            size: None,
+        };
+        let handle = self.insert(title.into(), None, state);
+        if self.get(parent).is_some() {
+            self.parents.insert(handle.id, parent);
+            self.workspaces.insert(handle.id, self.workspace_of(parent));
+            if self.is_sticky(parent) {
+                self.sticky.insert(handle.id);
+            }
+        }
+        handle
+    }
+
+    fn insert(
+        &mut self,
+        title: String,
+        app_id: Option<String>,
+        state: WindowState,
+    ) -> WindowHandle {
+        let handle = WindowHandle {
+            id: self.next_id,
+            title,
+            app_id,
+        };
+        self.next_id += 1;
+        self.windows.push(handle.clone());
+        self.states.insert(handle.id, state);
+        self.workspaces.insert(handle.id, self.active_workspace);
+        self.mru.insert(0, handle.id);
+        self.taskbar.push(handle.id);
+        handle
+    }
+
+    /// Closes `id` along with its child windows.
+    pub fn close(&mut self, id: WindowId) {
+        for child in self.children(id) {
+            self.close(child);
+        }
+        self.parents.remove(&id);
+        self.windows.retain(|window| window.id != id);
+        self.states.remove(&id);
+        self.maximized.remove(&id);
+        self.unplaced.remove(&id);
+        self.workspaces.remove(&id);
+        self.sticky.remove(&id);
+        self.minimized.remove(&id);
+        if let Some(shown) = &mut self.desktop_shown {
+            shown.retain(|&other| other != id);
+        }
+        self.mru.retain(|&other| other != id);
+        self.taskbar.retain(|&other| other != id);
+        self.flashing.remove(&id);
+        if let Some(assist) = &mut self.snap_assist {
+            assist.snapped.retain(|&other| other != id);
+        }
+        if let Some(index) = self.switcher {
+            self.switcher = (!self.mru.is_empty()).then(|| index.min(self.mru.len() - 1));
+        }
+    }
+
+    /// Open windows in a stable order, suitable for a keyed render loop.
+    pub fn windows(&self) -> &[WindowHandle] {
+        &self.windows
+    }
+
+    pub fn get(&self, id: WindowId) -> Option<&WindowHandle> {
+        self.windows.iter().find(|window| window.id == id)
+    }
+
+    pub fn set_title(&mut self, id: WindowId, title: impl Into<String>) {
+        if let Some(window) = self.windows.iter_mut().find(|window| window.id == id) {
+            window.title = title.into();
+        }
+    }
+
+    pub fn state(&self, id: WindowId) -> WindowState {
+        self.states.get(&id).copied().unwrap_or_default()
+    }
+
+    /// Commits the geometry of `id`. Its child windows move along with it.
+    pub fn set_state(&mut self, id: WindowId, state: WindowState) {
+        if self.get(id).is_none() {
+            return;
+        }
+        let previous = self.state(id).position;
+        self.states.insert(id, state);
+
+        let delta = (state.position.0 - previous.0, state.position.1 - previous.1);
+        if delta != (0.0, 0.0) {
+            for child in self.descendants(id) {
+                if let Some(child) = self.states.get_mut(&child) {
+                    child.position.0 += delta.0;
+                    child.position.1 += delta.1;
+                }
+            }
+        }
+    }
+
+    pub fn parent(&self, id: WindowId) -> Option<WindowId> {
+        self.parents.get(&id).copied()
+    }
+
+    /// Direct child windows of `id`, in the order they were opened.
+    pub fn children(&self, id: WindowId) -> Vec<WindowId> {
+        self.windows
+            .iter()
+            .map(|window| window.id)
+            .filter(|&child| self.parent(child) == Some(id))
+            .collect()
+    }
+
+    pub fn has_children(&self, id: WindowId) -> bool {
+        self.parents.values().any(|&parent| parent == id)
+    }
+
+    /// Children of `id`, their children and so on, parents before their
+    /// own children.
+    fn descendants(&self, id: WindowId) -> Vec<WindowId> {
+        let mut descendants = self.children(id);
+        let mut index = 0;
+        while let Some(&child) = descendants.get(index) {
+            descendants.extend(self.children(child));
+            index += 1;
+        }
+        descendants
+    }
+
+    /// Whether `id` still has to be placed by its `Movable`.
+    pub fn needs_placement(&self, id: WindowId) -> bool {
+        self.unplaced.contains(&id)
+    }
+
+    /// Records where `id` was placed.
+    pub fn place(&mut self, id: WindowId, position: (f64, f64)) {
+        if self.unplaced.remove(&id) {
+            let size = self.state(id).size;
+            self.states.insert(id, WindowState { position, size });
+        }
+    }
+
+    pub fn is_maximized(&self, id: WindowId) -> bool {
+        self.maximized.contains_key(&id)
+    }
+
+    /// Makes `id` fill a desktop of size `desktop`, remembering its
+    /// geometry for [`WindowManager::unmaximize`]. Child windows move along.
+    pub fn maximize(&mut self, id: WindowId, desktop: (f64, f64)) {
+        if self.get(id).is_none() || self.is_maximized(id) {
+            return;
+        }
+        let previous = self.state(id);
+        self.maximized.insert(id, previous);
+        self.set_state(
+            id,
+            WindowState {
+                position: (0.0, 0.0),
+                size: Some(desktop),
+            },
+        );
+    }
+
+    /// Restores the geometry `id` had before it was maximized.
+    pub fn unmaximize(&mut self, id: WindowId) {
+        if let Some(previous) = self.maximized.remove(&id) {
+            self.set_state(id, previous);
+        }
+    }
+
+    pub fn toggle_maximize(&mut self, id: WindowId, desktop: (f64, f64)) {
+        if self.is_maximized(id) {
+            self.unmaximize(id);
+        } else {
+            self.maximize(id, desktop);
+        }
+    }
+
+    /// Drops the maximized flag of `id` but keeps its current geometry, for
+    /// gestures that take over from there.
+    pub fn clear_maximized(&mut self, id: WindowId) {
+        self.maximized.remove(&id);
+    }
+
+    /// Moves and resizes `id` into `slot` of `layout` over a desktop of
+    /// size `desktop`, then offers the layout's other free slots to the
+    /// remaining windows through [`WindowManager::snap_assist`].
+    pub fn snap(&mut self, id: WindowId, layout: SnapLayout, slot: usize, desktop: (f64, f64)) {
+        let Some((position, size)) = snap_slot(layout, slot, desktop) else {
+            return;
+        };
+        if self.get(id).is_none() {
+            return;
+        }
+        self.maximized.remove(&id);
+        self.restore(id);
+        self.set_state(
+            id,
+            WindowState {
+                position,
+                size: Some(size),
+            },
+        );
+        self.focus(id);
+
+        let mut assist = match self.snap_assist.take() {
+            Some(assist) if assist.layout == layout && assist.desktop == desktop => assist,
+            _ => SnapAssist {
+                layout,
+                desktop,
+                free: (0..layout.slots().len()).collect(),
+                snapped: Vec::new(),
+            },
+        };
+        assist.free.retain(|&other| other != slot);
+        assist.snapped.push(id);
+        if !assist.free.is_empty() && !self.snap_candidates_of(&assist).is_empty() {
+            self.snap_assist = Some(assist);
+        }
+    }
+
+    /// Snaps `id` into the next column slot towards `direction` (see
+    /// [`next_tile`]), the keyboard counterpart of the snap layout menu.
+    pub fn tile(&mut self, id: WindowId, direction: TileDirection, desktop: (f64, f64)) {
+        let state = self.state(id);
+        let current = state
+            .size
+            .filter(|_| !self.is_maximized(id))
+            .and_then(|size| column_tile(state.position, size, desktop));
+        let (layout, slot) = next_tile(current, direction);
+        self.snap(id, layout, slot, desktop);
+    }
+
+    pub fn snap_assist(&self) -> Option<&SnapAssist> {
+        self.snap_assist.as_ref()
+    }
+
+    /// Visible windows snap assist offers for the free slots.
+    pub fn snap_candidates(&self) -> Vec<WindowId> {
+        self.snap_assist
+            .as_ref()
+            .map(|assist| self.snap_candidates_of(assist))
+            .unwrap_or_default()
+    }
+
+    fn snap_candidates_of(&self, assist: &SnapAssist) -> Vec<WindowId> {
+        self.mru
+            .iter()
+            .copied()
+            .filter(|&id| self.is_visible(id) && !assist.snapped.contains(&id))
+            .collect()
+    }
+
+    pub fn dismiss_snap_assist(&mut self) {
+        self.snap_assist = None;
+    }
+
+    pub fn active_workspace(&self) -> usize {
+        self.active_workspace
+    }
+
+    pub fn switch_workspace(&mut self, workspace: usize) {
+        if workspace < WORKSPACE_COUNT {
+            self.active_workspace = workspace;
+        }
+    }
+
+    pub fn workspace_of(&self, id: WindowId) -> usize {
+        self.workspaces.get(&id).copied().unwrap_or_default()
+    }
+
+    /// Whether `id` shows on `workspace`: it's on it or pinned to all.
+    pub fn is_on_workspace(&self, id: WindowId, workspace: usize) -> bool {
+        self.is_sticky(id) || self.workspace_of(id) == workspace
+    }
+
+    /// Whether `id` is on the active workspace and not minimized.
+    pub fn is_visible(&self, id: WindowId) -> bool {
+        self.is_on_workspace(id, self.active_workspace) && !self.minimized.contains(&id)
+    }
+
+    /// Whether `id` is pinned to every workspace.
+    pub fn is_sticky(&self, id: WindowId) -> bool {
+        self.sticky.contains(&id)
+    }
+
+    /// Pins `id` and its child windows to every workspace, or unpins them
+    /// onto the active one, where they were last seen.
+    ///
+    /// Geometry, focus and stacking order are shared by all workspaces, so
+    /// a pinned window keeps them across switches.
+    pub fn set_sticky(&mut self, id: WindowId, sticky: bool) {
+        if self.get(id).is_none() {
+            return;
+        }
+        for window in std::iter::once(id).chain(self.descendants(id)) {
+            if sticky {
+                self.sticky.insert(window);
+            } else if self.sticky.remove(&window) {
+                self.workspaces.insert(window, self.active_workspace);
+            }
+        }
+    }
+
+    pub fn is_minimized(&self, id: WindowId) -> bool {
+        self.minimized.contains(&id)
+    }
+
+    pub fn minimize(&mut self, id: WindowId) {
+        if self.get(id).is_some() {
+            self.minimized.insert(id);
+        }
+    }
+
+    pub fn restore(&mut self, id: WindowId) {
+        self.minimized.remove(&id);
+    }
+
+    /// Whether [`WindowManager::show_desktop`] is currently hiding windows.
+    pub fn showing_desktop(&self) -> bool {
+        self.desktop_shown.is_some()
+    }
+
+    /// Minimizes every visible window on the active workspace, or, when
+    /// called again, restores exactly those windows.
+    ///
+    /// Windows opened in between are left alone, so they stay visible on top
+    /// of the restored ones; windows the user restored by hand meanwhile are
+    /// simply restored again.
+    pub fn show_desktop(&mut self) {
+        match self.desktop_shown.take() {
+            Some(shown) => {
+                for id in shown {
+                    self.minimized.remove(&id);
+                }
+            }
+            None => {
+                let shown: Vec<WindowId> = self
+                    .windows
+                    .iter()
+                    .map(|window| window.id)
+                    .filter(|&id| self.is_visible(id))
+                    .collect();
+                self.minimized.extend(shown.iter().copied());
+                self.desktop_shown = Some(shown);
+            }
+        }
+    }
+
+    /// Moves `id` to `workspace`, unpinning it from the others, and
+    /// switches along with it when following moved windows is enabled.
+    pub fn move_to_workspace(&mut self, id: WindowId, workspace: usize) {
+        if workspace >= WORKSPACE_COUNT || self.get(id).is_none() {
+            return;
+        }
+        self.workspaces.insert(id, workspace);
+        self.sticky.remove(&id);
+        for child in self.descendants(id) {
+            self.workspaces.insert(child, workspace);
+            self.sticky.remove(&child);
+        }
+        if self.follow_moved {
+            self.active_workspace = workspace;
+            self.focus(id);
+        }
+    }
+
+    pub fn follow_moved(&self) -> bool {
+        self.follow_moved
+    }
+
+    pub fn set_follow_moved(&mut self, follow: bool) {
+        self.follow_moved = follow;
+    }
+
+    pub fn drag_target(&self) -> Option<usize> {
+        self.drag_target
+    }
+
+    pub fn set_drag_target(&mut self, workspace: Option<usize>) {
+        self.drag_target = workspace;
+    }
+
+    /// Drops `id` on the workspace indicator under it, if any, moving it to
+    /// that workspace, and clears the drag target.
+    pub fn drop_on_drag_target(&mut self, id: WindowId) {
+        if let Some(workspace) = self.drag_target.take() {
+            self.move_to_workspace(id, workspace);
+        }
+    }
+
+    /// Window ids ordered from most to least recently focused.
+    pub fn mru(&self) -> &[WindowId] {
+        &self.mru
+    }
+
+    pub fn focused(&self) -> Option<WindowId> {
+        self.mru.first().copied()
+    }
+
+    /// Focuses `id`. A window with child windows can't come above them, so
+    /// they are raised after it and the last of them ends up focused.
+    pub fn focus(&mut self, id: WindowId) {
+        self.flashing.remove(&id);
+        for window in std::iter::once(id).chain(self.descendants(id)) {
+            if let Some(index) = self.mru.iter().position(|&other| other == window) {
+                self.mru.remove(index);
+                self.mru.insert(0, window);
+            }
+        }
+    }
+
+    pub fn focus_policy(&self) -> FocusPolicy {
+        self.focus_policy
+    }
+
+    pub fn set_focus_policy(&mut self, policy: FocusPolicy) {
+        self.focus_policy = policy;
+    }
+
+    /// Whether `hover` over `id` should focus it at `now`, under the focus
+    /// policy. Windows aren't focused by hovering while the switcher is open.
+    pub fn wants_hover_focus(&self, id: WindowId, hover: &HoverFocus, now: f64) -> bool {
+        !self.switching()
+            && self.focused() != Some(id)
+            && self.is_visible(id)
+            && hover.due(self.focus_policy).is_some_and(|due| due <= now)
+    }
+
+    /// Window ids in taskbar order. New windows are appended.
+    pub fn taskbar(&self) -> &[WindowId] {
+        &self.taskbar
+    }
+
+    /// Moves the taskbar entry of `id` to `index`, clamped to the taskbar's
+    /// length once the entry has been taken out of its old place.
+    pub fn reorder_taskbar(&mut self, id: WindowId, index: usize) {
+        let Some(from) = self.taskbar.iter().position(|&other| other == id) else {
+            return;
+        };
+        self.taskbar.remove(from);
+        self.taskbar.insert(index.min(self.taskbar.len()), id);
+    }
+
+    /// Puts the windows in `order` first on the taskbar, in that order,
+    /// followed by the others as they were.
+    pub fn set_taskbar_order(&mut self, order: &[WindowId]) {
+        let known: Vec<WindowId> = order
+            .iter()
+            .copied()
+            .filter(|id| self.taskbar.contains(id))
+            .collect();
+        self.taskbar.retain(|id| !known.contains(id));
+        self.taskbar.splice(0..0, known);
+    }
+
+    /// Briefly pulses the frame and taskbar entry of `id` to draw the user's
+    /// attention. The window's `Movable` stops the flash after a few pulses;
+    /// focusing the window stops it right away. Focused windows don't flash.
+    pub fn flash(&mut self, id: WindowId) {
+        if self.get(id).is_some() && self.focused() != Some(id) {
+            self.flashing.insert(id, true);
+        }
+    }
+
+    pub fn is_flashing(&self, id: WindowId) -> bool {
+        self.flashing.contains_key(&id)
+    }
+
+    /// Whether the flash highlight of `id` is showing right now.
+    pub fn flash_lit(&self, id: WindowId) -> bool {
+        self.flashing.get(&id).copied().unwrap_or(false)
+    }
+
+    pub fn set_flash_lit(&mut self, id: WindowId, lit: bool) {
+        if let Some(current) = self.flashing.get_mut(&id) {
+            *current = lit;
+        }
+    }
+
+    pub fn stop_flash(&mut self, id: WindowId) {
+        self.flashing.remove(&id);
+    }
+
+    /// Stacking order of `id`: the focused window is on top and the rest
+    /// follow their focus history.
+    pub fn z_index(&self, id: WindowId) -> usize {
+        self.mru
+            .iter()
+            .position(|&other| other == id)
+            .map_or(0, |index| self.mru.len() - index)
+    }
+
+    /// Opens the switcher on the previously focused window, or moves its
+    /// highlight one step further back in the focus history.
+    pub fn next(&mut self) {
+        if self.mru.is_empty() {
+            return;
+        }
+        let len = self.mru.len();
+        self.switcher = Some(match self.switcher {
+            Some(index) => (index + 1) % len,
+            None => 1 % len,
+        });
+    }
+
+    /// Like [`WindowManager::next`], walking the focus history the other way.
+    pub fn prev(&mut self) {
+        if self.mru.is_empty() {
+            return;
+        }
+        let len = self.mru.len();
+        self.switcher = Some(match self.switcher {
+            Some(index) => (index + len - 1) % len,
+            None => len - 1,
+        });
+    }
+
+    pub fn switching(&self) -> bool {
+        self.switcher.is_some()
+    }
+
+    /// Window highlighted by the switcher, if it's open.
+    pub fn highlighted(&self) -> Option<WindowId> {
+        self.switcher.and_then(|index| self.mru.get(index).copied())
+    }
+
+    /// Closes the switcher, focusing (and restoring) the highlighted window.
+    pub fn commit_switch(&mut self) {
+        if let Some(id) = self.highlighted() {
+            self.restore(id);
+            self.focus(id);
+        }
+        self.switcher = None;
+    }
+
+    pub fn cancel_switch(&mut self) {
+        self.switcher = None;
+    }
+}
+
+pub fn use_window_manager() -> Signal<WindowManager> {
+    use_context()
+}
+
+/// Alt+Tab overlay listing the windows in focus order.
+#[component]
+pub fn WindowSwitcher() -> Element {
+    let manager = use_window_manager();
+    let manager = manager.read();
+
+    if !manager.switching() {
+        return rsx! {};
+    }
+
+    let highlighted = manager.highlighted();
+    let entries: Vec<WindowHandle> = manager
+        .mru()
+        .iter()
+        .filter_map(|&id| manager.get(id).cloned())
+        .collect();
+
+    rsx! {
+        div {
+            class: "fixed inset-0 flex items-center justify-center pointer-events-none",
+            style: "z-index: 10000;",
+            div { class: "flex gap-2 p-3 rounded-lg bg-slate-800/90 shadow-lg",
+                for entry in entries {
+                    div {
+                        key: "{entry.id}",
+                        class: if highlighted == Some(entry.id) {
+                            "px-4 py-2 rounded-md bg-slate-600 text-white"
+                        } else {
+                            "px-4 py-2 rounded-md text-slate-300"
+                        },
+                        "{entry.title}"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Workspace indicator of the [`WorkspaceSwitcher`] at `point`, in client
+/// coordinates.
+///
+/// Looks through every element at the point, since the window being dragged
+/// is usually the topmost one.
+pub fn workspace_at(point: (f64, f64)) -> Option<usize> {
+    browser::attribute_at(point, "data-workspace")?.parse().ok()
+}
+
+/// Row of workspace indicators. Clicking one switches to it; dropping a
+/// window on one moves the window there, and switches along with it while
+/// the trailing pin is toggled on.
+#[component]
+pub fn WorkspaceSwitcher() -> Element {
+    let mut manager = use_window_manager();
+    let active = manager.read().active_workspace();
+    let target = manager.read().drag_target();
+    let follow = manager.read().follow_moved();
+
+    rsx! {
+        div {
+            class: "fixed bottom-3 left-1/2 -translate-x-1/2 flex gap-2 rounded-lg bg-slate-800/80 p-2",
+            style: "z-index: 9000;",
+            for workspace in 0..WORKSPACE_COUNT {
+                button {
+                    key: "{workspace}",
+                    "data-workspace": "{workspace}",
+                    class: match (target == Some(workspace), workspace == active) {
+                        (true, _) => "h-8 w-10 rounded-md bg-sky-500 text-white ring-2 ring-sky-300",
+                        (false, true) => "h-8 w-10 rounded-md bg-slate-500 text-white",
+                        (false, false) => "h-8 w-10 rounded-md bg-slate-700 text-slate-300",
+                    },
+                    onclick: move |_| manager.write().switch_workspace(workspace),
+                    "{workspace + 1}"
+                }
+            }
+            button {
+                class: if follow {
+                    "h-8 w-8 rounded-md bg-sky-600 text-white"
+                } else {
+                    "h-8 w-8 rounded-md text-slate-400 hover:bg-slate-700"
+                },
+                title: "Follow windows to the workspace they're dropped on",
+                aria_pressed: follow,
+                onclick: move |_| manager.write().set_follow_moved(!follow),
+                "⇥"
+            }
+        }
+    }
+}
+
+/// Corner button toggling [`WindowManager::show_desktop`], also bound to
+/// Super+D.
+#[component]
+pub fn ShowDesktopButton() -> Element {
+    let mut manager = use_window_manager();
+    use_shortcut("Super+D", ShortcutScope::Global, move || {
+        manager.write().show_desktop()
+    });
+
+    rsx! {
+        button {
+            class: if manager.read().showing_desktop() {
+                "fixed bottom-0 right-0 h-10 w-2 border-l border-slate-400/60 bg-sky-400/40"
+            } else {
+                "fixed bottom-0 right-0 h-10 w-2 border-l border-slate-400/60 hover:bg-slate-300/40"
+            },
+            style: "z-index: 9000;",
+            title: "Show desktop",
+            onclick: move |_| manager.write().show_desktop(),
+        }
+    }
+}
+
+/// Controls for the [`FocusPolicy`].
+#[component]
+pub fn FocusSettings() -> Element {
+    let mut manager = use_window_manager();
+    let policy = manager.read().focus_policy();
+
+    rsx! {
+        div { class: "flex flex-col gap-2",
+            div { class: "flex gap-1",
+                for mode in FocusMode::ALL {
+                    button {
+                        key: "{mode:?}",
+                        class: if policy.mode == mode {
+                            "rounded-md bg-slate-500 px-2 py-1 text-xs text-white"
+                        } else {
+                            "rounded-md bg-slate-100 px-2 py-1 text-xs text-slate-600"
+                        },
+                        onclick: move |_| {
+                            manager.write().set_focus_policy(FocusPolicy { mode, ..policy });
+                        },
+                        "{mode.name()}"
+                    }
+                }
+            }
+            if policy.mode != FocusMode::Click {
+                NumberInput {
+                    label: "Hover delay (ms)",
+                    value: policy.focus_hover_delay_ms as f64,
+                    min: 0.0,
+                    max: 1000.0,
+                    step: 50.0,
+                    onchange: move |value: f64| {
+                        manager.write().set_focus_policy(FocusPolicy {
+                            focus_hover_delay_ms: value as u32,
+                            ..policy
+                        });
+                    },
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn focusing_moves_a_window_to_the_front_of_the_mru_order() {
+        let mut manager = WindowManager::default();
+        let ids: Vec<WindowId> = ["a", "b", "c"]
+            .into_iter()
+            .map(|title| manager.open(title).id)
+            .collect();
+        assert_eq!(manager.mru(), [ids[2], ids[1], ids[0]]);
+
+        manager.focus(ids[0]);
+        assert_eq!(manager.mru(), [ids[0], ids[2], ids[1]]);
+        assert_eq!(manager.focused(), Some(ids[0]));
+
+        manager.close(ids[0]);
+        assert_eq!(manager.mru(), [ids[2], ids[1]]);
+    }
+
+    #[test]
+    fn the_switcher_walks_the_mru_order() {
+        let mut manager = WindowManager::default();
+        let ids: Vec<WindowId> = ["a", "b", "c"]
+            .into_iter()
+            .map(|title| manager.open(title).id)
+            .collect();
+
+        // Opens on the previously focused window, then wraps around.
+        manager.next();
+        assert_eq!(manager.highlighted(), Some(ids[1]));
+        manager.next();
+        assert_eq!(manager.highlighted(), Some(ids[0]));
+        manager.next();
+        assert_eq!(manager.highlighted(), Some(ids[2]));
+        manager.prev();
+        assert_eq!(manager.highlighted(), Some(ids[0]));
+
+        manager.commit_switch();
+        assert!(!manager.switching());
+        assert_eq!(manager.mru(), [ids[0], ids[2], ids[1]]);
+
+        manager.prev();
+        manager.cancel_switch();
+        assert_eq!(manager.focused(), Some(ids[0]));
+    }
+
+    #[test]
+    fn maximizing_fills_the_desktop_container_not_the_viewport() {
+        let mut manager = WindowManager::default();
+        let before = WindowState {
+            position: (900.0, 700.0),
+            size: Some((400.0, 300.0)),
+        };
+        let id = manager.open_app("notes", "Notes", before).id;
+        // A desktop container smaller than the browser window, with the
+        // window lying outside of it.
+        let desktop = (800.0, 600.0);
+
+        manager.maximize(id, desktop);
+        assert!(manager.is_maximized(id));
+        assert_eq!(
+            manager.state(id),
+            WindowState {
+                position: (0.0, 0.0),
+                size: Some(desktop),
+            }
+        );
+
+        manager.toggle_maximize(id, desktop);
+        assert!(!manager.is_maximized(id));
+        assert_eq!(manager.state(id), before);
+
+        manager.snap(id, SnapLayout::Halves, 1, desktop);
+        assert_eq!(
+            manager.state(id),
+            WindowState {
+                position: (400.0, 0.0),
+                size: Some((400.0, 600.0)),
+            }
+        );
+    }
+
+    #[test]
+    fn sticky_windows_stay_visible_across_workspace_switches() {
+        let mut manager = WindowManager::default();
+        let pinned = manager.open("pinned").id;
+        let child = manager.open_child(pinned, "child").id;
+        let other = manager.open("other").id;
+        manager.set_sticky(pinned, true);
+        assert!(manager.is_sticky(child));
+
+        manager.switch_workspace(2);
+        assert!(manager.is_visible(pinned));
+        assert!(manager.is_visible(child));
+        assert!(!manager.is_visible(other));
+
+        // Minimized pinned windows stay hidden everywhere.
+        manager.minimize(pinned);
+        assert!(!manager.is_visible(pinned));
+        manager.restore(pinned);
+
+        // Unpinning leaves them on the workspace they were last seen on.
+        manager.set_sticky(pinned, false);
+        assert_eq!(manager.workspace_of(pinned), 2);
+        assert_eq!(manager.workspace_of(child), 2);
+        manager.switch_workspace(0);
+        assert!(!manager.is_visible(pinned));
+        assert!(manager.is_visible(other));
+
+        // Moving a pinned window to a workspace unpins it.
+        manager.set_sticky(other, true);
+        manager.move_to_workspace(other, 3);
+        assert!(!manager.is_sticky(other));
+        assert!(!manager.is_visible(other));
+    }
+
+    #[test]
+    fn dropping_a_window_on_a_workspace_moves_it_there() {
+        let mut manager = WindowManager::default();
+        let parent = manager.open("parent").id;
+        let child = manager.open_child(parent, "child").id;
+        let other = manager.open("other").id;
+
+        manager.set_drag_target(Some(2));
+        manager.drop_on_drag_target(parent);
+        assert_eq!(manager.drag_target(), None);
+        assert_eq!(manager.workspace_of(parent), 2);
+        assert_eq!(manager.workspace_of(child), 2);
+        // Without following, the desktop stays where it was.
+        assert_eq!(manager.active_workspace(), 0);
+        assert!(!manager.is_visible(parent));
+
+        // Dropped anywhere else, the window stays.
+        manager.drop_on_drag_target(other);
+        assert_eq!(manager.workspace_of(other), 0);
+
+        manager.set_follow_moved(true);
+        manager.set_drag_target(Some(3));
+        manager.drop_on_drag_target(other);
+        assert_eq!(manager.active_workspace(), 3);
+        assert_eq!(manager.focused(), Some(other));
+        assert!(manager.is_visible(other));
+    }
+
+    #[test]
+    fn show_desktop_hides_the_visible_windows_and_restores_exactly_them() {
+        let mut manager = WindowManager::default();
+        let [a, b, hidden] = ["a", "b", "hidden"].map(|title| manager.open(title).id);
+        let elsewhere = manager.open("elsewhere").id;
+        manager.move_to_workspace(elsewhere, 1);
+        manager.minimize(hidden);
+
+        manager.show_desktop();
+        assert!(manager.showing_desktop());
+        assert!([a, b, hidden].iter().all(|&id| manager.is_minimized(id)));
+        // Only the active workspace is cleared.
+        assert!(!manager.is_minimized(elsewhere));
+
+        // A window opened meanwhile stays put when the desktop comes back.
+        let opened = manager.open("opened").id;
+        manager.show_desktop();
+        assert!(!manager.showing_desktop());
+        assert!(manager.is_visible(a) && manager.is_visible(b));
+        assert!(manager.is_visible(opened));
+        // Minimized before, so not restored.
+        assert!(manager.is_minimized(hidden));
+    }
+
+    #[test]
+    fn taskbar_entries_can_be_reordered() {
+        let mut manager = WindowManager::default();
+        let [a, b, c] = ["a", "b", "c"].map(|title| manager.open(title).id);
+        assert_eq!(manager.taskbar(), [a, b, c]);
+
+        manager.reorder_taskbar(a, 2);
+        assert_eq!(manager.taskbar(), [b, c, a]);
+        // Past the end means last.
+        manager.reorder_taskbar(b, 10);
+        assert_eq!(manager.taskbar(), [c, a, b]);
+
+        // A saved order, which may name windows that are gone, goes first.
+        manager.set_taskbar_order(&[b, 42, a]);
+        assert_eq!(manager.taskbar(), [b, a, c]);
+        // Focus doesn't reorder the taskbar.
+        manager.focus(c);
+        assert_eq!(manager.taskbar(), [b, a, c]);
+    }
+
+    #[test]
+    fn focusing_a_flashing_window_stops_the_flash() {
+        let mut manager = WindowManager::default();
+        let id = manager.open("a").id;
+        let focused = manager.open("b").id;
+
+        // The focused window already has the user's attention.
+        manager.flash(focused);
+        assert!(!manager.is_flashing(focused));
+
+        manager.flash(id);
+        assert!(manager.is_flashing(id));
+        assert!(manager.flash_lit(id));
+        manager.set_flash_lit(id, false);
+        assert!(!manager.flash_lit(id));
+
+        manager.focus(id);
+        assert!(!manager.is_flashing(id));
+    }
+
+    #[test]
+    fn children_follow_their_parent_and_close_with_it() {
+        let mut manager = WindowManager::default();
+        let start = WindowState {
+            position: (100.0, 100.0),
+            size: Some((400.0, 300.0)),
+        };
+        let parent = manager.open_app("notes", "Notes", start).id;
+        let child = manager.open_child(parent, "Find").id;
+        let grandchild = manager.open_child(child, "Options").id;
+        let position = |manager: &WindowManager, id| manager.state(id).position;
+        assert_eq!(position(&manager, child), (132.0, 132.0));
+        assert_eq!(position(&manager, grandchild), (164.0, 164.0));
+
+        // Children stay above their parent when it's focused.
+        manager.focus(parent);
+        assert_eq!(manager.mru()[..3], [grandchild, child, parent]);
+
+        manager.set_state(
+            parent,
+            WindowState {
+                position: (150.0, 80.0),
+                ..start
+            },
+        );
+        assert_eq!(position(&manager, child), (182.0, 112.0));
+        assert_eq!(position(&manager, grandchild), (214.0, 144.0));
+
+        // Maximizing and restoring move them along too.
+        manager.maximize(parent, (800.0, 600.0));
+        assert_eq!(position(&manager, child), (32.0, 32.0));
+        manager.unmaximize(parent);
+        assert_eq!(position(&manager, child), (182.0, 112.0));
+        manager.snap(parent, SnapLayout::Halves, 1, (800.0, 600.0));
+        assert_eq!(position(&manager, child), (432.0, 32.0));
+
+        manager.close(parent);
+        assert!(manager.windows().is_empty());
+        assert!(manager.mru().is_empty());
+    }
+
+    #[test]
+    fn hovering_focuses_windows_according_to_the_policy() {
+        let mut manager = WindowManager::default();
+        let hovered = manager.open("hovered").id;
+        manager.open("focused");
+        let mut hover = HoverFocus::default();
+        assert_eq!(hover.due(FocusPolicy::default()), None);
+        hover.enter(1000.0);
+        hover.moved(1100.0);
+
+        let policy = |mode, delay| FocusPolicy {
+            mode,
+            focus_hover_delay_ms: delay,
+        };
+        assert_eq!(hover.due(policy(FocusMode::Click, 150)), None);
+        // Counted from entering the window...
+        assert_eq!(
+            hover.due(policy(FocusMode::FollowsPointer, 150)),
+            Some(1150.0)
+        );
+        // ...or from the pointer's last move, resting at least a little.
+        assert_eq!(hover.due(policy(FocusMode::Sloppy, 150)), Some(1250.0));
+        assert_eq!(hover.due(policy(FocusMode::Sloppy, 0)), Some(1220.0));
+
+        manager.set_focus_policy(policy(FocusMode::Sloppy, 150));
+        assert!(!manager.wants_hover_focus(hovered, &hover, 1200.0));
+        assert!(manager.wants_hover_focus(hovered, &hover, 1250.0));
+        // Not while switching windows with the keyboard.
+        manager.next();
+        assert!(!manager.wants_hover_focus(hovered, &hover, 1250.0));
+        manager.cancel_switch();
+
+        hover.leave();
+        assert!(!manager.wants_hover_focus(hovered, &hover, 5000.0));
+    }
+}